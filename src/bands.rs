@@ -0,0 +1,241 @@
+//! Band and level descriptors carrying custom feature properties
+//!
+//! Plain thresholds produce features with only `lower_level`/`upper_level` or
+//! `isovalue`. A [`BandDescriptor`] or [`LevelDescriptor`] can be passed in
+//! their place to attach units, labels, fill colors, z-order, product ids or
+//! any other JSON properties, plus an optional feature id. The extra
+//! properties are merged into the feature after the level properties, so a
+//! descriptor may also override them.
+//!
+//! Descriptors travel with their band through (parallel) generation, so each
+//! feature always carries its own descriptor's properties. Empty bands are
+//! still dropped, and the remaining features keep descriptor order.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::bands::BandDescriptor;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//! use serde_json::json;
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//!
+//! let bands = BandDescriptor::from_thresholds(&[12.0, 18.0, 22.0], |index, lower, upper| {
+//!     let mut properties = serde_json::Map::new();
+//!     properties.insert("label".into(), json!(format!("{lower}-{upper} °C")));
+//!     properties.insert("z_order".into(), json!(index));
+//!     properties
+//! });
+//! let features = grid.isobands_described(&bands)?;
+//! assert_eq!(features[0].property("label"), Some(&json!("12-18 °C")));
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+pub use geojson::feature::Id;
+use geojson::{Feature, JsonObject, JsonValue};
+
+/// An isoband with its own feature properties
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandDescriptor {
+    /// Lower threshold of the band
+    pub lower: f64,
+    /// Upper threshold of the band
+    pub upper: f64,
+    /// Feature id, if any
+    pub id: Option<Id>,
+    /// Properties merged into the band's feature
+    pub properties: JsonObject,
+}
+
+impl BandDescriptor {
+    /// Describe the band between `lower` and `upper` with no extra properties
+    pub fn new(lower: f64, upper: f64) -> Self {
+        Self {
+            lower,
+            upper,
+            id: None,
+            properties: JsonObject::new(),
+        }
+    }
+
+    /// Describe the bands between consecutive thresholds, building each
+    /// band's properties with `properties(index, lower, upper)`
+    pub fn from_thresholds<F>(thresholds: &[f64], properties: F) -> Vec<Self>
+    where
+        F: Fn(usize, f64, f64) -> JsonObject,
+    {
+        thresholds
+            .windows(2)
+            .enumerate()
+            .map(|(index, pair)| Self {
+                properties: properties(index, pair[0], pair[1]),
+                ..Self::new(pair[0], pair[1])
+            })
+            .collect()
+    }
+
+    /// Set the feature id
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Add one property
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// Merge this descriptor's id and properties into a generated feature
+    pub(crate) fn apply(&self, feature: Feature) -> Feature {
+        apply(feature, &self.id, &self.properties)
+    }
+}
+
+/// An isoline level with its own feature properties
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelDescriptor {
+    /// Contour level
+    pub level: f64,
+    /// Feature id, if any
+    pub id: Option<Id>,
+    /// Properties merged into the level's feature
+    pub properties: JsonObject,
+}
+
+impl LevelDescriptor {
+    /// Describe `level` with no extra properties
+    pub fn new(level: f64) -> Self {
+        Self {
+            level,
+            id: None,
+            properties: JsonObject::new(),
+        }
+    }
+
+    /// Describe each level, building its properties with
+    /// `properties(index, level)`
+    pub fn from_levels<F>(levels: &[f64], properties: F) -> Vec<Self>
+    where
+        F: Fn(usize, f64) -> JsonObject,
+    {
+        levels
+            .iter()
+            .enumerate()
+            .map(|(index, &level)| Self {
+                properties: properties(index, level),
+                ..Self::new(level)
+            })
+            .collect()
+    }
+
+    /// Set the feature id
+    pub fn with_id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Add one property
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// Merge this descriptor's id and properties into a generated feature
+    pub(crate) fn apply(&self, feature: Feature) -> Feature {
+        apply(feature, &self.id, &self.properties)
+    }
+}
+
+fn apply(mut feature: Feature, id: &Option<Id>, properties: &JsonObject) -> Feature {
+    if id.is_some() {
+        feature.id = id.clone();
+    }
+    feature
+        .properties
+        .get_or_insert_with(JsonObject::new)
+        .extend(properties.iter().map(|(k, v)| (k.clone(), v.clone())));
+    feature
+}
+
+/// Check band descriptors: at least one, each with `lower < upper`
+pub(crate) fn validate_bands(bands: &[BandDescriptor]) -> Result<()> {
+    if bands.is_empty() {
+        return Err(Error::invalid_thresholds(
+            "At least 1 band required for isobands",
+        ));
+    }
+    for band in bands {
+        // Also rejects NaN thresholds
+        if band.lower.partial_cmp(&band.upper) != Some(std::cmp::Ordering::Less) {
+            return Err(Error::invalid_thresholds(format!(
+                "Band lower threshold must be below its upper threshold, got {} to {}",
+                band.lower, band.upper
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check level descriptors: at least one
+pub(crate) fn validate_level_descriptors(levels: &[LevelDescriptor]) -> Result<()> {
+    if levels.is_empty() {
+        return Err(Error::invalid_thresholds(
+            "At least 1 level required for isolines",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_thresholds_and_apply() {
+        let bands = BandDescriptor::from_thresholds(&[0.0, 10.0, 20.0], |index, lower, _| {
+            let mut properties = JsonObject::new();
+            properties.insert("index".into(), json!(index));
+            properties.insert("lower_level".into(), json!(format!("{lower} mm")));
+            properties
+        });
+        assert_eq!(bands.len(), 2);
+        assert_eq!((bands[1].lower, bands[1].upper), (10.0, 20.0));
+
+        let feature = Feature {
+            properties: Some(JsonObject::from_iter([
+                ("lower_level".to_string(), json!(10.0)),
+                ("upper_level".to_string(), json!(20.0)),
+            ])),
+            ..Feature::default()
+        };
+        let feature = bands[1]
+            .clone()
+            .with_id(Id::String("band-1".into()))
+            .apply(feature);
+        assert_eq!(feature.id, Some(Id::String("band-1".into())));
+        assert_eq!(feature.property("index"), Some(&json!(1)));
+        assert_eq!(feature.property("lower_level"), Some(&json!("10 mm")));
+        assert_eq!(feature.property("upper_level"), Some(&json!(20.0)));
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(validate_bands(&[]).is_err());
+        assert!(validate_bands(&[BandDescriptor::new(5.0, 5.0)]).is_err());
+        assert!(validate_bands(&[BandDescriptor::new(f64::NAN, 5.0)]).is_err());
+        // Bands need not be contiguous
+        assert!(validate_bands(&[
+            BandDescriptor::new(0.0, 5.0),
+            BandDescriptor::new(10.0, 15.0)
+        ])
+        .is_ok());
+        assert!(validate_level_descriptors(&[]).is_err());
+        assert!(validate_level_descriptors(&[LevelDescriptor::new(1.0)]).is_ok());
+    }
+}
@@ -15,6 +15,7 @@ mod saddles;
 mod square;
 
 use crate::interpolation::interpolate_with_method;
+use crate::report::CellClass;
 use crate::types::{Edge, GridPoint, InterpolationMethod, Point, Side};
 use std::collections::HashMap;
 use std::fmt;
@@ -72,7 +73,8 @@ impl CellShape {
         Self { edges }
     }
 
-    /// Create edges for this cell configuration using full 81-case logic from Java
+    /// Create edges for this cell configuration using full 81-case logic from Java,
+    /// along with the shape class the configuration dispatched to
     pub fn from_config(
         config: CellConfig,
         tl: &GridPoint,
@@ -87,10 +89,10 @@ impl CellShape {
         is_right_edge: bool,
         is_bottom_edge: bool,
         is_left_edge: bool,
-    ) -> Option<Self> {
+    ) -> (CellClass, Option<Self>) {
         // Empty cells (all below or all above)
         if config == 0 || config == 170 {
-            return None;
+            return (CellClass::Empty, None);
         }
 
         // Get corner points and values
@@ -247,75 +249,697 @@ impl CellShape {
         let mut edges = Vec::new();
 
         // Route to appropriate shape handler based on config value
-        match config {
+        let class = match config {
             // Triangle cases (8 total)
-            169 | 1 => triangle_bl(&mut edges, &points, is_bottom_edge, is_left_edge),
-            166 | 4 => triangle_br(&mut edges, &points, is_right_edge, is_bottom_edge),
-            154 | 16 => triangle_tr(&mut edges, &points, is_right_edge, is_top_edge),
-            106 | 64 => triangle_tl(&mut edges, &points, is_left_edge, is_top_edge),
+            169 | 1 => {
+                triangle_bl(&mut edges, &points, is_bottom_edge, is_left_edge);
+                CellClass::Triangle
+            }
+            166 | 4 => {
+                triangle_br(&mut edges, &points, is_right_edge, is_bottom_edge);
+                CellClass::Triangle
+            }
+            154 | 16 => {
+                triangle_tr(&mut edges, &points, is_right_edge, is_top_edge);
+                CellClass::Triangle
+            }
+            106 | 64 => {
+                triangle_tl(&mut edges, &points, is_left_edge, is_top_edge);
+                CellClass::Triangle
+            }
 
             // Pentagon cases (24 total)
-            101 | 69 => pentagon_101(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            149 | 21 => pentagon_149(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            86 | 84 => pentagon_86(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            89 | 81 => pentagon_89(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            96 | 74 => pentagon_96(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            24 | 146 => pentagon_24(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            6 | 164 => pentagon_6(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            129 | 41 => pentagon_129(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            66 | 104 => pentagon_66(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            144 | 26 => pentagon_144(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            36 | 134 => pentagon_36(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            9 | 161 => pentagon_9(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
+            101 | 69 => {
+                pentagon_101(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            149 | 21 => {
+                pentagon_149(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            86 | 84 => {
+                pentagon_86(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            89 | 81 => {
+                pentagon_89(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            96 | 74 => {
+                pentagon_96(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            24 | 146 => {
+                pentagon_24(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            6 | 164 => {
+                pentagon_6(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            129 | 41 => {
+                pentagon_129(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            66 | 104 => {
+                pentagon_66(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            144 | 26 => {
+                pentagon_144(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            36 | 134 => {
+                pentagon_36(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
+            9 | 161 => {
+                pentagon_9(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Pentagon
+            }
 
             // Rectangle cases (12 total)
-            5 | 165 => rectangle_5(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            20 | 150 => rectangle_20(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            80 | 90 => rectangle_80(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            65 | 105 => rectangle_65(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            160 | 10 => rectangle_160(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            130 | 40 => rectangle_130(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
+            5 | 165 => {
+                rectangle_5(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Rectangle
+            }
+            20 | 150 => {
+                rectangle_20(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Rectangle
+            }
+            80 | 90 => {
+                rectangle_80(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Rectangle
+            }
+            65 | 105 => {
+                rectangle_65(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Rectangle
+            }
+            160 | 10 => {
+                rectangle_160(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Rectangle
+            }
+            130 | 40 => {
+                rectangle_130(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Rectangle
+            }
 
             // Trapezoid cases (8 total)
-            168 | 2 => trapezoid_168(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            162 | 8 => trapezoid_162(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            138 | 32 => trapezoid_138(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            42 | 128 => trapezoid_42(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
+            168 | 2 => {
+                trapezoid_168(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Trapezoid
+            }
+            162 | 8 => {
+                trapezoid_162(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Trapezoid
+            }
+            138 | 32 => {
+                trapezoid_138(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Trapezoid
+            }
+            42 | 128 => {
+                trapezoid_42(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Trapezoid
+            }
 
             // Hexagon cases (12 total)
-            37 | 133 => hexagon_37(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            148 | 22 => hexagon_148(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            82 | 88 => hexagon_82(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            73 | 97 => hexagon_73(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            145 | 25 => hexagon_145(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
-            70 | 100 => hexagon_70(&mut edges, &points, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge),
+            37 | 133 => {
+                hexagon_37(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Hexagon
+            }
+            148 | 22 => {
+                hexagon_148(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Hexagon
+            }
+            82 | 88 => {
+                hexagon_82(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Hexagon
+            }
+            73 | 97 => {
+                hexagon_73(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Hexagon
+            }
+            145 | 25 => {
+                hexagon_145(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Hexagon
+            }
+            70 | 100 => {
+                hexagon_70(
+                    &mut edges,
+                    &points,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                );
+                CellClass::Hexagon
+            }
 
             // Saddle cases (14 total) - these are complex with average calculations
-            153 => saddle_153(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            102 => saddle_102(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            68 => saddle_68(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            17 => saddle_17(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            136 => saddle_136(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            34 => saddle_34(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            152 => saddle_152(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            18 => saddle_18(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            137 => saddle_137(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            33 => saddle_33(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            98 => saddle_98(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            72 => saddle_72(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            38 => saddle_38(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
-            132 => saddle_132(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
+            153 => {
+                saddle_153(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            102 => {
+                saddle_102(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            68 => {
+                saddle_68(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            17 => {
+                saddle_17(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            136 => {
+                saddle_136(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            34 => {
+                saddle_34(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            152 => {
+                saddle_152(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            18 => {
+                saddle_18(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            137 => {
+                saddle_137(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            33 => {
+                saddle_33(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            98 => {
+                saddle_98(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            72 => {
+                saddle_72(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            38 => {
+                saddle_38(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
+            132 => {
+                saddle_132(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Saddle
+            }
 
             // Square case (1 total)
-            85 => square_85(&mut edges, &tl_pt, &tr_pt, &br_pt, &bl_pt, tl_val, tr_val, br_val, bl_val, lower, upper, smoothing, is_top_edge, is_right_edge, is_bottom_edge, is_left_edge, &interp, &get_edge_point),
+            85 => {
+                square_85(
+                    &mut edges,
+                    &tl_pt,
+                    &tr_pt,
+                    &br_pt,
+                    &bl_pt,
+                    tl_val,
+                    tr_val,
+                    br_val,
+                    bl_val,
+                    lower,
+                    upper,
+                    smoothing,
+                    is_top_edge,
+                    is_right_edge,
+                    is_bottom_edge,
+                    is_left_edge,
+                    &interp,
+                    &get_edge_point,
+                );
+                CellClass::Square
+            }
 
-            _ => return None,
-        }
+            _ => return (CellClass::Empty, None),
+        };
 
         if edges.is_empty() {
-            None
+            (class, None)
         } else {
-            Some(Self::new(edges))
+            (class, Some(Self::new(edges)))
         }
     }
 }
@@ -333,11 +957,41 @@ mod tests {
         let bl = GridPoint::new(0.0, 0.0, 0.0);
 
         // All below lower
-        let result = CellShape::from_config(0, &tl, &tr, &br, &bl, 5.0, 10.0, 0.999, InterpolationMethod::Cosine, false, false, false, false);
+        let result = CellShape::from_config(
+            0,
+            &tl,
+            &tr,
+            &br,
+            &bl,
+            5.0,
+            10.0,
+            0.999,
+            InterpolationMethod::Cosine,
+            false,
+            false,
+            false,
+            false,
+        )
+        .1;
         assert!(result.is_none());
 
         // All above upper
-        let result = CellShape::from_config(170, &tl, &tr, &br, &bl, 5.0, 10.0, 0.999, InterpolationMethod::Cosine, false, false, false, false);
+        let result = CellShape::from_config(
+            170,
+            &tl,
+            &tr,
+            &br,
+            &bl,
+            5.0,
+            10.0,
+            0.999,
+            InterpolationMethod::Cosine,
+            false,
+            false,
+            false,
+            false,
+        )
+        .1;
         assert!(result.is_none());
     }
 
@@ -349,7 +1003,22 @@ mod tests {
         let bl = GridPoint::new(0.0, 0.0, 4.0);
 
         // Config 169 (2221) - all above upper except BL between
-        let result = CellShape::from_config(169, &tl, &tr, &br, &bl, 5.0, 10.0, 0.999, InterpolationMethod::Cosine, false, false, false, false);
+        let result = CellShape::from_config(
+            169,
+            &tl,
+            &tr,
+            &br,
+            &bl,
+            5.0,
+            10.0,
+            0.999,
+            InterpolationMethod::Cosine,
+            false,
+            false,
+            false,
+            false,
+        )
+        .1;
         assert!(result.is_some());
         let shape = result.unwrap();
         assert!(shape.edges.len() > 0);
@@ -0,0 +1,413 @@
+//! Axis-aligned rectangle clipping for rings and polylines
+//!
+//! Polygon rings are clipped with Sutherland–Hodgman, which keeps a single ring
+//! and closes it along the rectangle boundary; this suits vector tile clipping.
+//! Band polygons are instead intersected exactly with the box as a
+//! [`ClipMask`](crate::mask::ClipMask). Polylines are clipped segment by
+//! segment with Liang–Barsky and split into pieces where they leave the box.
+//!
+//! [`CellWindow`] restricts contouring to the cells covering a clip box, for
+//! [`MarchingSquaresConfig::clip_bbox`](crate::MarchingSquaresConfig::clip_bbox).
+
+use crate::error::{Error, Result};
+use crate::grid::GridSource;
+use std::ops::Range;
+
+/// An axis-aligned clipping rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Rect {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Rect {
+    pub fn new(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        Self {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    /// Create a rectangle from a lon/lat box (min_lon, min_lat, max_lon, max_lat)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a bound is not finite or the box is empty
+    pub fn from_bbox(bbox: (f64, f64, f64, f64)) -> Result<Self> {
+        let (min_lon, min_lat, max_lon, max_lat) = bbox;
+        if ![min_lon, min_lat, max_lon, max_lat]
+            .iter()
+            .all(|v| v.is_finite())
+            || min_lon >= max_lon
+            || min_lat >= max_lat
+        {
+            return Err(Error::geometry_error(format!(
+                "Invalid bounding box {bbox:?}"
+            )));
+        }
+        Ok(Self::new(min_lon, min_lat, max_lon, max_lat))
+    }
+
+    /// Whether the point lies inside or on the boundary
+    pub fn contains(&self, (x, y): (f64, f64)) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    /// Whether every point of `points` lies inside or on the boundary
+    pub fn contains_all(&self, points: &[(f64, f64)]) -> bool {
+        points.iter().all(|&p| self.contains(p))
+    }
+
+    /// Bounding box of `points` (inverted when `points` is empty)
+    pub fn bounding(points: &[(f64, f64)]) -> Self {
+        points.iter().fold(
+            Self::new(
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |r, &(x, y)| {
+                Self::new(
+                    r.min_x.min(x),
+                    r.min_y.min(y),
+                    r.max_x.max(x),
+                    r.max_y.max(y),
+                )
+            },
+        )
+    }
+
+    /// Whether the two rectangles overlap or touch
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        other.min_x <= self.max_x
+            && other.max_x >= self.min_x
+            && other.min_y <= self.max_y
+            && other.max_y >= self.min_y
+    }
+
+    /// The overlap of the two rectangles, or `None` when they are disjoint
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        self.overlaps(other).then(|| {
+            Self::new(
+                self.min_x.max(other.min_x),
+                self.min_y.max(other.min_y),
+                self.max_x.min(other.max_x),
+                self.max_y.min(other.max_y),
+            )
+        })
+    }
+
+    /// Whether the bounding box of `points` overlaps this rectangle
+    pub fn intersects(&self, points: &[(f64, f64)]) -> bool {
+        self.overlaps(&Self::bounding(points))
+    }
+}
+
+/// Cell index ranges of the part of a grid that gets contoured
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CellWindow {
+    /// Cell rows (cell `r` lies between grid rows `r` and `r + 1`)
+    pub rows: Range<usize>,
+    /// Cell columns
+    pub cols: Range<usize>,
+}
+
+impl CellWindow {
+    /// Every cell of the grid
+    pub fn full<G: GridSource + ?Sized>(grid: &G) -> Self {
+        Self {
+            rows: 0..grid.rows() - 1,
+            cols: 0..grid.cols() - 1,
+        }
+    }
+
+    /// The smallest window holding every cell whose corners' bounding box
+    /// overlaps `rect`, or `None` when no cell does
+    ///
+    /// Reads each grid point once, so curvilinear grids are handled too.
+    pub fn covering<G: GridSource + ?Sized>(grid: &G, rect: &Rect) -> Option<Self> {
+        let (rows, cols) = (grid.rows(), grid.cols());
+        let mut window: Option<(usize, usize, usize, usize)> = None;
+
+        let read_row = |row: usize| {
+            (0..cols)
+                .map(|col| grid.point(row, col))
+                .collect::<Vec<_>>()
+        };
+        let mut top = read_row(0);
+        for row in 0..rows - 1 {
+            let bottom = read_row(row + 1);
+            for col in 0..cols - 1 {
+                let corners = [top[col], top[col + 1], bottom[col + 1], bottom[col]];
+                let points = corners.map(|p| (p.lon, p.lat));
+                if rect.intersects(&points) {
+                    window = Some(match window {
+                        None => (row, row, col, col),
+                        Some((r0, r1, c0, c1)) => {
+                            (r0.min(row), r1.max(row), c0.min(col), c1.max(col))
+                        }
+                    });
+                }
+            }
+            top = bottom;
+        }
+
+        window.map(|(r0, r1, c0, c1)| Self {
+            rows: r0..r1 + 1,
+            cols: c0..c1 + 1,
+        })
+    }
+}
+
+/// Planar signed area of a ring (positive when counter-clockwise)
+pub(crate) fn ring_area(ring: &[(f64, f64)]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (x0, y0) = ring[i];
+            let (x1, y1) = ring[(i + 1) % n];
+            x0 * y1 - x1 * y0
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// One side of the clipping rectangle
+#[derive(Clone, Copy)]
+enum Boundary {
+    Left(f64),
+    Right(f64),
+    Bottom(f64),
+    Top(f64),
+}
+
+impl Boundary {
+    fn inside(self, (x, y): (f64, f64)) -> bool {
+        match self {
+            Boundary::Left(v) => x >= v,
+            Boundary::Right(v) => x <= v,
+            Boundary::Bottom(v) => y >= v,
+            Boundary::Top(v) => y <= v,
+        }
+    }
+
+    fn intersect(self, (x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> (f64, f64) {
+        match self {
+            Boundary::Left(v) | Boundary::Right(v) => {
+                let t = (v - x0) / (x1 - x0);
+                (v, y0 + t * (y1 - y0))
+            }
+            Boundary::Bottom(v) | Boundary::Top(v) => {
+                let t = (v - y0) / (y1 - y0);
+                (x0 + t * (x1 - x0), v)
+            }
+        }
+    }
+}
+
+/// Clip a polygon ring to the rectangle (Sutherland–Hodgman)
+///
+/// The input may be open or closed. The output is open (the first point is not
+/// repeated) and is empty when nothing of the ring lies inside. Parts of the
+/// ring outside the box are replaced by runs along the box boundary.
+pub(crate) fn clip_ring(ring: &[(f64, f64)], rect: &Rect) -> Vec<(f64, f64)> {
+    let mut output: Vec<(f64, f64)> = match (ring.first(), ring.last()) {
+        (Some(first), Some(last)) if ring.len() > 1 && first == last => {
+            ring[..ring.len() - 1].to_vec()
+        }
+        _ => ring.to_vec(),
+    };
+
+    if rect.contains_all(&output) {
+        return output;
+    }
+
+    let boundaries = [
+        Boundary::Left(rect.min_x),
+        Boundary::Right(rect.max_x),
+        Boundary::Bottom(rect.min_y),
+        Boundary::Top(rect.max_y),
+    ];
+
+    for boundary in boundaries {
+        if output.is_empty() {
+            break;
+        }
+        let input = std::mem::take(&mut output);
+        let mut prev = *input.last().unwrap();
+        for &current in &input {
+            match (boundary.inside(current), boundary.inside(prev)) {
+                (true, true) => output.push(current),
+                (true, false) => {
+                    output.push(boundary.intersect(prev, current));
+                    output.push(current);
+                }
+                (false, true) => output.push(boundary.intersect(prev, current)),
+                (false, false) => {}
+            }
+            prev = current;
+        }
+    }
+
+    output
+}
+
+/// Clip a polyline to the rectangle (Liang–Barsky per segment)
+///
+/// Returns the pieces of the line inside the box, in order. A line that leaves
+/// and re-enters the box yields several pieces.
+pub(crate) fn clip_polyline(line: &[(f64, f64)], rect: &Rect) -> Vec<Vec<(f64, f64)>> {
+    let mut pieces: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut current: Vec<(f64, f64)> = Vec::new();
+
+    for segment in line.windows(2) {
+        match clip_segment(segment[0], segment[1], rect) {
+            Some((a, b)) => {
+                if current.last() != Some(&a) {
+                    if current.len() >= 2 {
+                        pieces.push(std::mem::take(&mut current));
+                    }
+                    current.clear();
+                    current.push(a);
+                }
+                current.push(b);
+            }
+            None => {
+                if current.len() >= 2 {
+                    pieces.push(std::mem::take(&mut current));
+                }
+                current.clear();
+            }
+        }
+    }
+
+    if current.len() >= 2 {
+        pieces.push(current);
+    }
+
+    pieces
+}
+
+/// Clip one segment to the rectangle, returning the visible part
+pub(crate) fn clip_segment(
+    (x0, y0): (f64, f64),
+    (x1, y1): (f64, f64),
+    rect: &Rect,
+) -> Option<((f64, f64), (f64, f64))> {
+    let dx = x1 - x0;
+    let dy = y1 - y0;
+    let mut t0: f64 = 0.0;
+    let mut t1: f64 = 1.0;
+
+    for (p, q) in [
+        (-dx, x0 - rect.min_x),
+        (dx, rect.max_x - x0),
+        (-dy, y0 - rect.min_y),
+        (dy, rect.max_y - y0),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+
+    if t0 > t1 {
+        return None;
+    }
+
+    let start = if t0 == 0.0 {
+        (x0, y0)
+    } else {
+        (x0 + t0 * dx, y0 + t0 * dy)
+    };
+    let end = if t1 == 1.0 {
+        (x1, y1)
+    } else {
+        (x0 + t1 * dx, y0 + t1 * dy)
+    };
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_ring_to_box() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+        // Fully inside: unchanged (closing point dropped)
+        let inside = [(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 1.0)];
+        assert_eq!(clip_ring(&inside, &rect), inside[..3].to_vec());
+
+        // Square straddling the right edge is cut at x = 10
+        let straddle = [(5.0, 2.0), (15.0, 2.0), (15.0, 8.0), (5.0, 8.0)];
+        let clipped = clip_ring(&straddle, &rect);
+        assert_eq!(clipped.len(), 4);
+        assert!(clipped.iter().all(|&p| rect.contains(p)));
+        assert!(clipped.contains(&(10.0, 2.0)) && clipped.contains(&(10.0, 8.0)));
+
+        // Fully outside
+        let outside = [(20.0, 20.0), (30.0, 20.0), (30.0, 30.0)];
+        assert!(clip_ring(&outside, &rect).is_empty());
+    }
+
+    #[test]
+    fn test_clip_polyline_splits() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let line = [
+            (-5.0, 5.0),
+            (5.0, 5.0),
+            (5.0, 15.0),
+            (8.0, 15.0),
+            (8.0, 5.0),
+        ];
+        let pieces = clip_polyline(&line, &rect);
+
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0], vec![(0.0, 5.0), (5.0, 5.0), (5.0, 10.0)]);
+        assert_eq!(pieces[1], vec![(8.0, 10.0), (8.0, 5.0)]);
+    }
+
+    #[test]
+    fn test_covering_cell_window() {
+        use crate::{GeoGrid, GridPoint};
+
+        let points = (0..5)
+            .map(|row| {
+                (0..6)
+                    .map(|col| GridPoint::new(col as f64, -(row as f64), 0.0))
+                    .collect()
+            })
+            .collect();
+        let grid = GeoGrid::from_points(points).unwrap();
+
+        let window = CellWindow::covering(&grid, &Rect::new(1.5, -2.5, 2.5, -1.5)).unwrap();
+        assert_eq!(
+            window,
+            CellWindow {
+                rows: 1..3,
+                cols: 1..3
+            }
+        );
+        assert_eq!(
+            CellWindow::covering(&grid, &Rect::new(-9.0, -9.0, 99.0, 9.0)),
+            Some(CellWindow::full(&grid))
+        );
+        assert!(CellWindow::covering(&grid, &Rect::new(10.0, 10.0, 11.0, 11.0)).is_none());
+        assert!(Rect::from_bbox((1.0, 0.0, 1.0, 2.0)).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Colors and color maps for styled contour output
+//!
+//! Renderers and styled writers (KML, SVG, PNG) ask a [`ColorMap`] for the
+//! color of each band or level. [`ColorRamp`] is a piecewise-linear map
+//! between value stops, and any `Fn(f64, f64) -> Color` closure over the band
+//! levels can be used as a map as well.
+
+use crate::error::{Error, Result};
+
+/// An 8-bit sRGB color with alpha
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    /// Opacity, 0 (transparent) to 255 (opaque)
+    pub a: u8,
+}
+
+impl Color {
+    /// Create an opaque color
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Create a color with alpha
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Return this color with a different alpha
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Parse `#rrggbb` or `#rrggbbaa` (the `#` is optional)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not 6 or 8 hex digits
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !(digits.len() == 6 || digits.len() == 8) || !digits.is_ascii() {
+            return Err(Error::invalid_color(format!(
+                "Expected #rrggbb or #rrggbbaa, got {hex:?}"
+            )));
+        }
+        let channel = |i: usize| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| Error::invalid_color(format!("Invalid hex color {hex:?}")))
+        };
+        Ok(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+            a: if digits.len() == 8 { channel(6)? } else { 255 },
+        })
+    }
+
+    /// Format as `#rrggbb`, or `#rrggbbaa` when not fully opaque
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    /// Format in KML's `aabbggrr` order
+    pub fn to_kml(&self) -> String {
+        format!("{:02x}{:02x}{:02x}{:02x}", self.a, self.b, self.g, self.r)
+    }
+
+    /// Linear interpolation between two colors, `t` in [0, 1]
+    pub fn lerp(self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+        Color {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: mix(self.a, other.a),
+        }
+    }
+}
+
+/// Assigns colors to isobands and isolines
+pub trait ColorMap {
+    /// Color for the band between `lower` and `upper`
+    fn band_color(&self, lower: f64, upper: f64) -> Color;
+
+    /// Color for the isoline at `level`
+    fn level_color(&self, level: f64) -> Color {
+        self.band_color(level, level)
+    }
+}
+
+impl<F: Fn(f64, f64) -> Color> ColorMap for F {
+    fn band_color(&self, lower: f64, upper: f64) -> Color {
+        self(lower, upper)
+    }
+}
+
+/// A piecewise-linear color ramp over value stops
+///
+/// Values below the first stop take its color and values above the last stop
+/// take the last color. Bands are colored at their midpoint.
+///
+/// # Example
+///
+/// ```rust
+/// use geo_marching_squares_rs::color::{Color, ColorMap, ColorRamp};
+///
+/// let ramp = ColorRamp::new(vec![
+///     (0.0, Color::rgb(0, 0, 255)),
+///     (100.0, Color::rgb(255, 0, 0)),
+/// ])?;
+/// assert_eq!(ramp.color_at(50.0), Color::rgb(128, 0, 128));
+/// assert_eq!(ramp.band_color(0.0, 20.0), ramp.color_at(10.0));
+/// # Ok::<(), geo_marching_squares_rs::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    stops: Vec<(f64, Color)>,
+}
+
+impl ColorRamp {
+    /// Create a ramp from `(value, color)` stops
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no stops, or the values are not finite and
+    /// in ascending order
+    pub fn new(stops: Vec<(f64, Color)>) -> Result<Self> {
+        if stops.is_empty() {
+            return Err(Error::invalid_color("Color ramp needs at least one stop"));
+        }
+        if stops.iter().any(|(v, _)| !v.is_finite()) || stops.windows(2).any(|w| w[1].0 < w[0].0) {
+            return Err(Error::invalid_color(
+                "Color ramp stops must be finite and ascending",
+            ));
+        }
+        Ok(Self { stops })
+    }
+
+    /// Spread colors evenly between `min` and `max`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `colors` is empty or `max < min`
+    pub fn evenly_spaced(min: f64, max: f64, colors: &[Color]) -> Result<Self> {
+        let n = colors.len();
+        let stops = colors
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                let t = if n > 1 {
+                    i as f64 / (n - 1) as f64
+                } else {
+                    0.0
+                };
+                (min + (max - min) * t, c)
+            })
+            .collect();
+        Self::new(stops)
+    }
+
+    /// Get the ramp stops
+    pub fn stops(&self) -> &[(f64, Color)] {
+        &self.stops
+    }
+
+    /// Color at a value
+    pub fn color_at(&self, value: f64) -> Color {
+        let i = self.stops.partition_point(|(v, _)| *v <= value);
+        if i == 0 {
+            return self.stops[0].1;
+        }
+        if i == self.stops.len() {
+            return self.stops[i - 1].1;
+        }
+        let (v0, c0) = self.stops[i - 1];
+        let (v1, c1) = self.stops[i];
+        c0.lerp(c1, (value - v0) / (v1 - v0))
+    }
+}
+
+impl ColorMap for ColorRamp {
+    fn band_color(&self, lower: f64, upper: f64) -> Color {
+        self.color_at((lower + upper) / 2.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let c = Color::from_hex("#1e90ff").unwrap();
+        assert_eq!(c, Color::rgb(0x1e, 0x90, 0xff));
+        assert_eq!(c.to_hex(), "#1e90ff");
+        assert_eq!(c.to_kml(), "ffff901e");
+
+        let c = Color::from_hex("11223380").unwrap();
+        assert_eq!(c.a, 0x80);
+        assert_eq!(c.to_hex(), "#11223380");
+
+        assert!(Color::from_hex("#12345").is_err());
+        assert!(Color::from_hex("#zzzzzz").is_err());
+    }
+
+    #[test]
+    fn test_ramp_interpolation() {
+        let ramp = ColorRamp::evenly_spaced(
+            0.0,
+            10.0,
+            &[
+                Color::rgb(0, 0, 0),
+                Color::rgb(200, 100, 0),
+                Color::rgb(200, 100, 200),
+            ],
+        )
+        .unwrap();
+        assert_eq!(ramp.color_at(-5.0), Color::rgb(0, 0, 0));
+        assert_eq!(ramp.color_at(2.5), Color::rgb(100, 50, 0));
+        assert_eq!(ramp.color_at(5.0), Color::rgb(200, 100, 0));
+        assert_eq!(ramp.color_at(20.0), Color::rgb(200, 100, 200));
+
+        let closure = |lower: f64, _upper: f64| {
+            if lower < 0.0 {
+                Color::rgb(0, 0, 255)
+            } else {
+                Color::rgb(255, 0, 0)
+            }
+        };
+        assert_eq!(closure.level_color(-1.0), Color::rgb(0, 0, 255));
+
+        assert!(ColorRamp::new(vec![]).is_err());
+        assert!(
+            ColorRamp::new(vec![(1.0, Color::rgb(0, 0, 0)), (0.0, Color::rgb(0, 0, 0))]).is_err()
+        );
+    }
+}
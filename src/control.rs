@@ -0,0 +1,210 @@
+//! Cancellation and progress reporting for long-running contour jobs
+//!
+//! A [`ContourControl`] is passed to
+//! [`GridSource::isobands_with_control`](crate::GridSource::isobands_with_control)
+//! or [`GridSource::isolines_with_control`](crate::GridSource::isolines_with_control).
+//! It carries an optional [`CancellationToken`], an optional deadline and an
+//! optional progress callback.
+//!
+//! Cancellation is checked before each band or level and between grid rows
+//! while a band is processed, also when bands run in parallel. A cancelled or
+//! timed-out run returns [`Error::Cancelled`]. Progress is reported once per
+//! completed band or level.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::control::{CancellationToken, ContourControl};
+//! use geo_marching_squares_rs::{Error, GeoGrid, GridPoint, GridSource};
+//! use std::time::Duration;
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//!
+//! let token = CancellationToken::new();
+//! let control = ContourControl::new()
+//!     .with_cancellation(token.clone())
+//!     .with_timeout(Duration::from_secs(30))
+//!     .with_progress(|p| println!("{}/{} bands", p.completed, p.total));
+//! let features = grid.isobands_with_control(&[12.0, 18.0, 22.0], &control)?;
+//!
+//! // e.g. from another thread, when the client disconnects
+//! token.cancel();
+//! let result = grid.isobands_with_control(&[12.0, 18.0, 22.0], &control);
+//! assert!(matches!(result, Err(Error::Cancelled)));
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A shareable flag that cancels contour runs
+///
+/// Clones share the same flag, so one clone can be handed to the contour run
+/// and another kept by whoever decides to cancel.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every run using this token (or a clone of it)
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress of a contour run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Bands or levels completed so far
+    pub completed: usize,
+    /// Bands or levels in the run
+    pub total: usize,
+}
+
+impl Progress {
+    /// Completed fraction, from 0.0 to 1.0
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.completed as f64 / self.total as f64
+        }
+    }
+}
+
+type ProgressFn = dyn Fn(Progress) + Send + Sync;
+
+/// Cancellation, deadline and progress settings for a contour run
+///
+/// The default control never cancels and reports no progress.
+#[derive(Clone, Default)]
+pub struct ContourControl {
+    token: Option<CancellationToken>,
+    deadline: Option<Instant>,
+    progress: Option<Arc<ProgressFn>>,
+}
+
+impl ContourControl {
+    /// Create a control that never cancels and reports no progress
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the run when `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Cancel the run once `deadline` has passed
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Cancel the run once `timeout` has elapsed from now
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Call `progress` after each band or level completes
+    ///
+    /// With the `parallel` feature the callback may be called from several
+    /// threads, and completions may be reported out of band order.
+    pub fn with_progress<F>(mut self, progress: F) -> Self
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Whether the run should stop
+    pub fn is_cancelled(&self) -> bool {
+        self.token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Return [`Error::Cancelled`] if the run should stop
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn report(&self, completed: usize, total: usize) {
+        if let Some(progress) = &self.progress {
+            progress(Progress { completed, total });
+        }
+    }
+}
+
+impl fmt::Debug for ContourControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContourControl")
+            .field("token", &self.token)
+            .field("deadline", &self.deadline)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_and_deadline() {
+        let token = CancellationToken::new();
+        let control = ContourControl::new().with_cancellation(token.clone());
+        assert!(control.check().is_ok());
+        token.clone().cancel();
+        assert!(matches!(control.check(), Err(Error::Cancelled)));
+
+        let expired = ContourControl::new().with_deadline(Instant::now());
+        assert!(expired.is_cancelled());
+        assert!(!ContourControl::new()
+            .with_timeout(Duration::from_secs(3600))
+            .is_cancelled());
+
+        assert_eq!(
+            Progress {
+                completed: 1,
+                total: 4
+            }
+            .fraction(),
+            0.25
+        );
+        assert_eq!(
+            Progress {
+                completed: 0,
+                total: 0
+            }
+            .fraction(),
+            1.0
+        );
+    }
+}
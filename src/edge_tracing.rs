@@ -4,6 +4,7 @@
 //! complete polygon rings from individual cell edges.
 
 use crate::cell_shapes::CellShape;
+use crate::report::{TraceFailure, TraceFailureKind};
 use crate::types::{Edge, Point};
 
 /// A cell in the grid with its edges
@@ -19,10 +20,8 @@ pub struct CellWithEdges {
     total_edge_count: usize,
     /// Cell configuration value (0-170 for 3-level, 0-15 for 2-level)
     pub config: u8,
-    /// Corner values: (tl, tr, br, bl)
-    pub corners: (f64, f64, f64, f64),
-    /// Boundary flags: (is_top, is_right, is_bottom, is_left)
-    pub boundaries: (bool, bool, bool, bool),
+    /// Whether the cell may be crossed by the clip mask boundary
+    pub on_mask_edge: bool,
 }
 
 impl CellWithEdges {
@@ -35,13 +34,12 @@ impl CellWithEdges {
             cleared: false,
             total_edge_count: total_edges,
             config: 0,
-            corners: (0.0, 0.0, 0.0, 0.0),
-            boundaries: (false, false, false, false),
+            on_mask_edge: false,
         }
     }
 
-    /// Create a new cell with edges and configuration info
-    pub fn new_with_config(shape: CellShape, config: u8, corners: (f64, f64, f64, f64), boundaries: (bool, bool, bool, bool)) -> Self {
+    /// Create a new cell with edges and its configuration value
+    pub fn new_with_config(shape: CellShape, config: u8) -> Self {
         let total_edges = shape.edges.len();
         Self {
             shape,
@@ -49,8 +47,7 @@ impl CellWithEdges {
             cleared: false,
             total_edge_count: total_edges,
             config,
-            corners,
-            boundaries,
+            on_mask_edge: false,
         }
     }
 
@@ -131,7 +128,17 @@ fn points_equal(p1: &Point, p2: &Point) -> bool {
 
 /// Trace a single polygon ring starting from a cell
 ///
-/// Returns the list of points forming a closed ring, or None if tracing fails
+/// Returns the list of points forming a closed ring, or None if tracing fails.
+/// Traces that stop before the ring closes are recorded in `failures` and
+/// their partial ring is dropped.
+///
+/// There is no built-in length cap: every step removes at least one edge from
+/// the grid, so a trace ends after at most as many steps as there are edges.
+/// `max_steps` optionally stops a trace earlier, which is reported as
+/// [`TraceFailureKind::IterationLimit`] and drops the ring.
+///
+/// `on_mask_edge` is set when the trace visits a cell flagged as crossed by
+/// the clip mask boundary.
 ///
 /// This follows the Java algorithm exactly (MarchingSquares.java lines 63-109):
 /// 1. Get chained edges from current cell
@@ -142,6 +149,9 @@ pub fn trace_ring(
     cells: &mut Vec<Vec<Option<CellWithEdges>>>,
     start_row: usize,
     start_col: usize,
+    max_steps: Option<usize>,
+    failures: &mut Vec<TraceFailure>,
+    on_mask_edge: &mut bool,
 ) -> Option<Vec<Point>> {
     let rows = cells.len();
     let cols = if rows > 0 { cells[0].len() } else { 0 };
@@ -157,22 +167,16 @@ pub fn trace_ring(
     let mut current_edge: Option<Edge> = None;
     let mut all_edges = Vec::new();
     let mut go_on = true;
+    let mut stopped: Option<(TraceFailureKind, Option<u8>)> = None;
 
-    let mut iterations = 0;
-    const MAX_ITERATIONS: usize = 10000;
-
-    // Debug tracing if we start at specific problematic areas
-    // Looking for the ring that creates the diagonal from west coast to east coast
-    let debug_trace = start_row < 50; // Debug first 50 rows
-    if debug_trace {
-        eprintln!("🎯 Starting trace at cell ({},{})", start_row, start_col);
-    }
+    let mut steps = 0;
 
     // Java: while (goOn && !cells[y][x].getEdges(...).isEmpty())
     while go_on {
-        iterations += 1;
-        if iterations > MAX_ITERATIONS {
-            return None;
+        steps += 1;
+        if max_steps.is_some_and(|max| steps > max) {
+            stopped = Some((TraceFailureKind::IterationLimit, None));
+            break;
         }
 
         // Get the current cell
@@ -183,17 +187,21 @@ pub fn trace_ring(
         {
             Some(c) => c,
             None => {
-                eprintln!("⚠️ trace_ring at ({},{}) STOPPED: Cell not found at ({},{}), {} edges collected",
-                    start_row, start_col, current_row, current_col, all_edges.len());
+                let kind = if current_row < rows && current_col < cols {
+                    TraceFailureKind::DeadEnd
+                } else {
+                    TraceFailureKind::LeftGrid
+                };
+                stopped = Some((kind, None));
                 break;
             }
         };
 
         if cell.is_cleared() {
-            eprintln!("⚠️ trace_ring at ({},{}) STOPPED: Cell cleared at ({},{}), {} edges collected",
-                start_row, start_col, current_row, current_col, all_edges.len());
+            stopped = Some((TraceFailureKind::ClearedCell, Some(cell.config)));
             break;
         }
+        *on_mask_edge |= cell.on_mask_edge;
 
         // Get chained edges from current cell
         // Java: cells[y][x].getEdges(currentEdge==null?null:currentEdge.getEnd(), ...)
@@ -204,15 +212,7 @@ pub fn trace_ring(
         };
 
         if tmp_edges.is_empty() {
-            let (tl, tr, br, bl) = cell.corners;
-            let (is_top, is_right, is_bottom, is_left) = cell.boundaries;
-            if let Some(ref edge) = current_edge {
-                eprintln!("⚠️ trace_ring at ({},{}) STOPPED: No edges at ({},{}) from point ({:?},{:?}), config={}, corners=[{:.2},{:.2},{:.2},{:.2}], boundaries=[T:{},R:{},B:{},L:{}], {} edges collected",
-                    start_row, start_col, current_row, current_col, edge.end.x, edge.end.y, cell.config, tl, tr, br, bl, is_top, is_right, is_bottom, is_left, all_edges.len());
-            } else {
-                eprintln!("⚠️ trace_ring at ({},{}) STOPPED: No edges at ({},{}), config={}, corners=[{:.2},{:.2},{:.2},{:.2}], boundaries=[T:{},R:{},B:{},L:{}], {} edges collected",
-                    start_row, start_col, current_row, current_col, cell.config, tl, tr, br, bl, is_top, is_right, is_bottom, is_left, all_edges.len());
-            }
+            stopped = Some((TraceFailureKind::DeadEnd, Some(cell.config)));
             break;
         }
 
@@ -222,15 +222,6 @@ pub fn trace_ring(
 
             // Java: for (Edge edge : tmpEdges) { ... }
             for edge in &tmp_edges {
-                // Debug: Check for unusually long edges (possible tracing bug)
-                if let (Some(sx), Some(sy), Some(ex), Some(ey)) = (edge.start.x, edge.start.y, edge.end.x, edge.end.y) {
-                    let edge_length_deg = ((ex - sx).powi(2) + (ey - sy).powi(2)).sqrt();
-                    if edge_length_deg > 10.0 {  // ~1000km at mid-latitudes
-                        eprintln!("🚨 LONG EDGE DETECTED: ({},{}) edge from ({:.6},{:.6}) to ({:.6},{:.6}), length={:.2}°, move={:?}",
-                            current_row, current_col, sx, sy, ex, ey, edge_length_deg, edge.move_dir);
-                    }
-                }
-
                 // Java: cells[y][x].removeEdge(edge.getStart());
                 cell_mut.remove_edge(&edge.start);
 
@@ -248,13 +239,16 @@ pub fn trace_ring(
             break;
         }
 
+        let (prev_row, prev_col) = (current_row, current_col);
+        let prev_on_mask_edge = cells[prev_row][prev_col]
+            .as_ref()
+            .is_some_and(|cell| cell.on_mask_edge);
+
         // Java: Move logic happens AFTER the for loop (lines 86-97)
         // This runs even if we broke from the for loop above
         // Java relies on short-circuit evaluation of the while condition to avoid
         // accessing out-of-bounds cells when goOn is false
         if let Some(ref edge) = current_edge {
-            let old_row = current_row;
-            let old_col = current_col;
             match edge.move_dir {
                 crate::types::Move::Right => {
                     current_col += 1;
@@ -273,15 +267,44 @@ pub fn trace_ring(
                     // Continue with while loop
                 }
             }
-            if debug_trace {
-                eprintln!("   Move {:?}: ({},{}) -> ({},{}) go_on={}",
-                    edge.move_dir, old_row, old_col, current_row, current_col, go_on);
+        }
+
+        // Rings closing along cells skipped outside a clip mask turn at corners
+        // the shapes' moves do not cover; continue in the neighbour that holds
+        // the next edge
+        if go_on && prev_on_mask_edge {
+            if let Some(ref edge) = current_edge {
+                if !continues_at(cells, current_row, current_col, &edge.end) {
+                    if let Some((row, col)) =
+                        neighbour_continuing(cells, prev_row, prev_col, &edge.end)
+                    {
+                        current_row = row;
+                        current_col = col;
+                    }
+                }
             }
         }
 
         // If go_on is false, the while condition will fail on next iteration
     }
 
+    let mut fail = |kind: TraceFailureKind, config: Option<u8>| {
+        failures.push(TraceFailure {
+            kind,
+            start_row,
+            start_col,
+            row: current_row,
+            col: current_col,
+            config,
+            edges: all_edges.len(),
+        });
+    };
+    if let Some((kind, config)) = stopped {
+        fail(kind, config);
+        // The ring never closed; closing it would cut a chord across the band
+        return None;
+    }
+
     // Build the points list from all edges (Java lines 100-106)
     if all_edges.is_empty() {
         return None;
@@ -293,63 +316,69 @@ pub fn trace_ring(
         points.push(edge.end.clone());
     }
 
-    // CRITICAL FIX: Ensure ring is closed
-    // Java: Ring closes when edge.end == all_edges[0].start
-    // With bitwise equality this should work exactly
-    if points.len() >= 2 {
-        let first = points[0].clone();
-        let last_idx = points.len() - 1;
-        let last = points[last_idx].clone();
-
-        if first != last {
-            if let (Some(fx), Some(fy), Some(lx), Some(ly)) = (first.x, first.y, last.x, last.y) {
-                let dx = fx - lx;
-                let dy = fy - ly;
-                let dist = (dx * dx + dy * dy).sqrt();
-
-                const EPSILON: f64 = 1.0; // 1 degree - for warning only
-                if dist > EPSILON {
-                    // Ring is NOT closed and they're too far apart - this is a real error
-                    eprintln!("⚠️ WARNING: Ring at ({},{}) failed to close! first=({:.6},{:.6}) last=({:.6},{:.6}) dist={:.6}°",
-                        start_row, start_col, fx, fy, lx, ly, dist);
-                }
-            }
-        }
-    }
-
-    // Debug: Check for long segments in the final ring
-    for i in 0..points.len().saturating_sub(1) {
-        let p1 = &points[i];
-        let p2 = &points[i + 1];
-        if let (Some(x1), Some(y1), Some(x2), Some(y2)) = (p1.x, p1.y, p2.x, p2.y) {
-            let seg_length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
-            if seg_length > 10.0 {
-                eprintln!("🚨 LONG SEGMENT IN RING: segment {} from ({:.6},{:.6}) to ({:.6},{:.6}), length={:.2}°",
-                    i, x1, y1, x2, y2, seg_length);
-                eprintln!("   Ring has {} total points, {} edges traced", points.len(), all_edges.len());
-            }
-        }
-    }
-
     if points.len() >= 3 {
         Some(points)
     } else {
+        fail(TraceFailureKind::Degenerate, None);
         None
     }
 }
 
+/// Whether the cell at `row`, `col` has an unused edge starting at `point`
+fn continues_at(
+    cells: &[Vec<Option<CellWithEdges>>],
+    row: usize,
+    col: usize,
+    point: &Point,
+) -> bool {
+    cells
+        .get(row)
+        .and_then(|r| r.get(col))
+        .and_then(|c| c.as_ref())
+        .is_some_and(|cell| !cell.is_cleared() && cell.shape.edges.contains_key(point))
+}
+
+/// The cell around `row`, `col` (itself included) with an unused edge
+/// starting at `point`
+fn neighbour_continuing(
+    cells: &[Vec<Option<CellWithEdges>>],
+    row: usize,
+    col: usize,
+    point: &Point,
+) -> Option<(usize, usize)> {
+    let rows = row.saturating_sub(1)..=row + 1;
+    rows.flat_map(|r| (col.saturating_sub(1)..=col + 1).map(move |c| (r, c)))
+        .find(|&(r, c)| continues_at(cells, r, c, point))
+}
+
+/// Rings traced from a grid of cells, with trace statistics
+#[derive(Debug, Default)]
+pub struct TracedRings {
+    /// Rings with at least 3 points
+    pub rings: Vec<Vec<Point>>,
+    /// Whether each ring passes through a cell on the clip mask boundary
+    pub on_mask_edge: Vec<bool>,
+    /// Ring traces started
+    pub attempts: usize,
+    /// Traces that stopped before closing
+    pub failures: Vec<TraceFailure>,
+}
+
 /// Trace all polygon rings from a grid of cells
 ///
-/// Returns a list of polygon rings (each ring is a Vec<Point>)
+/// Returns the polygon rings (each ring is a Vec<Point>) with the number of
+/// traces attempted and every failed trace.
 /// Only returns rings with at least 3 points (valid polygons per GeoJSON spec)
-pub fn trace_all_rings(cells: &mut Vec<Vec<Option<CellWithEdges>>>) -> Vec<Vec<Point>> {
-    let mut rings = Vec::new();
-    let mut failed_traces = 0;
-    let mut total_attempts = 0;
+/// `max_steps` is passed on to [`trace_ring`].
+pub fn trace_all_rings(
+    cells: &mut Vec<Vec<Option<CellWithEdges>>>,
+    max_steps: Option<usize>,
+) -> TracedRings {
+    let mut traced = TracedRings::default();
 
     let rows = cells.len();
     if rows == 0 {
-        return rings;
+        return traced;
     }
     let cols = cells[0].len();
 
@@ -358,38 +387,39 @@ pub fn trace_all_rings(cells: &mut Vec<Vec<Option<CellWithEdges>>>) -> Vec<Vec<P
         for col in 0..cols {
             // Keep tracing from this cell until all its edges are used
             loop {
-                total_attempts += 1;
-                match trace_ring(cells, row, col) {
+                if cells[row][col]
+                    .as_ref()
+                    .is_none_or(|cell| cell.is_cleared())
+                {
+                    break;
+                }
+                traced.attempts += 1;
+                let mut on_mask_edge = false;
+                match trace_ring(
+                    cells,
+                    row,
+                    col,
+                    max_steps,
+                    &mut traced.failures,
+                    &mut on_mask_edge,
+                ) {
                     Some(ring) => {
                         // Only include rings with at least 3 points
                         // (GeoJSON requires at least 4 coordinates for a valid polygon ring,
                         // with the first and last being identical. Since we don't duplicate
                         // the closing point, we need at least 3 distinct points)
                         if ring.len() >= 3 {
-                            rings.push(ring);
-                        }
-                    }
-                    None => {
-                        // Check if there are still edges in this cell
-                        if let Some(Some(cell)) = cells.get(row).and_then(|r| r.get(col)) {
-                            if !cell.is_cleared() && !cell.shape.edges.is_empty() {
-                                failed_traces += 1;
-                            }
+                            traced.rings.push(ring);
+                            traced.on_mask_edge.push(on_mask_edge);
                         }
-                        break;
                     }
+                    None => break,
                 }
             }
         }
     }
 
-    // Optional: Uncomment for debugging
-    // eprintln!("\n📊 EDGE TRACING SUMMARY:");
-    // eprintln!("   Total rings traced: {}", rings.len());
-    // eprintln!("   Total trace attempts: {}", total_attempts);
-    // eprintln!("   Failed traces: {}", failed_traces);
-
-    rings
+    traced
 }
 
 #[cfg(test)]
@@ -476,7 +506,7 @@ mod tests {
 
         let mut cells = vec![vec![Some(cell)]];
 
-        let ring = trace_ring(&mut cells, 0, 0);
+        let ring = trace_ring(&mut cells, 0, 0, None, &mut Vec::new(), &mut false);
 
         assert!(ring.is_some(), "Ring tracing should succeed");
         let points = ring.unwrap();
@@ -484,4 +514,91 @@ mod tests {
         // First and last should be the same (closed loop)
         assert!(points_equal(&points[0], points.last().unwrap()));
     }
+
+    #[test]
+    fn test_failed_trace_is_recorded() {
+        // The trace walks right into a cell with no edges and never closes
+        let edge = Edge::new(Point::new(0.0, 0.0), Point::new(1.0, 0.0), Move::Right);
+        let cell = CellWithEdges::new(CellShape::new(vec![edge]));
+        let mut cells = vec![vec![Some(cell), None]];
+
+        let traced = trace_all_rings(&mut cells, None);
+        assert_eq!(traced.attempts, 1);
+        assert!(traced.rings.is_empty());
+        assert_eq!(traced.failures.len(), 1);
+        let failure = traced.failures[0];
+        assert_eq!(failure.kind, TraceFailureKind::DeadEnd);
+        assert_eq!((failure.start_row, failure.start_col), (0, 0));
+        assert_eq!((failure.row, failure.col), (0, 1));
+        assert_eq!(failure.edges, 1);
+    }
+
+    /// A 1-row strip of cells whose ring runs right along the top edges and
+    /// back left along the bottom ones
+    fn strip(len: usize) -> Vec<Vec<Option<CellWithEdges>>> {
+        let row = (0..len)
+            .map(|i| {
+                let x = i as f64;
+                let last = i + 1 == len;
+                let mut edges = vec![Edge::new(
+                    Point::new(x, 1.0),
+                    Point::new(x + 1.0, 1.0),
+                    if last { Move::None } else { Move::Right },
+                )];
+                if last {
+                    edges.push(Edge::new(
+                        Point::new(x + 1.0, 1.0),
+                        Point::new(x + 1.0, 0.0),
+                        Move::None,
+                    ));
+                }
+                edges.push(Edge::new(
+                    Point::new(x + 1.0, 0.0),
+                    Point::new(x, 0.0),
+                    if i == 0 { Move::None } else { Move::Left },
+                ));
+                if i == 0 {
+                    edges.push(Edge::new(
+                        Point::new(0.0, 0.0),
+                        Point::new(0.0, 1.0),
+                        Move::None,
+                    ));
+                }
+                Some(CellWithEdges::new(CellShape::new(edges)))
+            })
+            .collect();
+        vec![row]
+    }
+
+    #[test]
+    fn test_long_ring_has_no_length_cap() {
+        let mut cells = strip(6000);
+        let traced = trace_all_rings(&mut cells, None);
+        assert!(traced.failures.is_empty());
+        assert_eq!(traced.rings.len(), 1);
+        assert_eq!(traced.rings[0].len(), 2 * 6000 + 3);
+    }
+
+    #[test]
+    fn test_incomplete_ring_is_dropped() {
+        // Without its last cell the strip's ring dead-ends after several edges
+        let mut cells = strip(4);
+        cells[0][3] = None;
+        let mut failures = Vec::new();
+        let ring = trace_ring(&mut cells, 0, 0, None, &mut failures, &mut false);
+        assert!(ring.is_none());
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, TraceFailureKind::DeadEnd);
+        assert!(failures[0].edges >= 3);
+    }
+
+    #[test]
+    fn test_step_limit_is_reported() {
+        let mut cells = strip(50);
+        let mut failures = Vec::new();
+        let ring = trace_ring(&mut cells, 0, 0, Some(10), &mut failures, &mut false);
+        assert!(ring.is_none());
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, TraceFailureKind::IterationLimit);
+    }
 }
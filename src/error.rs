@@ -1,5 +1,6 @@
 //! Error types for the geo-marching-squares-rs crate
 
+use crate::report::{TraceFailure, TraceFailureKind};
 use thiserror::Error;
 
 /// Result type alias for this crate
@@ -31,6 +32,37 @@ pub enum Error {
 
     #[error("Geometric operation failed: {message}")]
     GeometryError { message: String },
+
+    #[error("I/O error: {source}")]
+    Io {
+        #[from]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid grid cache: {message}")]
+    InvalidCache { message: String },
+
+    #[error("Invalid tile coordinates: {z}/{x}/{y}")]
+    InvalidTile { z: u8, x: u32, y: u32 },
+
+    #[error("Invalid color: {message}")]
+    InvalidColor { message: String },
+
+    #[error("Invalid palette at line {line}: {message}")]
+    InvalidPalette { line: usize, message: String },
+
+    #[error("Contour generation cancelled")]
+    Cancelled,
+
+    #[error("Ring trace failed in band {lower}-{upper} at cell ({row}, {col}): {kind}")]
+    TraceFailed {
+        lower: f64,
+        upper: f64,
+        row: usize,
+        col: usize,
+        config: Option<u8>,
+        kind: TraceFailureKind,
+    },
 }
 
 impl Error {
@@ -61,4 +93,38 @@ impl Error {
             message: message.into(),
         }
     }
-}
\ No newline at end of file
+
+    pub fn invalid_cache(message: impl Into<String>) -> Self {
+        Self::InvalidCache {
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_tile(z: u8, x: u32, y: u32) -> Self {
+        Self::InvalidTile { z, x, y }
+    }
+
+    pub fn invalid_color(message: impl Into<String>) -> Self {
+        Self::InvalidColor {
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_palette(line: usize, message: impl Into<String>) -> Self {
+        Self::InvalidPalette {
+            line,
+            message: message.into(),
+        }
+    }
+
+    pub fn trace_failed(lower: f64, upper: f64, failure: &TraceFailure) -> Self {
+        Self::TraceFailed {
+            lower,
+            upper,
+            row: failure.row,
+            col: failure.col,
+            config: failure.config,
+            kind: failure.kind,
+        }
+    }
+}
@@ -0,0 +1,505 @@
+//! Value pre-smoothing filters
+//!
+//! Noisy fields (model reflectivity, precipitation) produce ragged contours. Smoothing
+//! the values before contouring gives much cleaner results than smoothing the rings
+//! afterwards, because the topology of the bands stays consistent.
+//!
+//! A [`FilteredGrid`] holds the filtered values and reads coordinates from the source
+//! grid, so the geometry is shared rather than copied.
+//!
+//! # Nodata
+//!
+//! Non-finite values (NaN, ±∞) and the optional sentinel set with
+//! [`ValueFilter::with_nodata`] are treated as missing. Missing samples are excluded
+//! from every kernel (the remaining weights are renormalized) and missing cells keep
+//! their original value, so gaps never bleed into valid data.
+
+use crate::error::{Error, Result};
+use crate::grid::{validate_levels, validate_thresholds, GeoGrid, GridSource};
+use crate::interpolation::haversine_km;
+use crate::marching_squares::{generate_isobands, generate_isolines};
+use crate::types::{GridPoint, MarchingSquaresConfig};
+use geojson::Feature;
+
+/// Standard deviation of a Gaussian filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sigma {
+    /// Sigma in grid cells
+    Cells(f64),
+    /// Sigma in kilometers, converted to cells using the mean grid spacing
+    Kilometers(f64),
+}
+
+/// The kind of value filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum FilterKind {
+    /// Gaussian blur, truncated at 3 sigma
+    Gaussian(Sigma),
+    /// Mean over a (2 * radius + 1) square window
+    Box { radius: usize },
+    /// Median over a (2 * radius + 1) square window (preserves sharp edges)
+    Median { radius: usize },
+}
+
+/// A value filter applied to a grid before contouring
+///
+/// # Example
+///
+/// ```rust
+/// # use geo_marching_squares_rs::{GeoGrid, GridPoint};
+/// use geo_marching_squares_rs::filter::{Sigma, ValueFilter};
+///
+/// # let grid = GeoGrid::from_points(vec![
+/// #     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+/// #     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+/// # ])?;
+/// let filter = ValueFilter::gaussian(Sigma::Kilometers(6.0)).with_nodata(-9999.0);
+/// let smoothed = grid.smooth(&filter)?;
+/// let isobands = smoothed.isobands(&[12.0, 18.0, 22.0])?;
+/// # Ok::<(), geo_marching_squares_rs::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueFilter {
+    /// Filter kernel
+    kind: FilterKind,
+    /// Sentinel value marking missing data (in addition to non-finite values)
+    nodata: Option<f32>,
+}
+
+impl ValueFilter {
+    /// Create a filter of the given kind
+    pub fn new(kind: FilterKind) -> Self {
+        Self { kind, nodata: None }
+    }
+
+    /// Create a Gaussian filter
+    pub fn gaussian(sigma: Sigma) -> Self {
+        Self::new(FilterKind::Gaussian(sigma))
+    }
+
+    /// Create a box (moving average) filter
+    pub fn box_mean(radius: usize) -> Self {
+        Self::new(FilterKind::Box { radius })
+    }
+
+    /// Create a median filter
+    pub fn median(radius: usize) -> Self {
+        Self::new(FilterKind::Median { radius })
+    }
+
+    /// Treat `value` as missing data
+    pub fn with_nodata(mut self, value: f32) -> Self {
+        self.nodata = Some(value);
+        self
+    }
+
+    /// Get the filter kernel
+    pub fn kind(&self) -> FilterKind {
+        self.kind
+    }
+
+    /// Get the nodata sentinel, if any
+    pub fn nodata(&self) -> Option<f32> {
+        self.nodata
+    }
+
+    /// Check whether a value is valid data
+    fn is_valid(&self, value: f32) -> bool {
+        value.is_finite() && Some(value) != self.nodata
+    }
+
+    /// Filter the values of `grid`, returning them in row-major order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sigma is not positive and finite, or if a kilometer
+    /// sigma is used on a grid with zero spacing
+    pub fn apply<G: GridSource + ?Sized>(&self, grid: &G) -> Result<Vec<f32>> {
+        let rows = grid.rows();
+        let cols = grid.cols();
+        let values: Vec<f32> = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| grid.point(row, col).value))
+            .collect();
+
+        match self.kind {
+            FilterKind::Gaussian(sigma) => {
+                let (sigma_x, sigma_y) = match sigma {
+                    Sigma::Cells(cells) => (cells, cells),
+                    Sigma::Kilometers(km) => {
+                        let (dx, dy) = mean_spacing_km(grid);
+                        if dx <= 0.0 || dy <= 0.0 {
+                            return Err(Error::invalid_dimensions(
+                                "Cannot convert sigma in kilometers on a grid with zero spacing",
+                            ));
+                        }
+                        (km / dx, km / dy)
+                    }
+                };
+                if !(sigma_x.is_finite() && sigma_y.is_finite() && sigma_x > 0.0 && sigma_y > 0.0) {
+                    return Err(Error::invalid_dimensions(
+                        "Gaussian sigma must be positive and finite",
+                    ));
+                }
+                Ok(self.separable(
+                    &values,
+                    rows,
+                    cols,
+                    &gaussian_kernel(sigma_x),
+                    &gaussian_kernel(sigma_y),
+                ))
+            }
+            FilterKind::Box { radius } => {
+                let kernel = vec![1.0; 2 * radius + 1];
+                Ok(self.separable(&values, rows, cols, &kernel, &kernel))
+            }
+            FilterKind::Median { radius } => Ok(self.median_filter(&values, rows, cols, radius)),
+        }
+    }
+
+    /// Normalized separable convolution that skips missing samples
+    ///
+    /// Convolves both the masked values and the mask itself, then divides, so the
+    /// kernel weights are renormalized over the valid samples in each window.
+    fn separable(
+        &self,
+        values: &[f32],
+        rows: usize,
+        cols: usize,
+        kernel_x: &[f64],
+        kernel_y: &[f64],
+    ) -> Vec<f32> {
+        let rx = kernel_x.len() / 2;
+        let ry = kernel_y.len() / 2;
+
+        // Horizontal pass
+        let mut sum = vec![0.0f64; rows * cols];
+        let mut weight = vec![0.0f64; rows * cols];
+        for row in 0..rows {
+            for col in 0..cols {
+                let (mut s, mut w) = (0.0, 0.0);
+                for (k, kw) in kernel_x.iter().enumerate() {
+                    let c = col as isize + k as isize - rx as isize;
+                    if c < 0 || c >= cols as isize {
+                        continue;
+                    }
+                    let v = values[row * cols + c as usize];
+                    if self.is_valid(v) {
+                        s += kw * v as f64;
+                        w += kw;
+                    }
+                }
+                sum[row * cols + col] = s;
+                weight[row * cols + col] = w;
+            }
+        }
+
+        // Vertical pass
+        let mut output = values.to_vec();
+        for row in 0..rows {
+            for col in 0..cols {
+                let index = row * cols + col;
+                if !self.is_valid(values[index]) {
+                    continue;
+                }
+                let (mut s, mut w) = (0.0, 0.0);
+                for (k, kw) in kernel_y.iter().enumerate() {
+                    let r = row as isize + k as isize - ry as isize;
+                    if r < 0 || r >= rows as isize {
+                        continue;
+                    }
+                    s += kw * sum[r as usize * cols + col];
+                    w += kw * weight[r as usize * cols + col];
+                }
+                if w > 0.0 {
+                    output[index] = (s / w) as f32;
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Median over a square window of valid samples
+    fn median_filter(&self, values: &[f32], rows: usize, cols: usize, radius: usize) -> Vec<f32> {
+        let mut output = values.to_vec();
+        let mut window = Vec::with_capacity((2 * radius + 1).pow(2));
+
+        for row in 0..rows {
+            for col in 0..cols {
+                if !self.is_valid(values[row * cols + col]) {
+                    continue;
+                }
+
+                window.clear();
+                for r in row.saturating_sub(radius)..(row + radius + 1).min(rows) {
+                    for c in col.saturating_sub(radius)..(col + radius + 1).min(cols) {
+                        let v = values[r * cols + c];
+                        if self.is_valid(v) {
+                            window.push(v);
+                        }
+                    }
+                }
+
+                let mid = window.len() / 2;
+                window.select_nth_unstable_by(mid, f32::total_cmp);
+                let upper = window[mid];
+                output[row * cols + col] = if window.len() % 2 == 1 {
+                    upper
+                } else {
+                    // Even count: average the two middle values
+                    let lower = window[..mid]
+                        .iter()
+                        .copied()
+                        .fold(f32::NEG_INFINITY, f32::max);
+                    (lower + upper) / 2.0
+                };
+            }
+        }
+
+        output
+    }
+}
+
+/// A grid view with filtered values and the source grid's coordinates
+#[derive(Debug, Clone)]
+pub struct FilteredGrid<'a, G: GridSource + ?Sized> {
+    /// The grid providing coordinates
+    source: &'a G,
+    /// Filtered values in row-major order
+    values: Vec<f32>,
+    /// Configuration for marching squares algorithm
+    config: MarchingSquaresConfig,
+}
+
+impl<'a, G: GridSource + ?Sized> FilteredGrid<'a, G> {
+    /// Filter the values of `source`
+    ///
+    /// The view inherits the source grid's configuration.
+    ///
+    /// # Errors
+    ///
+    /// See [`ValueFilter::apply`]
+    pub fn new(source: &'a G, filter: &ValueFilter) -> Result<Self> {
+        Ok(Self {
+            source,
+            values: filter.apply(source)?,
+            config: source.config().clone(),
+        })
+    }
+
+    /// Get the number of rows in the grid
+    pub fn rows(&self) -> usize {
+        self.source.rows()
+    }
+
+    /// Get the number of columns in the grid
+    pub fn cols(&self) -> usize {
+        self.source.cols()
+    }
+
+    /// Get the filtered values in row-major order
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Get the filtered grid point at a specific position
+    ///
+    /// Returns `None` if indices are out of bounds
+    pub fn get(&self, row: usize, col: usize) -> Option<GridPoint> {
+        if row < self.rows() && col < self.cols() {
+            Some(GridSource::point(self, row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Get a reference to the configuration
+    pub fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+
+    /// Get a mutable reference to the configuration
+    pub fn config_mut(&mut self) -> &mut MarchingSquaresConfig {
+        &mut self.config
+    }
+
+    /// Copy the filtered grid into an owned [`GeoGrid`]
+    pub fn to_geo_grid(&self) -> Result<GeoGrid> {
+        let points = (0..self.rows())
+            .map(|row| {
+                (0..self.cols())
+                    .map(|col| GridSource::point(self, row, col))
+                    .collect()
+            })
+            .collect();
+        GeoGrid::from_points_with_config(points, self.config.clone())
+    }
+
+    /// Generate isobands (filled contours) on the filtered values
+    ///
+    /// See [`GeoGrid::isobands`] for details.
+    pub fn isobands(&self, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        validate_thresholds(thresholds)?;
+        generate_isobands(self, thresholds)
+    }
+
+    /// Generate isolines (contour lines) on the filtered values
+    ///
+    /// See [`GeoGrid::isolines`] for details.
+    pub fn isolines(&self, levels: &[f64]) -> Result<Vec<Feature>> {
+        validate_levels(levels)?;
+        generate_isolines(self, levels)
+    }
+}
+
+impl<G: GridSource + ?Sized> GridSource for FilteredGrid<'_, G> {
+    fn rows(&self) -> usize {
+        self.source.rows()
+    }
+
+    fn cols(&self) -> usize {
+        self.source.cols()
+    }
+
+    fn point(&self, row: usize, col: usize) -> GridPoint {
+        let mut point = self.source.point(row, col);
+        point.value = self.values[row * self.source.cols() + col];
+        point
+    }
+
+    fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+}
+
+impl GeoGrid {
+    /// Get a view of this grid with smoothed values and the same coordinates
+    ///
+    /// See [`ValueFilter`] for the available filters.
+    ///
+    /// # Errors
+    ///
+    /// See [`ValueFilter::apply`]
+    pub fn smooth(&self, filter: &ValueFilter) -> Result<FilteredGrid<'_, Self>> {
+        FilteredGrid::new(self, filter)
+    }
+}
+
+/// Gaussian kernel truncated at 3 sigma (weights are normalized during convolution)
+fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    let radius = (3.0 * sigma).ceil() as isize;
+    (-radius..=radius)
+        .map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect()
+}
+
+/// Estimate the mean (column, row) spacing of a grid in kilometers
+///
+/// Samples about 100 rows and columns, which is plenty for smooth model grids.
+fn mean_spacing_km<G: GridSource + ?Sized>(grid: &G) -> (f64, f64) {
+    let rows = grid.rows();
+    let cols = grid.cols();
+    let row_step = rows.div_ceil(100).max(1);
+    let col_step = cols.div_ceil(100).max(1);
+
+    let (mut dx, mut nx, mut dy, mut ny) = (0.0, 0usize, 0.0, 0usize);
+    for row in (0..rows).step_by(row_step) {
+        for col in (0..cols).step_by(col_step) {
+            let p = grid.point(row, col);
+            if col + 1 < cols {
+                let q = grid.point(row, col + 1);
+                dx += haversine_km(p.lon, p.lat, q.lon, q.lat);
+                nx += 1;
+            }
+            if row + 1 < rows {
+                let q = grid.point(row + 1, col);
+                dy += haversine_km(p.lon, p.lat, q.lon, q.lat);
+                ny += 1;
+            }
+        }
+    }
+
+    (dx / nx.max(1) as f64, dy / ny.max(1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_grid(values: &[[f32; 5]; 5]) -> GeoGrid {
+        let points = (0..5)
+            .map(|row| {
+                (0..5)
+                    .map(|col| {
+                        GridPoint::new(
+                            -100.0 + col as f64 * 0.1,
+                            40.0 - row as f64 * 0.1,
+                            values[row][col],
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        GeoGrid::from_points(points).unwrap()
+    }
+
+    const SPIKE: [[f32; 5]; 5] = [
+        [0.0, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 90.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.0],
+    ];
+
+    #[test]
+    fn test_box_filter_spreads_spike() {
+        let grid = create_test_grid(&SPIKE);
+        let smoothed = grid.smooth(&ValueFilter::box_mean(1)).unwrap();
+        assert!((smoothed.get(2, 2).unwrap().value - 10.0).abs() < 1e-5);
+        assert!((smoothed.get(1, 1).unwrap().value - 10.0).abs() < 1e-5);
+        assert_eq!(smoothed.get(0, 0).unwrap().value, 0.0);
+        // Coordinates come from the source grid
+        assert_eq!(smoothed.get(3, 4).unwrap().lon, grid.get(3, 4).unwrap().lon);
+    }
+
+    #[test]
+    fn test_median_removes_spike() {
+        let grid = create_test_grid(&SPIKE);
+        let smoothed = grid.smooth(&ValueFilter::median(1)).unwrap();
+        assert!(smoothed.values().iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn test_gaussian_preserves_constant_field() {
+        let grid = create_test_grid(&[[7.0; 5]; 5]);
+        for sigma in [Sigma::Cells(1.5), Sigma::Kilometers(12.0)] {
+            let smoothed = grid.smooth(&ValueFilter::gaussian(sigma)).unwrap();
+            assert!(smoothed.values().iter().all(|&v| (v - 7.0).abs() < 1e-5));
+        }
+        assert!(grid
+            .smooth(&ValueFilter::gaussian(Sigma::Cells(0.0)))
+            .is_err());
+    }
+
+    #[test]
+    fn test_nodata_is_respected() {
+        let mut values = [[5.0; 5]; 5];
+        values[2][2] = -9999.0;
+        values[0][0] = f32::NAN;
+        let grid = create_test_grid(&values);
+
+        for filter in [
+            ValueFilter::box_mean(1),
+            ValueFilter::median(1),
+            ValueFilter::gaussian(Sigma::Cells(1.0)),
+        ] {
+            let smoothed = grid.smooth(&filter.with_nodata(-9999.0)).unwrap();
+            assert_eq!(smoothed.get(2, 2).unwrap().value, -9999.0);
+            assert!(smoothed.get(0, 0).unwrap().value.is_nan());
+            // Neighbors of missing cells are not pulled towards the sentinel
+            assert!((smoothed.get(2, 1).unwrap().value - 5.0).abs() < 1e-5);
+            assert!((smoothed.get(1, 1).unwrap().value - 5.0).abs() < 1e-5);
+        }
+    }
+}
@@ -1,10 +1,211 @@
 //! Grid structure for managing pre-transformed geographic coordinate grids
 
+use crate::bands::{validate_bands, validate_level_descriptors, BandDescriptor, LevelDescriptor};
+use crate::control::ContourControl;
 use crate::error::{Error, Result};
-use crate::marching_squares::{generate_isobands, generate_isolines};
-use crate::types::{GridPoint, MarchingSquaresConfig};
+use crate::marching_squares::{
+    generate_described_isobands, generate_described_isolines, generate_isoband_polygons,
+    generate_isobands, generate_isobands_controlled, generate_isobands_geo,
+    generate_isobands_reported, generate_isolines, generate_isolines_controlled,
+    generate_isolines_geo, generate_isolines_reported,
+};
+use crate::report::ContourReport;
+use crate::types::{GridPoint, Isoband, Isoline, MarchingSquaresConfig};
 use geojson::Feature;
 
+/// Read-only access to a grid of geographic points
+///
+/// The contouring pipeline only ever reads the four corners of each cell, so it
+/// is written against this trait rather than against [`GeoGrid`] directly. This
+/// lets borrowed views (for example over `ndarray` arrays) be contoured in place
+/// without first materializing a `Vec<Vec<GridPoint>>`.
+///
+/// Implementations must have at least 2 rows and 2 columns, and `point` must be
+/// valid for every `row < rows()` and `col < cols()`.
+pub trait GridSource: Sync {
+    /// Number of rows in the grid
+    fn rows(&self) -> usize;
+
+    /// Number of columns in the grid
+    fn cols(&self) -> usize;
+
+    /// Get the grid point at the given row and column
+    ///
+    /// Indices are guaranteed by the caller to be in bounds.
+    fn point(&self, row: usize, col: usize) -> GridPoint;
+
+    /// Configuration for marching squares algorithm
+    fn config(&self) -> &MarchingSquaresConfig;
+
+    /// Generate isobands as `geo-types` MultiPolygons for the given thresholds
+    ///
+    /// Produces the same bands as [`GeoGrid::isobands`], but returns typed
+    /// geometries that can be passed straight to `geo` algorithms. Empty bands
+    /// are omitted; each [`Isoband`] carries its own levels.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Fewer than 2 thresholds are provided
+    /// - Thresholds are not in ascending order
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+    ///
+    /// let grid = GeoGrid::from_points(vec![
+    ///     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+    ///     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+    /// ])?;
+    /// for band in grid.isobands_geo(&[12.0, 18.0, 22.0])? {
+    ///     println!("{}-{}: {} polygons", band.lower, band.upper, band.polygons.0.len());
+    /// }
+    /// # Ok::<(), geo_marching_squares_rs::Error>(())
+    /// ```
+    fn isobands_geo(&self, thresholds: &[f64]) -> Result<Vec<Isoband>> {
+        validate_thresholds(thresholds)?;
+        generate_isobands_geo(self, thresholds)
+    }
+
+    /// Generate isolines as `geo-types` MultiLineStrings for the given levels
+    ///
+    /// Produces the same lines as [`GeoGrid::isolines`]. Levels with no
+    /// crossings are omitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no levels are provided
+    fn isolines_geo(&self, levels: &[f64]) -> Result<Vec<Isoline>> {
+        validate_levels(levels)?;
+        generate_isolines_geo(self, levels)
+    }
+
+    /// Generate isoband features for the given band descriptors
+    ///
+    /// Like [`GeoGrid::isobands`], with each feature also carrying its
+    /// descriptor's id and properties. Bands need not be contiguous. Empty
+    /// bands are omitted and the rest keep descriptor order, also when bands
+    /// are generated in parallel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no bands are given or a band's lower threshold is
+    /// not below its upper threshold
+    fn isobands_described(&self, bands: &[BandDescriptor]) -> Result<Vec<Feature>> {
+        validate_bands(bands)?;
+        generate_described_isobands(self, bands)
+    }
+
+    /// Generate one isoband feature per polygon for the given thresholds
+    ///
+    /// Instead of one MultiPolygon per band, each polygon (exterior ring plus
+    /// its holes) becomes a `Polygon` feature with a deterministic numeric id
+    /// `band_index << 32 | polygon_index`, for client-side hit-testing and
+    /// feature-state styling. Polygons within a band are ordered north to
+    /// south, then west to east, by their northernmost vertex.
+    ///
+    /// Each feature carries `lower_level`, `upper_level`, `band_index`,
+    /// `polygon_index`, its geodesic `area_km2` (holes excluded) and its
+    /// area-weighted `centroid` as `[lon, lat]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Fewer than 2 thresholds are provided
+    /// - Thresholds are not in ascending order
+    fn isoband_polygons(&self, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        validate_thresholds(thresholds)?;
+        generate_isoband_polygons(self, thresholds)
+    }
+
+    /// Generate isoband features along with a [`ContourReport`]
+    ///
+    /// Produces the same features as [`GeoGrid::isobands`]. The report has
+    /// one [`BandReport`](crate::report::BandReport) per threshold pair,
+    /// including empty bands, with rings traced, failed or abandoned traces
+    /// and their cell locations, cells per shape class, vertex counts and
+    /// timing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Fewer than 2 thresholds are provided
+    /// - Thresholds are not in ascending order
+    fn isobands_with_report(&self, thresholds: &[f64]) -> Result<(Vec<Feature>, ContourReport)> {
+        validate_thresholds(thresholds)?;
+        generate_isobands_reported(self, thresholds, &ContourControl::default())
+    }
+
+    /// Generate isoline features along with a [`ContourReport`]
+    ///
+    /// Produces the same features as [`GeoGrid::isolines`]. The report has
+    /// one [`LevelReport`](crate::report::LevelReport) per level, including
+    /// levels with no crossings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no levels are provided
+    fn isolines_with_report(&self, levels: &[f64]) -> Result<(Vec<Feature>, ContourReport)> {
+        validate_levels(levels)?;
+        generate_isolines_reported(self, levels, &ContourControl::default())
+    }
+
+    /// Generate isoband features with cancellation and progress reporting
+    ///
+    /// Produces the same features as [`GeoGrid::isobands`]. The run stops
+    /// with [`Error::Cancelled`](crate::Error::Cancelled) once the control's
+    /// token is cancelled or its deadline passes; progress is reported after
+    /// each band. See [`control`](crate::control).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Fewer than 2 thresholds are provided
+    /// - Thresholds are not in ascending order
+    /// - The run is cancelled
+    fn isobands_with_control(
+        &self,
+        thresholds: &[f64],
+        control: &ContourControl,
+    ) -> Result<Vec<Feature>> {
+        validate_thresholds(thresholds)?;
+        generate_isobands_controlled(self, thresholds, control)
+    }
+
+    /// Generate isoline features with cancellation and progress reporting
+    ///
+    /// Produces the same features as [`GeoGrid::isolines`]. The run stops
+    /// with [`Error::Cancelled`](crate::Error::Cancelled) once the control's
+    /// token is cancelled or its deadline passes; progress is reported after
+    /// each level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no levels are provided or the run is cancelled
+    fn isolines_with_control(
+        &self,
+        levels: &[f64],
+        control: &ContourControl,
+    ) -> Result<Vec<Feature>> {
+        validate_levels(levels)?;
+        generate_isolines_controlled(self, levels, control)
+    }
+
+    /// Generate isoline features for the given level descriptors
+    ///
+    /// Like [`GeoGrid::isolines`], with each feature also carrying its
+    /// descriptor's id and properties. Levels with no crossings are omitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no levels are given
+    fn isolines_described(&self, levels: &[LevelDescriptor]) -> Result<Vec<Feature>> {
+        validate_level_descriptors(levels)?;
+        generate_described_isolines(self, levels)
+    }
+}
+
 /// A geographic grid with pre-transformed coordinates
 ///
 /// This structure holds a 2D grid of points with geographic coordinates (lon, lat)
@@ -75,12 +276,7 @@ impl GeoGrid {
         let rows = points.len();
         let cols = points[0].len();
 
-        if rows < 2 || cols < 2 {
-            return Err(Error::invalid_dimensions(format!(
-                "Grid must be at least 2x2, got {}x{}",
-                rows, cols
-            )));
-        }
+        validate_dimensions(rows, cols)?;
 
         // Validate all rows have same length
         for (i, row) in points.iter().enumerate() {
@@ -189,21 +385,7 @@ impl GeoGrid {
     /// # Ok::<(), geo_marching_squares_rs::Error>(())
     /// ```
     pub fn isobands(&self, thresholds: &[f64]) -> Result<Vec<Feature>> {
-        if thresholds.len() < 2 {
-            return Err(Error::invalid_thresholds(
-                "At least 2 thresholds required for isobands",
-            ));
-        }
-
-        // Validate thresholds are sorted
-        for i in 1..thresholds.len() {
-            if thresholds[i] <= thresholds[i - 1] {
-                return Err(Error::invalid_thresholds(
-                    "Thresholds must be in ascending order",
-                ));
-            }
-        }
-
+        validate_thresholds(thresholds)?;
         generate_isobands(self, thresholds)
     }
 
@@ -237,12 +419,7 @@ impl GeoGrid {
     /// # Ok::<(), geo_marching_squares_rs::Error>(())
     /// ```
     pub fn isolines(&self, levels: &[f64]) -> Result<Vec<Feature>> {
-        if levels.is_empty() {
-            return Err(Error::invalid_thresholds(
-                "At least 1 level required for isolines",
-            ));
-        }
-
+        validate_levels(levels)?;
         generate_isolines(self, levels)
     }
 
@@ -286,6 +463,65 @@ impl GeoGrid {
     }
 }
 
+impl GridSource for GeoGrid {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn point(&self, row: usize, col: usize) -> GridPoint {
+        self.points[row][col]
+    }
+
+    fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+}
+
+/// Check that a grid is large enough to contain at least one cell
+pub(crate) fn validate_dimensions(rows: usize, cols: usize) -> Result<()> {
+    if rows < 2 || cols < 2 {
+        return Err(Error::invalid_dimensions(format!(
+            "Grid must be at least 2x2, got {}x{}",
+            rows, cols
+        )));
+    }
+    Ok(())
+}
+
+/// Check isoband thresholds: at least 2 values, strictly ascending
+pub(crate) fn validate_thresholds(thresholds: &[f64]) -> Result<()> {
+    if thresholds.len() < 2 {
+        return Err(Error::invalid_thresholds(
+            "At least 2 thresholds required for isobands",
+        ));
+    }
+
+    // Validate thresholds are sorted
+    for i in 1..thresholds.len() {
+        if thresholds[i] <= thresholds[i - 1] {
+            return Err(Error::invalid_thresholds(
+                "Thresholds must be in ascending order",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check isoline levels: at least 1 value
+pub(crate) fn validate_levels(levels: &[f64]) -> Result<()> {
+    if levels.is_empty() {
+        return Err(Error::invalid_thresholds(
+            "At least 1 level required for isolines",
+        ));
+    }
+    Ok(())
+}
+
 /// Implement IntoIterator for GeoGrid references
 impl<'a> IntoIterator for &'a GeoGrid {
     type Item = &'a GridPoint;
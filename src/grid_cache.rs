@@ -0,0 +1,526 @@
+//! Binary grid cache format
+//!
+//! Computing lon/lat for a large projected grid is expensive, so the geometry can be
+//! written once to a compact binary file and loaded back by any number of processes.
+//! Values are optional: a coordinate-only file can be shared by several workers, each
+//! swapping in its own value array with [`GridCache::with_values`].
+//!
+//! # File Layout
+//!
+//! All numbers are little-endian. The header is 32 bytes, so both coordinate arrays
+//! start 8-byte aligned.
+//!
+//! ```text
+//! offset  size          field
+//! 0       8             magic "GMSGRID\0"
+//! 8       4             format version (u32, currently 1)
+//! 12      4             flags (u32, bit 0 = values present)
+//! 16      8             rows (u64)
+//! 24      8             cols (u64)
+//! 32      8*rows*cols   longitudes (f64, row-major)
+//! ..      8*rows*cols   latitudes (f64, row-major)
+//! ..      4*rows*cols   values (f32, row-major, only if flag bit 0 is set)
+//! ```
+//!
+//! Loading only parses the header and checks the file length, so it takes constant
+//! time. Coordinates are decoded on access while contouring.
+//!
+//! With the `mmap` feature, [`MappedGridCache`] memory-maps a cache file so the
+//! operating system shares the pages between processes.
+
+use crate::error::{Error, Result};
+use crate::grid::{validate_dimensions, validate_levels, validate_thresholds, GridSource};
+use crate::marching_squares::{generate_isobands, generate_isolines};
+use crate::types::{GridPoint, MarchingSquaresConfig};
+use geojson::Feature;
+use std::io::Write;
+
+/// File magic identifying a grid cache
+pub const GRID_CACHE_MAGIC: [u8; 8] = *b"GMSGRID\0";
+
+/// Current grid cache format version
+pub const GRID_CACHE_VERSION: u32 = 1;
+
+/// Size of the fixed header in bytes
+const HEADER_LEN: usize = 32;
+
+/// Header flag: value array present
+const FLAG_HAS_VALUES: u32 = 1;
+
+/// Write a grid to the cache format
+///
+/// # Arguments
+///
+/// * `grid` - Grid to write
+/// * `writer` - Destination (wrap files in a `BufWriter`)
+/// * `include_values` - Whether to store the values alongside the coordinates
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use geo_marching_squares_rs::{GeoGrid, GridPoint};
+/// use geo_marching_squares_rs::grid_cache::write_grid_cache;
+/// use std::io::BufWriter;
+///
+/// # let grid = GeoGrid::from_points(vec![
+/// #     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+/// #     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+/// # ])?;
+/// let file = std::fs::File::create("hrrr_conus.grid")?;
+/// write_grid_cache(&grid, BufWriter::new(file), false)?;
+/// # Ok::<(), geo_marching_squares_rs::Error>(())
+/// ```
+pub fn write_grid_cache<G, W>(grid: &G, mut writer: W, include_values: bool) -> Result<()>
+where
+    G: GridSource + ?Sized,
+    W: Write,
+{
+    let rows = grid.rows();
+    let cols = grid.cols();
+    let flags = if include_values { FLAG_HAS_VALUES } else { 0 };
+
+    writer.write_all(&GRID_CACHE_MAGIC)?;
+    writer.write_all(&GRID_CACHE_VERSION.to_le_bytes())?;
+    writer.write_all(&flags.to_le_bytes())?;
+    writer.write_all(&(rows as u64).to_le_bytes())?;
+    writer.write_all(&(cols as u64).to_le_bytes())?;
+
+    for row in 0..rows {
+        for col in 0..cols {
+            writer.write_all(&grid.point(row, col).lon.to_le_bytes())?;
+        }
+    }
+    for row in 0..rows {
+        for col in 0..cols {
+            writer.write_all(&grid.point(row, col).lat.to_le_bytes())?;
+        }
+    }
+    if include_values {
+        for row in 0..rows {
+            for col in 0..cols {
+                writer.write_all(&grid.point(row, col).value.to_le_bytes())?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// A parsed grid cache borrowing its bytes
+///
+/// The bytes typically come from a memory map ([`MappedGridCache`]) but any buffer
+/// works, e.g. a file read into a `Vec<u8>`.
+#[derive(Debug, Clone, Copy)]
+pub struct GridCache<'a> {
+    /// Number of rows
+    rows: usize,
+    /// Number of columns
+    cols: usize,
+    /// Longitude array bytes
+    lon: &'a [u8],
+    /// Latitude array bytes
+    lat: &'a [u8],
+    /// Value array bytes, if stored in the file
+    values: Option<&'a [u8]>,
+}
+
+impl<'a> GridCache<'a> {
+    /// Parse a grid cache from its bytes
+    ///
+    /// Only the header is read; coordinates are not validated again since they were
+    /// validated when the source grid was built.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the magic, version or length does not match
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LEN || bytes[..8] != GRID_CACHE_MAGIC {
+            return Err(Error::invalid_cache("Not a grid cache file"));
+        }
+
+        let version = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        if version != GRID_CACHE_VERSION {
+            return Err(Error::invalid_cache(format!(
+                "Unsupported format version {} (expected {})",
+                version, GRID_CACHE_VERSION
+            )));
+        }
+
+        let flags = u32::from_le_bytes(bytes[12..16].try_into().unwrap());
+        let dimension = |field: &[u8]| {
+            usize::try_from(u64::from_le_bytes(field.try_into().unwrap()))
+                .map_err(|_| Error::invalid_cache("Grid dimensions overflow"))
+        };
+        let rows = dimension(&bytes[16..24])?;
+        let cols = dimension(&bytes[24..32])?;
+        validate_dimensions(rows, cols)?;
+
+        let overflow = || Error::invalid_cache("Grid dimensions overflow");
+        let count = rows.checked_mul(cols).ok_or_else(overflow)?;
+        let coords_len = count.checked_mul(8).ok_or_else(overflow)?;
+        let values_len = if flags & FLAG_HAS_VALUES != 0 {
+            count.checked_mul(4).ok_or_else(overflow)?
+        } else {
+            0
+        };
+        let expected = coords_len
+            .checked_mul(2)
+            .and_then(|len| len.checked_add(values_len))
+            .and_then(|len| len.checked_add(HEADER_LEN))
+            .ok_or_else(overflow)?;
+        if bytes.len() != expected {
+            return Err(Error::invalid_cache(format!(
+                "Expected {} bytes for a {}x{} grid, got {}",
+                expected,
+                rows,
+                cols,
+                bytes.len()
+            )));
+        }
+
+        let lon_start = HEADER_LEN;
+        let lat_start = lon_start + coords_len;
+        let values_start = lat_start + coords_len;
+
+        Ok(Self {
+            rows,
+            cols,
+            lon: &bytes[lon_start..lat_start],
+            lat: &bytes[lat_start..values_start],
+            values: (values_len > 0).then(|| &bytes[values_start..]),
+        })
+    }
+
+    /// Get the number of rows in the grid
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the number of columns in the grid
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Whether the file stores values alongside the coordinates
+    pub fn has_values(&self) -> bool {
+        self.values.is_some()
+    }
+
+    /// Get the (lon, lat) of a grid point
+    ///
+    /// Returns `None` if indices are out of bounds
+    pub fn coordinates(&self, row: usize, col: usize) -> Option<(f64, f64)> {
+        if row < self.rows && col < self.cols {
+            let index = row * self.cols + col;
+            Some((read_f64(self.lon, index), read_f64(self.lat, index)))
+        } else {
+            None
+        }
+    }
+
+    /// Get a contourable grid using the values stored in the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file holds coordinates only
+    pub fn grid(&self) -> Result<CachedGrid<'a>> {
+        let values = self
+            .values
+            .ok_or_else(|| Error::invalid_cache("Cache file has no values"))?;
+
+        Ok(CachedGrid {
+            cache: *self,
+            values: CachedValues::Bytes(values),
+            config: MarchingSquaresConfig::default(),
+        })
+    }
+
+    /// Get a contourable grid using the cached coordinates and the given values
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - Values in row-major order, `rows * cols` long
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value count does not match the grid
+    pub fn with_values<'v>(&self, values: &'v [f32]) -> Result<CachedGrid<'v>>
+    where
+        'a: 'v,
+    {
+        if values.len() != self.rows * self.cols {
+            return Err(Error::invalid_dimensions(format!(
+                "Expected {} values for a {}x{} grid, got {}",
+                self.rows * self.cols,
+                self.rows,
+                self.cols,
+                values.len()
+            )));
+        }
+
+        Ok(CachedGrid {
+            cache: *self,
+            values: CachedValues::Slice(values),
+            config: MarchingSquaresConfig::default(),
+        })
+    }
+}
+
+/// Where a [`CachedGrid`] reads its values from
+#[derive(Debug, Clone, Copy)]
+enum CachedValues<'a> {
+    /// Little-endian f32 array inside the cache file
+    Bytes(&'a [u8]),
+    /// Caller-supplied values
+    Slice(&'a [f32]),
+}
+
+/// A contourable grid backed by a [`GridCache`]
+#[derive(Debug, Clone)]
+pub struct CachedGrid<'a> {
+    /// Cached grid geometry
+    cache: GridCache<'a>,
+    /// Grid values
+    values: CachedValues<'a>,
+    /// Configuration for marching squares algorithm
+    config: MarchingSquaresConfig,
+}
+
+impl CachedGrid<'_> {
+    /// Get the number of rows in the grid
+    pub fn rows(&self) -> usize {
+        self.cache.rows
+    }
+
+    /// Get the number of columns in the grid
+    pub fn cols(&self) -> usize {
+        self.cache.cols
+    }
+
+    /// Get the grid point at a specific position
+    ///
+    /// Returns `None` if indices are out of bounds
+    pub fn get(&self, row: usize, col: usize) -> Option<GridPoint> {
+        if row < self.cache.rows && col < self.cache.cols {
+            Some(GridSource::point(self, row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Get a reference to the configuration
+    pub fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+
+    /// Get a mutable reference to the configuration
+    pub fn config_mut(&mut self) -> &mut MarchingSquaresConfig {
+        &mut self.config
+    }
+
+    /// Generate isobands (filled contours) for the given thresholds
+    ///
+    /// See [`GeoGrid::isobands`](crate::GeoGrid::isobands) for details.
+    pub fn isobands(&self, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        validate_thresholds(thresholds)?;
+        generate_isobands(self, thresholds)
+    }
+
+    /// Generate isolines (contour lines) for the given levels
+    ///
+    /// See [`GeoGrid::isolines`](crate::GeoGrid::isolines) for details.
+    pub fn isolines(&self, levels: &[f64]) -> Result<Vec<Feature>> {
+        validate_levels(levels)?;
+        generate_isolines(self, levels)
+    }
+}
+
+impl GridSource for CachedGrid<'_> {
+    fn rows(&self) -> usize {
+        self.cache.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cache.cols
+    }
+
+    fn point(&self, row: usize, col: usize) -> GridPoint {
+        let index = row * self.cache.cols + col;
+        let value = match self.values {
+            CachedValues::Bytes(bytes) => read_f32(bytes, index),
+            CachedValues::Slice(values) => values[index],
+        };
+        GridPoint::new(
+            read_f64(self.cache.lon, index),
+            read_f64(self.cache.lat, index),
+            value,
+        )
+    }
+
+    fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+}
+
+/// A memory-mapped grid cache file
+///
+/// Requires the `mmap` feature.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use geo_marching_squares_rs::grid_cache::MappedGridCache;
+///
+/// let mapped = MappedGridCache::open("hrrr_conus.grid")?;
+/// let cache = mapped.cache()?;
+///
+/// let values: Vec<f32> = vec![0.0; cache.rows() * cache.cols()];
+/// let isobands = cache.with_values(&values)?.isobands(&[0.0, 10.0, 20.0])?;
+/// # Ok::<(), geo_marching_squares_rs::Error>(())
+/// ```
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct MappedGridCache {
+    /// The mapped file
+    mmap: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedGridCache {
+    /// Memory-map a grid cache file and check its header
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be mapped or is not a valid grid cache
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map is read-only. Cache files are written once and then shared,
+        // so they are not expected to be modified while mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        GridCache::from_bytes(&mmap)?;
+        Ok(Self { mmap })
+    }
+
+    /// Get the parsed cache view of the mapped bytes
+    pub fn cache(&self) -> Result<GridCache<'_>> {
+        GridCache::from_bytes(&self.mmap)
+    }
+}
+
+/// Read the f64 at `index` from a little-endian array
+#[inline]
+fn read_f64(bytes: &[u8], index: usize) -> f64 {
+    let start = index * 8;
+    f64::from_le_bytes(bytes[start..start + 8].try_into().unwrap())
+}
+
+/// Read the f32 at `index` from a little-endian array
+#[inline]
+fn read_f32(bytes: &[u8], index: usize) -> f32 {
+    let start = index * 4;
+    f32::from_le_bytes(bytes[start..start + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GeoGrid;
+
+    fn create_test_grid() -> GeoGrid {
+        GeoGrid::from_points(vec![
+            vec![
+                GridPoint::new(-100.0, 41.0, 10.0),
+                GridPoint::new(-99.0, 41.0, 20.0),
+                GridPoint::new(-98.0, 41.0, 30.0),
+            ],
+            vec![
+                GridPoint::new(-100.0, 40.0, 15.0),
+                GridPoint::new(-99.0, 40.0, 25.0),
+                GridPoint::new(-98.0, 40.0, 35.0),
+            ],
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_with_values() {
+        let grid = create_test_grid();
+        let mut bytes = Vec::new();
+        write_grid_cache(&grid, &mut bytes, true).unwrap();
+
+        let cache = GridCache::from_bytes(&bytes).unwrap();
+        assert_eq!(cache.rows(), 2);
+        assert_eq!(cache.cols(), 3);
+        assert!(cache.has_values());
+        assert_eq!(cache.coordinates(1, 2), Some((-98.0, 40.0)));
+
+        let cached = cache.grid().unwrap();
+        for row in 0..2 {
+            for col in 0..3 {
+                assert_eq!(cached.get(row, col), grid.get(row, col).copied());
+            }
+        }
+
+        let thresholds = [12.0, 22.0, 32.0];
+        assert_eq!(
+            cached.isobands(&thresholds).unwrap().len(),
+            grid.isobands(&thresholds).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_coordinates_only_with_values() {
+        let grid = create_test_grid();
+        let mut bytes = Vec::new();
+        write_grid_cache(&grid, &mut bytes, false).unwrap();
+
+        let cache = GridCache::from_bytes(&bytes).unwrap();
+        assert!(!cache.has_values());
+        assert!(cache.grid().is_err());
+
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let cached = cache.with_values(&values).unwrap();
+        assert_eq!(cached.get(1, 0), Some(GridPoint::new(-100.0, 40.0, 4.0)));
+        assert!(cache.with_values(&values[..5]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        let grid = create_test_grid();
+        let mut bytes = Vec::new();
+        write_grid_cache(&grid, &mut bytes, true).unwrap();
+
+        assert!(GridCache::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(GridCache::from_bytes(b"not a grid").is_err());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[8] = 99;
+        assert!(GridCache::from_bytes(&wrong_version).is_err());
+
+        // rows * cols = 2^62: the byte lengths overflow, which must not wrap
+        // around to a header-only file
+        let mut huge = bytes[..HEADER_LEN].to_vec();
+        huge[16..24].copy_from_slice(&(1u64 << 31).to_le_bytes());
+        huge[24..32].copy_from_slice(&(1u64 << 31).to_le_bytes());
+        assert!(matches!(
+            GridCache::from_bytes(&huge),
+            Err(Error::InvalidCache { .. })
+        ));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_cache() {
+        let grid = create_test_grid();
+        let path = std::env::temp_dir().join(format!("gms_cache_{}.grid", std::process::id()));
+        write_grid_cache(&grid, std::fs::File::create(&path).unwrap(), true).unwrap();
+
+        let mapped = MappedGridCache::open(&path).unwrap();
+        let cached = mapped.cache().unwrap().grid().unwrap();
+        assert_eq!(cached.get(0, 1), grid.get(0, 1).copied());
+
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -244,6 +244,19 @@ pub fn interpolate_point_great_circle(
     Point::from_lon_lat(lon.to_degrees(), lat.to_degrees())
 }
 
+/// Mean Earth radius in kilometers (IUGG)
+pub(crate) const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Great circle distance between two lon/lat points in kilometers (haversine formula)
+pub(crate) fn haversine_km(lon0: f64, lat0: f64, lon1: f64, lat1: f64) -> f64 {
+    let (lat0, lat1) = (lat0.to_radians(), lat1.to_radians());
+    let dlat = lat1 - lat0;
+    let dlon = (lon1 - lon0).to_radians();
+
+    let a = (dlat / 2.0).sin().powi(2) + lat0.cos() * lat1.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,21 +45,37 @@
 //! ```
 
 mod cell_shapes;
+mod clip;
 mod edge_tracing;
 mod error;
 mod grid;
 mod marching_squares;
+#[cfg(feature = "ndarray")]
+mod ndarray_grid;
 mod polygon_util;
 mod simd_ops;
 mod types;
 
+pub mod bands;
+pub mod color;
+pub mod control;
+pub mod filter;
+pub mod grid_cache;
 pub mod interpolation;
+pub mod mask;
+pub mod output;
+pub mod palette;
+pub mod refine;
+pub mod report;
+pub mod thresholds;
 
 pub use error::{Error, Result};
-pub use grid::GeoGrid;
+pub use grid::{GeoGrid, GridSource};
+#[cfg(feature = "ndarray")]
+pub use ndarray_grid::ArrayGrid;
 pub use types::{
-    round_coordinate, Edge, GridPoint, InterpolationMethod, MarchingSquaresConfig, Move, Point,
-    Side,
+    round_coordinate, CoordinatePrecision, Edge, GridPoint, InterpolationMethod, Isoband, Isoline,
+    MarchingSquaresConfig, Move, Point, RoundingMode, Side,
 };
 
 // Re-export commonly used types
@@ -7,14 +7,22 @@
 //! - Phase 1: Simple isoline-based (fast, basic)
 //! - Phase 2: Full edge tracing with polygon nesting (accurate, complex)
 
+use crate::bands::{BandDescriptor, LevelDescriptor};
 use crate::cell_shapes::CellShape;
+use crate::clip::{clip_polyline, CellWindow, Rect};
+use crate::control::ContourControl;
 use crate::edge_tracing::{trace_all_rings, CellWithEdges};
-use crate::error::Result;
-use crate::grid::GeoGrid;
-use crate::interpolation::interpolate_side;
-use crate::polygon_util::organize_polygons;
-use crate::types::{GridPoint, Point, Side};
-use geojson::{Feature, Geometry, Value as GeoValue};
+use crate::error::{Error, Result};
+use crate::grid::GridSource;
+use crate::interpolation::{interpolate_side, EARTH_RADIUS_KM};
+use crate::mask::{CellMask, ClipMask};
+use crate::polygon_util::{nest_rings, organize_polygons};
+use crate::report::{BandReport, ContourReport, LevelReport};
+use crate::types::{CoordinatePrecision, GridPoint, Isoband, Isoline, Point, Side};
+use geo_types::{Coord, LineString, MultiLineString, MultiPolygon, Polygon};
+use geojson::{feature::Id, Feature, Geometry, Value as GeoValue};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
 /// Generate isobands (filled contour polygons) for the given thresholds
 ///
@@ -23,59 +31,302 @@ use geojson::{Feature, Geometry, Value as GeoValue};
 ///
 /// Uses Phase 2 algorithm with edge tracing and polygon nesting.
 /// If the 'parallel' feature is enabled, processes bands concurrently.
-pub fn generate_isobands(grid: &GeoGrid, thresholds: &[f64]) -> Result<Vec<Feature>> {
+pub fn generate_isobands<G: GridSource + ?Sized>(
+    grid: &G,
+    thresholds: &[f64],
+) -> Result<Vec<Feature>> {
+    generate_isobands_controlled(grid, thresholds, &ContourControl::default())
+}
+
+/// Generate isobands with cancellation and progress reporting
+///
+/// Same features as [`generate_isobands`]. Cancellation is checked before
+/// each band and between rows within a band, also on the parallel path;
+/// progress is reported after each band.
+pub fn generate_isobands_controlled<G: GridSource + ?Sized>(
+    grid: &G,
+    thresholds: &[f64],
+    control: &ContourControl,
+) -> Result<Vec<Feature>> {
+    let precision = grid.config().precision;
+    let clipping = Clipping::new(grid)?;
+    map_bands(thresholds, control, |lower, upper| {
+        let (polygons, _) = trace_band_reported(grid, &clipping, lower, upper, control)?;
+        Ok(polygons.map(|polygons| band_feature(&polygons, lower, upper, precision)))
+    })
+}
+
+/// Generate isobands as `geo-types` geometries for the given thresholds
+///
+/// Same bands as [`generate_isobands`], without building GeoJSON coordinates.
+pub fn generate_isobands_geo<G: GridSource + ?Sized>(
+    grid: &G,
+    thresholds: &[f64],
+) -> Result<Vec<Isoband>> {
+    let clipping = Clipping::new(grid)?;
+    map_bands(thresholds, &ContourControl::default(), |lower, upper| {
+        Ok(
+            trace_band(grid, &clipping, lower, upper)?.map(|polygons| Isoband {
+                lower,
+                upper,
+                polygons: band_geometry(&polygons, grid.config().precision),
+            }),
+        )
+    })
+}
+
+/// Generate isobands for the given thresholds, with a run report
+///
+/// Same features as [`generate_isobands_controlled`]. The report has an entry
+/// for every band, including empty ones, in threshold order.
+pub fn generate_isobands_reported<G: GridSource + ?Sized>(
+    grid: &G,
+    thresholds: &[f64],
+    control: &ContourControl,
+) -> Result<(Vec<Feature>, ContourReport)> {
+    let start = Instant::now();
+    let precision = grid.config().precision;
+    let clipping = Clipping::new(grid)?;
+
+    let results = map_bands(thresholds, control, |lower, upper| {
+        let band_start = Instant::now();
+        let (polygons, mut report) = trace_band_reported(grid, &clipping, lower, upper, control)?;
+        let feature = polygons.map(|polygons| band_feature(&polygons, lower, upper, precision));
+        report.elapsed = band_start.elapsed();
+        Ok(Some((feature, report)))
+    })?;
+
+    let (features, bands): (Vec<Option<Feature>>, Vec<BandReport>) = results.into_iter().unzip();
+    let report = ContourReport {
+        bands,
+        elapsed: start.elapsed(),
+        ..ContourReport::default()
+    };
+    Ok((features.into_iter().flatten().collect(), report))
+}
+
+/// Generate isobands for the given band descriptors
+///
+/// Each feature carries its descriptor's id and properties on top of the
+/// level properties. Empty bands are dropped; the rest keep descriptor order.
+pub fn generate_described_isobands<G: GridSource + ?Sized>(
+    grid: &G,
+    bands: &[BandDescriptor],
+) -> Result<Vec<Feature>> {
+    let clipping = Clipping::new(grid)?;
+    map_ordered(bands, &ContourControl::default(), |band| {
+        Ok(
+            generate_isobands_phase2(grid, &clipping, band.lower, band.upper)?
+                .map(|feature| band.apply(feature)),
+        )
+    })
+}
+
+/// Generate one isoband feature per polygon for the given thresholds
+///
+/// Each polygon (an exterior ring with its holes) becomes its own `Polygon`
+/// feature. Polygons are ordered by their northernmost vertex, north to south
+/// and then west to east, and get the numeric id
+/// `band_index << 32 | polygon_index`, where `band_index` is the position of
+/// the band's lower threshold. Ids are therefore stable across runs for the
+/// same grid and thresholds, whatever order the rings were traced in.
+///
+/// Besides the level properties, each feature carries `band_index`,
+/// `polygon_index`, its geodesic `area_km2` (holes excluded) and its planar
+/// area-weighted `centroid` as `[lon, lat]`. The centroid of a concave polygon
+/// may lie outside it.
+pub fn generate_isoband_polygons<G: GridSource + ?Sized>(
+    grid: &G,
+    thresholds: &[f64],
+) -> Result<Vec<Feature>> {
+    let precision = grid.config().precision;
+    let bands: Vec<(usize, f64, f64)> = thresholds
+        .windows(2)
+        .enumerate()
+        .map(|(band, pair)| (band, pair[0], pair[1]))
+        .collect();
+    let clipping = Clipping::new(grid)?;
+
+    let features = map_ordered(
+        &bands,
+        &ContourControl::default(),
+        |&(band, lower, upper)| {
+            Ok(trace_band(grid, &clipping, lower, upper)?
+                .map(|polygons| polygon_features(polygons, band, lower, upper, precision)))
+        },
+    )?;
+    Ok(features.into_iter().flatten().collect())
+}
+
+/// Run `process` for each consecutive threshold pair, dropping empty bands
+///
+/// If the 'parallel' feature is enabled, bands are processed concurrently.
+/// Output order always follows the thresholds.
+fn map_bands<T, F>(thresholds: &[f64], control: &ContourControl, process: F) -> Result<Vec<T>>
+where
+    T: Send,
+    F: Fn(f64, f64) -> Result<Option<T>> + Sync,
+{
+    let pairs: Vec<(f64, f64)> = thresholds
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect();
+    map_ordered(&pairs, control, |&(lower, upper)| process(lower, upper))
+}
+
+/// Run `process` for each item, dropping `None` results
+///
+/// If the 'parallel' feature is enabled, items are processed concurrently.
+/// Output order always follows the input, so each result stays paired with
+/// the item that produced it. Cancellation is checked before each item and
+/// progress is reported after each one.
+fn map_ordered<I, T, F>(items: &[I], control: &ContourControl, process: F) -> Result<Vec<T>>
+where
+    I: Sync,
+    T: Send,
+    F: Fn(&I) -> Result<Option<T>> + Sync,
+{
+    let completed = AtomicUsize::new(0);
+    let process = |item: &I| -> Result<Option<T>> {
+        control.check()?;
+        let result = process(item)?;
+        control.report(completed.fetch_add(1, Ordering::Relaxed) + 1, items.len());
+        Ok(result)
+    };
+
     #[cfg(feature = "parallel")]
     {
         use rayon::prelude::*;
 
-        // Process bands in parallel
-        let features: Result<Vec<Option<Feature>>> = (0..thresholds.len() - 1)
-            .into_par_iter()
-            .map(|i| {
-                let lower = thresholds[i];
-                let upper = thresholds[i + 1];
-                generate_isobands_phase2(grid, lower, upper)
-            })
-            .collect();
+        // Process items in parallel; the first error (or cancellation) stops
+        // the remaining items
+        let results: Result<Vec<Option<T>>> = items.par_iter().map(&process).collect();
 
-        Ok(features?.into_iter().flatten().collect())
+        Ok(results?.into_iter().flatten().collect())
     }
 
     #[cfg(not(feature = "parallel"))]
     {
-        let mut features = Vec::new();
-
-        // Process each band sequentially
-        for i in 0..thresholds.len() - 1 {
-            let lower = thresholds[i];
-            let upper = thresholds[i + 1];
+        let mut results = Vec::new();
 
-            let band = generate_isobands_phase2(grid, lower, upper)?;
-            if let Some(feature) = band {
-                features.push(feature);
+        // Process each item sequentially
+        for item in items {
+            if let Some(result) = process(item)? {
+                results.push(result);
             }
         }
 
-        Ok(features)
+        Ok(results)
     }
 }
 
 /// Generate isolines (contour lines) for the given levels
-pub fn generate_isolines(grid: &GeoGrid, levels: &[f64]) -> Result<Vec<Feature>> {
+pub fn generate_isolines<G: GridSource + ?Sized>(grid: &G, levels: &[f64]) -> Result<Vec<Feature>> {
+    generate_isolines_controlled(grid, levels, &ContourControl::default())
+}
+
+/// Generate isolines with cancellation and progress reporting
+///
+/// Same features as [`generate_isolines`]. Cancellation is checked before
+/// each level and between rows; progress is reported after each level.
+pub fn generate_isolines_controlled<G: GridSource + ?Sized>(
+    grid: &G,
+    levels: &[f64],
+    control: &ContourControl,
+) -> Result<Vec<Feature>> {
+    Ok(generate_isolines_reported(grid, levels, control)?.0)
+}
+
+/// Generate isolines for the given levels, with a run report
+///
+/// Same features as [`generate_isolines_controlled`]. The report has an entry
+/// for every level, including levels with no crossings, in input order.
+pub fn generate_isolines_reported<G: GridSource + ?Sized>(
+    grid: &G,
+    levels: &[f64],
+    control: &ContourControl,
+) -> Result<(Vec<Feature>, ContourReport)> {
+    let start = Instant::now();
     let mut features = Vec::new();
+    let mut reports = Vec::with_capacity(levels.len());
+    let clipping = Clipping::new(grid)?;
+
+    for (i, &level) in levels.iter().enumerate() {
+        control.check()?;
+        let level_start = Instant::now();
+        let (feature, mut report) = process_isoline_reported(grid, &clipping, level, control)?;
+        report.elapsed = level_start.elapsed();
+        features.extend(feature);
+        reports.push(report);
+        control.report(i + 1, levels.len());
+    }
 
-    for &level in levels {
-        let line = process_isoline(grid, level)?;
-        if let Some(feature) = line {
-            features.push(feature);
+    let report = ContourReport {
+        levels: reports,
+        elapsed: start.elapsed(),
+        ..ContourReport::default()
+    };
+    Ok((features, report))
+}
+
+/// Generate isolines for the given level descriptors
+///
+/// Each feature carries its descriptor's id and properties on top of the
+/// `isovalue` property. Levels with no crossings are dropped.
+pub fn generate_described_isolines<G: GridSource + ?Sized>(
+    grid: &G,
+    levels: &[LevelDescriptor],
+) -> Result<Vec<Feature>> {
+    let mut features = Vec::new();
+    let clipping = Clipping::new(grid)?;
+
+    for level in levels {
+        if let Some(feature) = process_isoline(grid, &clipping, level.level)? {
+            features.push(level.apply(feature));
         }
     }
 
     Ok(features)
 }
 
+/// Generate isolines as `geo-types` geometries for the given levels
+///
+/// Same lines as [`generate_isolines`], without building GeoJSON coordinates.
+pub fn generate_isolines_geo<G: GridSource + ?Sized>(
+    grid: &G,
+    levels: &[f64],
+) -> Result<Vec<Isoline>> {
+    let precision = grid.config().precision;
+    let mut isolines = Vec::new();
+    let clipping = Clipping::new(grid)?;
+
+    for &level in levels {
+        let lines: MultiLineString<f64> = isoline_segments(grid, &clipping, level)
+            .iter()
+            .map(|segment| {
+                segment
+                    .iter()
+                    .filter_map(|p| rounded_xy(p, precision))
+                    .map(|(x, y)| Coord { x, y })
+                    .collect::<LineString<f64>>()
+            })
+            .filter(|line| line.0.len() >= 2)
+            .collect();
+
+        if !lines.0.is_empty() {
+            isolines.push(Isoline { level, lines });
+        }
+    }
+
+    Ok(isolines)
+}
+
 /// Process a single isoband between lower and upper thresholds
-fn process_band(grid: &GeoGrid, lower: f64, upper: f64) -> Result<Option<Feature>> {
+fn process_band<G: GridSource + ?Sized>(
+    grid: &G,
+    lower: f64,
+    upper: f64,
+) -> Result<Option<Feature>> {
     let rows = grid.rows();
     let cols = grid.cols();
 
@@ -86,10 +337,10 @@ fn process_band(grid: &GeoGrid, lower: f64, upper: f64) -> Result<Option<Feature
     for row in 0..rows - 1 {
         for col in 0..cols - 1 {
             // Get the four corners of the cell
-            let tl = grid.get(row, col).unwrap();
-            let tr = grid.get(row, col + 1).unwrap();
-            let br = grid.get(row + 1, col + 1).unwrap();
-            let bl = grid.get(row + 1, col).unwrap();
+            let tl = &grid.point(row, col);
+            let tr = &grid.point(row, col + 1);
+            let br = &grid.point(row + 1, col + 1);
+            let bl = &grid.point(row + 1, col);
 
             // Calculate the cell configuration value
             let config = calculate_cell_config(tl, tr, br, bl, lower, upper);
@@ -179,19 +430,106 @@ fn process_band(grid: &GeoGrid, lower: f64, upper: f64) -> Result<Option<Feature
 }
 
 /// Process a single isoline at the given level
-fn process_isoline(grid: &GeoGrid, level: f64) -> Result<Option<Feature>> {
-    let rows = grid.rows();
-    let cols = grid.cols();
+fn process_isoline<G: GridSource + ?Sized>(
+    grid: &G,
+    clipping: &Clipping,
+    level: f64,
+) -> Result<Option<Feature>> {
+    Ok(process_isoline_reported(grid, clipping, level, &ContourControl::default())?.0)
+}
 
-    let mut line_strings: Vec<Vec<Vec<f64>>> = Vec::new();
+/// Process a single isoline, also reporting its statistics
+///
+/// The report's `elapsed` is left for the caller to fill in.
+fn process_isoline_reported<G: GridSource + ?Sized>(
+    grid: &G,
+    clipping: &Clipping,
+    level: f64,
+    control: &ContourControl,
+) -> Result<(Option<Feature>, LevelReport)> {
+    let precision = grid.config().precision;
+    let (segments, cells_crossed) = isoline_segments_counted(grid, clipping, level, control)?;
+    let line_strings: Vec<Vec<Vec<f64>>> = segments
+        .iter()
+        .map(|segment| {
+            segment
+                .iter()
+                .filter_map(|p| rounded_xy(p, precision))
+                .map(|(x, y)| vec![x, y])
+                .collect::<Vec<_>>()
+        })
+        .filter(|line| line.len() >= 2)
+        .collect();
 
-    // Process each cell in the grid
-    for row in 0..rows - 1 {
-        for col in 0..cols - 1 {
-            let tl = grid.get(row, col).unwrap();
-            let tr = grid.get(row, col + 1).unwrap();
-            let br = grid.get(row + 1, col + 1).unwrap();
-            let bl = grid.get(row + 1, col).unwrap();
+    let report = LevelReport {
+        level,
+        cells_crossed,
+        lines: line_strings.len(),
+        vertices: line_strings.iter().map(Vec::len).sum(),
+        ..LevelReport::default()
+    };
+    if line_strings.is_empty() {
+        return Ok((None, report));
+    }
+
+    let geometry = Geometry::new(GeoValue::MultiLineString(line_strings));
+
+    let mut feature = Feature {
+        bbox: None,
+        geometry: Some(geometry),
+        id: None,
+        properties: Some(serde_json::Map::new()),
+        foreign_members: None,
+    };
+
+    if let Some(ref mut props) = feature.properties {
+        props.insert("isovalue".to_string(), serde_json::json!(level));
+    }
+
+    Ok((Some(feature), report))
+}
+
+/// Collect the unrounded line segments of a single isoline, cell by cell
+pub(crate) fn isoline_segments<G: GridSource + ?Sized>(
+    grid: &G,
+    clipping: &Clipping,
+    level: f64,
+) -> Vec<Vec<Point>> {
+    // The default control never cancels
+    isoline_segments_counted(grid, clipping, level, &ContourControl::default())
+        .map(|(segments, _)| segments)
+        .unwrap_or_default()
+}
+
+/// Collect the line segments of a single isoline and count the cells it crosses
+///
+/// Cancellation is checked before each row.
+fn isoline_segments_counted<G: GridSource + ?Sized>(
+    grid: &G,
+    clipping: &Clipping,
+    level: f64,
+    control: &ContourControl,
+) -> Result<(Vec<Vec<Point>>, usize)> {
+    let mut segments_out = Vec::new();
+    let mut cells_crossed = 0;
+
+    let Some(window) = &clipping.window else {
+        return Ok((segments_out, cells_crossed));
+    };
+
+    // Process each cell in the window, skipping those outside the clip mask
+    let mask = grid.config().clip_mask.as_deref();
+    for (i, row) in window.rows.clone().enumerate() {
+        control.check()?;
+        for (j, col) in window.cols.clone().enumerate() {
+            let class = clipping.class(i, j);
+            if class == CellMask::Outside {
+                continue;
+            }
+            let tl = &grid.point(row, col);
+            let tr = &grid.point(row, col + 1);
+            let br = &grid.point(row + 1, col + 1);
+            let bl = &grid.point(row + 1, col);
 
             // Calculate cell configuration for isoline
             let config = calculate_isoline_config(tl, tr, br, bl, level);
@@ -199,6 +537,7 @@ fn process_isoline(grid: &GeoGrid, level: f64) -> Result<Option<Feature>> {
             if config == 0 || config == 15 {
                 continue;
             }
+            cells_crossed += 1;
 
             // Get the line segments for this cell
             if let Some(segments) = get_isoline_segments(
@@ -210,46 +549,23 @@ fn process_isoline(grid: &GeoGrid, level: f64) -> Result<Option<Feature>> {
                 level,
                 grid.config().smoothing_factor.into(),
             ) {
-                for segment in segments {
-                    let line: Vec<Vec<f64>> = segment
+                let segments = match mask {
+                    Some(mask) if class == CellMask::Boundary => segments
                         .iter()
-                        .filter_map(|p| {
-                            match (p.x, p.y) {
-                                (Some(x), Some(y)) => Some(vec![
-                                    crate::types::round_coordinate(x),
-                                    crate::types::round_coordinate(y)
-                                ]),
-                                _ => None,
-                            }
-                        })
-                        .collect();
-                    if line.len() >= 2 {
-                        line_strings.push(line);
-                    }
+                        .flat_map(|segment| mask_line(segment, mask))
+                        .collect(),
+                    _ => segments,
+                };
+                match &clipping.rect {
+                    Some(rect) => segments_out
+                        .extend(segments.iter().flat_map(|segment| clip_line(segment, rect))),
+                    None => segments_out.extend(segments),
                 }
             }
         }
     }
 
-    if line_strings.is_empty() {
-        return Ok(None);
-    }
-
-    let geometry = Geometry::new(GeoValue::MultiLineString(line_strings));
-
-    let mut feature = Feature {
-        bbox: None,
-        geometry: Some(geometry),
-        id: None,
-        properties: Some(serde_json::Map::new()),
-        foreign_members: None,
-    };
-
-    if let Some(ref mut props) = feature.properties {
-        props.insert("isovalue".to_string(), serde_json::json!(level));
-    }
-
-    Ok(Some(feature))
+    Ok((segments_out, cells_crossed))
 }
 
 /// Calculate the configuration value for an isoband cell (3-level comparison)
@@ -685,7 +1001,6 @@ fn get_isoline_segments(
 
     Some(segments)
 }
-
 /// Phase 2: Generate isobands using full edge tracing and polygon nesting
 ///
 /// This is a more accurate implementation that:
@@ -693,38 +1008,89 @@ fn get_isoline_segments(
 /// - Traces complete polygon rings using edge-following
 /// - Organizes polygons with proper hole detection
 /// - Returns MultiPolygons with interior rings
-pub fn generate_isobands_phase2(grid: &GeoGrid, lower: f64, upper: f64) -> Result<Option<Feature>> {
-    let rows = grid.rows();
-    let cols = grid.cols();
+pub fn generate_isobands_phase2<G: GridSource + ?Sized>(
+    grid: &G,
+    clipping: &Clipping,
+    lower: f64,
+    upper: f64,
+) -> Result<Option<Feature>> {
+    Ok(trace_band(grid, clipping, lower, upper)?
+        .map(|polygons| band_feature(&polygons, lower, upper, grid.config().precision)))
+}
 
-    // Create a 2D array of cells with their shapes
-    let mut cells: Vec<Vec<Option<CellWithEdges>>> = Vec::with_capacity(rows - 1);
+/// Traced polygons of one band: each exterior ring with its holes
+///
+/// Rings are unrounded and end with a copy of their first point, as traced;
+/// rings rebuilt by clipping are closed the same way.
+pub(crate) type BandPolygons = Vec<(Vec<Point>, Vec<Vec<Point>>)>;
 
-    for row in 0..rows - 1 {
-        let mut cell_row = Vec::with_capacity(cols - 1);
+/// Trace and organize the polygon rings of a single band
+///
+/// Returns `None` when the band is empty. This is the shared core behind every
+/// isoband output format.
+pub(crate) fn trace_band<G: GridSource + ?Sized>(
+    grid: &G,
+    clipping: &Clipping,
+    lower: f64,
+    upper: f64,
+) -> Result<Option<BandPolygons>> {
+    Ok(trace_band_reported(grid, clipping, lower, upper, &ContourControl::default())?.0)
+}
 
-        for col in 0..cols - 1 {
-            let tl = grid.get(row, col).unwrap();
-            let tr = grid.get(row, col + 1).unwrap();
-            let br = grid.get(row + 1, col + 1).unwrap();
-            let bl = grid.get(row + 1, col).unwrap();
+/// Trace a single band, also reporting its tracing statistics
+///
+/// Cancellation is checked before each row of cells. In strict mode the first
+/// incomplete ring trace is an error. The report's `elapsed` is left for the
+/// caller to fill in.
+pub(crate) fn trace_band_reported<G: GridSource + ?Sized>(
+    grid: &G,
+    clipping: &Clipping,
+    lower: f64,
+    upper: f64,
+    control: &ContourControl,
+) -> Result<(Option<BandPolygons>, BandReport)> {
+    let mut report = BandReport {
+        lower,
+        upper,
+        ..BandReport::default()
+    };
+    let Some(window) = &clipping.window else {
+        return Ok((None, report));
+    };
+
+    // Cells outside the clip mask are skipped; like the window edges, they
+    // are treated as grid boundaries so rings close along them
+    let mask = grid.config().clip_mask.as_deref();
+    let outside = |i: usize, j: usize| clipping.class(i, j) == CellMask::Outside;
+    let (height, width) = (window.rows.len(), window.cols.len());
+
+    // Create a 2D array of the window's cells with their shapes
+    let mut cells: Vec<Vec<Option<CellWithEdges>>> = Vec::with_capacity(height);
+
+    for (i, row) in window.rows.clone().enumerate() {
+        control.check()?;
+        let mut cell_row = Vec::with_capacity(width);
+
+        for (j, col) in window.cols.clone().enumerate() {
+            if outside(i, j) {
+                cell_row.push(None);
+                continue;
+            }
+            let tl = &grid.point(row, col);
+            let tr = &grid.point(row, col + 1);
+            let br = &grid.point(row + 1, col + 1);
+            let bl = &grid.point(row + 1, col);
 
             // Calculate cell configuration
             let config = calculate_cell_config(tl, tr, br, bl, lower, upper);
 
             // Create cell shape
-            let is_top = row == 0;
-            let is_right = col + 1 == cols - 1;
-            let is_bottom = row + 1 == rows - 1;
-            let is_left = col == 0;
-
-            // Debug ALL cells around (282,1797) - even empty ones
-            if row >= 275 && row <= 290 && col >= 1790 && col <= 1797 {
-                eprintln!("🎯 DEBUG CELL ({},{}) config={} tl={:.2} tr={:.2} br={:.2} bl={:.2} boundaries=[T:{},R:{},B:{},L:{}]",
-                    row, col, config, tl.value, tr.value, br.value, bl.value, is_top, is_right, is_bottom, is_left);
-            }
+            let is_top = i == 0 || outside(i - 1, j);
+            let is_right = j + 1 == width || outside(i, j + 1);
+            let is_bottom = i + 1 == height || outside(i + 1, j);
+            let is_left = j == 0 || outside(i, j - 1);
 
-            let shape_opt = CellShape::from_config(
+            let (shape_class, shape_opt) = CellShape::from_config(
                 config,
                 tl,
                 tr,
@@ -739,122 +1105,309 @@ pub fn generate_isobands_phase2(grid: &GeoGrid, lower: f64, upper: f64) -> Resul
                 is_bottom,
                 is_left,
             );
+            report.cells.record(shape_class);
 
-            if let Some(shape) = shape_opt {
-                // Debug TOP boundary cells only, and only first 20 columns
-                if is_top && col < 20 {
-                    eprintln!("🔍 TOP BOUNDARY ({},{}) config={} tl={:.2} tr={:.2} br={:.2} bl={:.2} edges={}",
-                        row, col, config, tl.value, tr.value, br.value, bl.value, shape.edges.len());
-                    for (start, edge) in &shape.edges {
-                        eprintln!("   Edge: ({:?},{:?}) -> ({:?},{:?}) move={:?}",
-                            start.x, start.y, edge.end.x, edge.end.y, edge.move_dir);
-                    }
-                }
-
-                // Debug cells with edges in the problem area
-                if row >= 275 && row <= 290 && col >= 1790 && col <= 1797 {
-                    eprintln!("   ✓ ({},{}) {} edges", row, col, shape.edges.len());
-                    for (start, edge) in &shape.edges {
-                        eprintln!("      Edge: ({:?},{:?}) -> ({:?},{:?}) move={:?}",
-                            start.x, start.y, edge.end.x, edge.end.y, edge.move_dir);
-                    }
-                }
-
-                let corners = (tl.value as f64, tr.value as f64, br.value as f64, bl.value as f64);
-                let boundaries = (is_top, is_right, is_bottom, is_left);
-                cell_row.push(Some(CellWithEdges::new_with_config(shape, config, corners, boundaries)));
-            } else {
-                // Debug empty cells in the problem area
-                if row >= 275 && row <= 290 && col >= 1790 && col <= 1797 {
-                    eprintln!("   ✗ 0 edges (config {} skipped or produced no shape)", config);
-                }
-                cell_row.push(None);
-            }
+            cell_row.push(shape_opt.map(|shape| {
+                let mut cell = CellWithEdges::new_with_config(shape, config);
+                cell.on_mask_edge = clipping.class(i, j) == CellMask::Boundary;
+                cell
+            }));
         }
 
         cells.push(cell_row);
     }
 
     // Trace all polygon rings
-    let rings = trace_all_rings(&mut cells);
+    let mut traced = trace_all_rings(&mut cells, grid.config().max_ring_steps);
+    for failure in &mut traced.failures {
+        failure.start_row += window.rows.start;
+        failure.row += window.rows.start;
+        failure.start_col += window.cols.start;
+        failure.col += window.cols.start;
+    }
+    if grid.config().strict {
+        if let Some(failure) = traced.failures.iter().find(|f| f.kind.is_incomplete()) {
+            return Err(Error::trace_failed(lower, upper, failure));
+        }
+    }
+    report.trace_attempts = traced.attempts;
+    report.failures = traced.failures;
+    report.rings = traced.rings.len();
+    report.vertices = traced.rings.iter().map(Vec::len).sum();
 
     // CRITICAL FIX: Match Java behavior - return None for empty results
     // Java filters out empty features (MarchingSquares.java:245)
-    if rings.is_empty() {
-        return Ok(None);
+    if traced.rings.is_empty() {
+        return Ok((None, report));
     }
 
     // Organize polygons with hole detection
-    let organized = organize_polygons(rings);
+    let mut organized = match mask {
+        Some(mask) => mask_band(traced.rings, &traced.on_mask_edge, mask),
+        None => organize_polygons(traced.rings),
+    };
+    if let (Some(rect), Some(rect_mask)) = (&clipping.rect, &clipping.rect_mask) {
+        organized = clip_band(organized, rect, rect_mask);
+    }
+    if organized.is_empty() {
+        return Ok((None, report));
+    }
+    report.polygons = organized.len();
+    report.holes = organized.iter().map(|(_, holes)| holes.len()).sum();
 
-    // Convert to GeoJSON MultiPolygon
-    let multi_polygon: Vec<Vec<Vec<Vec<f64>>>> = organized
-        .into_iter()
-        .enumerate()
-        .map(|(poly_idx, (exterior, holes))| {
-            let mut polygon_rings = Vec::new();
-
-            // Debug: Check if ring is actually closed before we do anything
-            if let (Some(first), Some(last)) = (exterior.first(), exterior.last()) {
-                let is_closed = first.x == last.x && first.y == last.y;
-                if !is_closed {
-                    if let (Some(fx), Some(fy), Some(lx), Some(ly)) = (first.x, first.y, last.x, last.y) {
-                        eprintln!("🚨 POLYGON {} NOT CLOSED from trace_ring! first=({:.12},{:.12}) last=({:.12},{:.12}), dist={:.6}",
-                            poly_idx, fx, fy, lx, ly,
-                            ((lx - fx).powi(2) + (ly - fy).powi(2)).sqrt());
-                    }
-                }
-            }
+    Ok((Some(organized), report))
+}
 
-            // CRITICAL FIX: Close the ring BEFORE rounding to ensure first == last after rounding
-            // trace_ring returns rings where first and last are bitwise identical.
-            // If we round first, they may round to different values, creating diagonal artifacts.
-            // Solution: Duplicate the first point BEFORE rounding, then round all points together.
-            let mut exterior_for_rounding = exterior.clone();
-            if let Some(first) = exterior_for_rounding.first().cloned() {
-                exterior_for_rounding.push(first); // Now guaranteed: first == last (bitwise)
+/// The configured clip box, the window of cells to contour and the window's
+/// cells classified against the clip mask
+///
+/// Finding the window and classifying cells read the whole grid, so this is
+/// built once per run and shared by every band and level.
+pub(crate) struct Clipping {
+    rect: Option<Rect>,
+    /// The clip box as a mask, for clipping band polygons
+    rect_mask: Option<ClipMask>,
+    window: Option<CellWindow>,
+    classes: Option<Vec<Vec<CellMask>>>,
+}
+
+impl Clipping {
+    /// The window covers the clip box and the clip mask's bounds, or the whole
+    /// grid when neither is set. It is `None` when they miss the grid or each
+    /// other.
+    pub(crate) fn new<G: GridSource + ?Sized>(grid: &G) -> Result<Self> {
+        let config = grid.config();
+        let rect = config.clip_bbox.map(Rect::from_bbox).transpose()?;
+        let rect_mask = rect.as_ref().map(ClipMask::from_rect);
+        let bounds = match (&rect, &config.clip_mask) {
+            (None, None) => {
+                return Ok(Self {
+                    rect,
+                    rect_mask,
+                    window: Some(CellWindow::full(grid)),
+                    classes: None,
+                })
             }
+            (Some(rect), None) => Some(*rect),
+            (None, Some(mask)) => Some(mask.bounds()),
+            (Some(rect), Some(mask)) => rect.intersection(&mask.bounds()),
+        };
+        let window = bounds.and_then(|bounds| CellWindow::covering(grid, &bounds));
+        let classes = config
+            .clip_mask
+            .as_deref()
+            .zip(window.as_ref())
+            .map(|(mask, window)| classify_cells(grid, window, mask));
+        Ok(Self {
+            rect,
+            rect_mask,
+            window,
+            classes,
+        })
+    }
+
+    /// Class of the window cell at `i`, `j` (window-relative); every cell is
+    /// inside when there is no clip mask
+    fn class(&self, i: usize, j: usize) -> CellMask {
+        self.classes
+            .as_ref()
+            .map_or(CellMask::Inside, |classes| classes[i][j])
+    }
+}
 
-            // Now round all coordinates (including the duplicated closing point)
-            let exterior_coords: Vec<Vec<f64>> = exterior_for_rounding
+/// Classify each cell of the window against the clip mask
+fn classify_cells<G: GridSource + ?Sized>(
+    grid: &G,
+    window: &CellWindow,
+    mask: &ClipMask,
+) -> Vec<Vec<CellMask>> {
+    window
+        .rows
+        .clone()
+        .map(|row| {
+            window
+                .cols
+                .clone()
+                .map(|col| mask.classify(&cell_corners(grid, row, col)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Lon/lat of a cell's corners (tl, tr, br, bl)
+fn cell_corners<G: GridSource + ?Sized>(grid: &G, row: usize, col: usize) -> [(f64, f64); 4] {
+    [
+        (row, col),
+        (row, col + 1),
+        (row + 1, col + 1),
+        (row + 1, col),
+    ]
+    .map(|(r, c)| {
+        let p = grid.point(r, c);
+        (p.lon, p.lat)
+    })
+}
+
+/// Clip traced band rings to a polygon mask
+///
+/// Rings are nested first. Polygons with no ring through a cell on the mask
+/// boundary lie inside the mask and are kept as traced, unless they surround
+/// a mask hole; the rest are intersected with the mask.
+fn mask_band(rings: Vec<Vec<Point>>, on_mask_edge: &[bool], mask: &ClipMask) -> BandPolygons {
+    let nested = nest_rings(&rings);
+    let mut rings: Vec<Option<Vec<Point>>> = rings.into_iter().map(Some).collect();
+    let mut polygons = Vec::with_capacity(nested.len());
+
+    for (exterior, holes) in nested {
+        let exterior_ring = rings[exterior].take().unwrap_or_default();
+        let hole_rings: Vec<Vec<Point>> = holes
+            .iter()
+            .map(|&h| rings[h].take().unwrap_or_default())
+            .collect();
+
+        let crosses = on_mask_edge[exterior] || holes.iter().any(|&h| on_mask_edge[h]);
+        let exterior_xy = xy_ring(&exterior_ring);
+        if !crosses && !mask.has_hole_inside(&exterior_xy) {
+            polygons.push((exterior_ring, hole_rings));
+            continue;
+        }
+
+        let holes_xy: Vec<Vec<(f64, f64)>> = hole_rings.iter().map(|ring| xy_ring(ring)).collect();
+        polygons.extend(
+            mask.clip_polygon(&exterior_xy, &holes_xy)
                 .iter()
-                .filter_map(|p| {
-                    match (p.x, p.y) {
-                        (Some(x), Some(y)) => Some(vec![
-                            crate::types::round_coordinate(x),
-                            crate::types::round_coordinate(y)
-                        ]),
-                        _ => None,
-                    }
-                })
-                .collect();
-            polygon_rings.push(exterior_coords);
-
-            // Add interior rings (holes) - also must be closed BEFORE rounding
-            for hole in holes {
-                // Close the ring BEFORE rounding
-                let mut hole_for_rounding = hole.clone();
-                if let Some(first) = hole_for_rounding.first().cloned() {
-                    hole_for_rounding.push(first);
-                }
+                .map(|(exterior, holes)| polygon_points(exterior, holes)),
+        );
+    }
 
-                // Now round all coordinates
-                let hole_coords: Vec<Vec<f64>> = hole_for_rounding
-                    .iter()
-                    .filter_map(|p| {
-                        match (p.x, p.y) {
-                            (Some(x), Some(y)) => Some(vec![
-                                crate::types::round_coordinate(x),
-                                crate::types::round_coordinate(y)
-                            ]),
-                            _ => None,
-                        }
-                    })
-                    .collect();
-                polygon_rings.push(hole_coords);
-            }
+    polygons
+}
+
+/// Points of a clipped polygon's open rings, closed like traced rings
+fn polygon_points(
+    exterior: &[(f64, f64)],
+    holes: &[Vec<(f64, f64)>],
+) -> (Vec<Point>, Vec<Vec<Point>>) {
+    (
+        closed_points(exterior),
+        holes.iter().map(|hole| closed_points(hole)).collect(),
+    )
+}
+
+/// Coordinates of a traced ring or line
+fn xy_ring(ring: &[Point]) -> Vec<(f64, f64)> {
+    ring.iter().filter_map(|p| Some((p.x?, p.y?))).collect()
+}
+
+/// Points of an open clipped ring, repeating the first point at the end like
+/// traced rings
+fn closed_points(ring: &[(f64, f64)]) -> Vec<Point> {
+    ring.iter()
+        .chain(ring.first())
+        .map(|&(x, y)| Point::actual(x, y))
+        .collect()
+}
+
+/// Clip traced band polygons to a rectangle
+///
+/// Polygons inside the rectangle are kept as traced and those outside it are
+/// dropped. The rest are intersected with `rect_mask`, the rectangle as a
+/// mask, so a polygon the box cuts in several places becomes several polygons
+/// closed along the box edges, and holes crossing the edges open up into them.
+fn clip_band(polygons: BandPolygons, rect: &Rect, rect_mask: &ClipMask) -> BandPolygons {
+    let mut clipped = Vec::with_capacity(polygons.len());
+
+    for (exterior, holes) in polygons {
+        let exterior_xy = xy_ring(&exterior);
+        if rect.contains_all(&exterior_xy) {
+            clipped.push((exterior, holes));
+            continue;
+        }
+        if !rect.intersects(&exterior_xy) {
+            continue;
+        }
+
+        let holes_xy: Vec<Vec<(f64, f64)>> = holes.iter().map(|ring| xy_ring(ring)).collect();
+        clipped.extend(
+            rect_mask
+                .clip_polygon(&exterior_xy, &holes_xy)
+                .iter()
+                .map(|(exterior, holes)| polygon_points(exterior, holes)),
+        );
+    }
 
-            polygon_rings
+    clipped
+}
+
+/// Clip an isoline segment to a rectangle, returning the pieces inside
+fn clip_line(segment: &[Point], rect: &Rect) -> Vec<Vec<Point>> {
+    line_points(clip_polyline(&xy_ring(segment), rect))
+}
+
+/// Clip an isoline segment to a polygon mask, returning the pieces inside
+fn mask_line(segment: &[Point], mask: &ClipMask) -> Vec<Vec<Point>> {
+    line_points(mask.clip_line(&xy_ring(segment)))
+}
+
+fn line_points(pieces: Vec<Vec<(f64, f64)>>) -> Vec<Vec<Point>> {
+    pieces
+        .into_iter()
+        .map(|piece| {
+            piece
+                .into_iter()
+                .map(|(x, y)| Point::actual(x, y))
+                .collect()
+        })
+        .collect()
+}
+
+/// Rounded output coordinates of a traced ring, closed with its first point
+///
+/// CRITICAL FIX: Close the ring BEFORE rounding to ensure first == last after rounding.
+/// trace_ring returns rings where first and last are bitwise identical.
+/// If we round first, they may round to different values, creating diagonal artifacts.
+/// Solution: Duplicate the first point BEFORE rounding, then round all points together.
+/// This holds at any precision, including full `f64` output.
+pub(crate) fn closed_ring(
+    ring: &[Point],
+    precision: CoordinatePrecision,
+) -> impl Iterator<Item = (f64, f64)> + '_ {
+    closed_ring_xy(ring).map(move |(x, y)| (precision.round(x), precision.round(y)))
+}
+
+/// Unrounded coordinates of a traced ring, closed with its first point
+fn closed_ring_xy(ring: &[Point]) -> impl Iterator<Item = (f64, f64)> + '_ {
+    ring.iter()
+        .chain(ring.first())
+        .filter_map(|p| Some((p.x?, p.y?)))
+}
+
+/// Rounded output coordinates of an actual point (placeholders are skipped)
+pub(crate) fn rounded_xy(p: &Point, precision: CoordinatePrecision) -> Option<(f64, f64)> {
+    match (p.x, p.y) {
+        (Some(x), Some(y)) => Some((precision.round(x), precision.round(y))),
+        _ => None,
+    }
+}
+
+/// Convert traced band polygons to a GeoJSON MultiPolygon feature
+fn band_feature(
+    polygons: &BandPolygons,
+    lower: f64,
+    upper: f64,
+    precision: CoordinatePrecision,
+) -> Feature {
+    let multi_polygon: Vec<Vec<Vec<Vec<f64>>>> = polygons
+        .iter()
+        .map(|(exterior, holes)| {
+            std::iter::once(exterior)
+                .chain(holes)
+                .map(|ring| {
+                    closed_ring(ring, precision)
+                        .map(|(x, y)| vec![x, y])
+                        .collect()
+                })
+                .collect()
         })
         .collect();
 
@@ -873,5 +1426,186 @@ pub fn generate_isobands_phase2(grid: &GeoGrid, lower: f64, upper: f64) -> Resul
         props.insert("upper_level".to_string(), serde_json::json!(upper));
     }
 
-    Ok(Some(feature))
+    feature
+}
+
+/// Split traced band polygons into one feature each, in a stable order
+fn polygon_features(
+    mut polygons: BandPolygons,
+    band: usize,
+    lower: f64,
+    upper: f64,
+    precision: CoordinatePrecision,
+) -> Vec<Feature> {
+    for (exterior, holes) in polygons.iter_mut() {
+        std::iter::once(exterior)
+            .chain(holes.iter_mut())
+            .for_each(anchor_ring);
+        holes.sort_by(|a, b| ring_order(a, b));
+    }
+    polygons.sort_by(|a, b| ring_order(&a.0, &b.0));
+
+    polygons
+        .iter()
+        .enumerate()
+        .map(|(index, (exterior, holes))| {
+            let rings: Vec<Vec<Vec<f64>>> = std::iter::once(exterior)
+                .chain(holes.iter())
+                .map(|ring| {
+                    closed_ring(ring, precision)
+                        .map(|(x, y)| vec![x, y])
+                        .collect()
+                })
+                .collect();
+
+            let area =
+                ring_area_km2(exterior) - holes.iter().map(|h| ring_area_km2(h)).sum::<f64>();
+            let (cx, cy) = polygon_centroid(exterior, holes);
+
+            let mut props = serde_json::Map::new();
+            props.insert("lower_level".to_string(), serde_json::json!(lower));
+            props.insert("upper_level".to_string(), serde_json::json!(upper));
+            props.insert("band_index".to_string(), serde_json::json!(band));
+            props.insert("polygon_index".to_string(), serde_json::json!(index));
+            props.insert("area_km2".to_string(), serde_json::json!(area.max(0.0)));
+            props.insert(
+                "centroid".to_string(),
+                serde_json::json!([precision.round(cx), precision.round(cy)]),
+            );
+
+            Feature {
+                bbox: None,
+                geometry: Some(Geometry::new(GeoValue::Polygon(rings))),
+                id: Some(Id::Number(((band as u64) << 32 | index as u64).into())),
+                properties: Some(props),
+                foreign_members: None,
+            }
+        })
+        .collect()
+}
+
+/// Start a ring at its northernmost (then westernmost) vertex
+///
+/// Tracing may start a ring anywhere and may repeat its first point at the
+/// end; anchoring makes the output coordinates independent of both.
+fn anchor_ring(ring: &mut Vec<Point>) {
+    if ring.len() > 1 && ring.first().map(|p| (p.x, p.y)) == ring.last().map(|p| (p.x, p.y)) {
+        ring.pop();
+    }
+    let anchor = ring
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| Some((i, p.x?, p.y?)))
+        .reduce(|best, c| {
+            if c.2 > best.2 || (c.2 == best.2 && c.1 < best.1) {
+                c
+            } else {
+                best
+            }
+        });
+    if let Some((i, _, _)) = anchor {
+        ring.rotate_left(i);
+    }
+}
+
+/// Order rings by their northernmost (then westernmost) vertex
+///
+/// Ties fall back to the other extremes and the vertex count, so the order
+/// does not depend on where tracing started each ring.
+fn ring_order(a: &[Point], b: &[Point]) -> std::cmp::Ordering {
+    let extremes = |ring: &[Point]| {
+        let mut top = (f64::NEG_INFINITY, f64::INFINITY);
+        let (mut bottom, mut left) = (f64::INFINITY, f64::INFINITY);
+        for (x, y) in closed_ring_xy(ring) {
+            if y > top.0 || (y == top.0 && x < top.1) {
+                top = (y, x);
+            }
+            bottom = bottom.min(y);
+            left = left.min(x);
+        }
+        [-top.0, top.1, bottom, left]
+    };
+    let (ea, eb) = (extremes(a), extremes(b));
+    ea.iter()
+        .zip(&eb)
+        .map(|(x, y)| x.total_cmp(y))
+        .find(|o| o.is_ne())
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+/// Geodesic area of a ring on the mean-radius sphere, in km²
+fn ring_area_km2(ring: &[Point]) -> f64 {
+    let coords: Vec<(f64, f64)> = closed_ring_xy(ring).collect();
+    let sum: f64 = coords
+        .windows(2)
+        .map(|w| {
+            let ((x1, y1), (x2, y2)) = (w[0], w[1]);
+            (x2 - x1).to_radians() * (2.0 + y1.to_radians().sin() + y2.to_radians().sin())
+        })
+        .sum();
+    (sum * EARTH_RADIUS_KM * EARTH_RADIUS_KM / 2.0).abs()
+}
+
+/// Planar area-weighted centroid of a polygon with holes, in lon/lat
+fn polygon_centroid(exterior: &[Point], holes: &[Vec<Point>]) -> (f64, f64) {
+    let Some((ox, oy)) = closed_ring_xy(exterior).next() else {
+        return (f64::NAN, f64::NAN);
+    };
+
+    // Accumulate relative to the first vertex to limit cancellation
+    let moments = |ring: &[Point]| {
+        let coords: Vec<(f64, f64)> = closed_ring_xy(ring)
+            .map(|(x, y)| (x - ox, y - oy))
+            .collect();
+        let (mut area, mut mx, mut my) = (0.0, 0.0, 0.0);
+        for w in coords.windows(2) {
+            let ((x1, y1), (x2, y2)) = (w[0], w[1]);
+            let cross = x1 * y2 - x2 * y1;
+            area += cross;
+            mx += (x1 + x2) * cross;
+            my += (y1 + y2) * cross;
+        }
+        // Orientation-independent: exterior adds, holes subtract
+        let sign = if area < 0.0 { -1.0 } else { 1.0 };
+        (area * sign / 2.0, mx * sign / 6.0, my * sign / 6.0)
+    };
+
+    let (mut area, mut mx, mut my) = moments(exterior);
+    for hole in holes {
+        let (a, x, y) = moments(hole);
+        area -= a;
+        mx -= x;
+        my -= y;
+    }
+
+    if area.abs() > f64::EPSILON * f64::EPSILON {
+        (ox + mx / area, oy + my / area)
+    } else {
+        // Degenerate polygon: fall back to the vertex mean
+        let coords: Vec<(f64, f64)> = exterior.iter().filter_map(|p| Some((p.x?, p.y?))).collect();
+        let n = coords.len() as f64;
+        (
+            coords.iter().map(|c| c.0).sum::<f64>() / n,
+            coords.iter().map(|c| c.1).sum::<f64>() / n,
+        )
+    }
+}
+
+/// Convert traced band polygons to a `geo-types` MultiPolygon
+fn band_geometry(polygons: &BandPolygons, precision: CoordinatePrecision) -> MultiPolygon<f64> {
+    let to_line_string = |ring: &Vec<Point>| -> LineString<f64> {
+        closed_ring(ring, precision)
+            .map(|(x, y)| Coord { x, y })
+            .collect()
+    };
+
+    polygons
+        .iter()
+        .map(|(exterior, holes)| {
+            Polygon::new(
+                to_line_string(exterior),
+                holes.iter().map(to_line_string).collect(),
+            )
+        })
+        .collect()
 }
@@ -0,0 +1,792 @@
+//! Clipping contours to a polygon mask
+//!
+//! A [`ClipMask`] set as [`MarchingSquaresConfig::clip_mask`](crate::MarchingSquaresConfig::clip_mask)
+//! restricts isobands and isolines to the inside of a `geo_types::MultiPolygon`,
+//! such as a county, coastline or country outline.
+//!
+//! Grid cells are first classified against the mask. Cells entirely outside it
+//! are skipped before tracing, and rings close along the skipped cells instead.
+//! Polygons traced only through cells entirely inside the mask are output
+//! without clipping. The remaining polygons are intersected with the mask,
+//! keeping the holes of both the band and the mask.
+//!
+//! Mask polygons must not overlap each other. Ring orientation does not matter.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::mask::ClipMask;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource, MarchingSquaresConfig};
+//! use geo_types::{polygon, MultiPolygon};
+//!
+//! let county: MultiPolygon<f64> = polygon![
+//!     (x: -99.8, y: 40.2), (x: -99.2, y: 40.2), (x: -99.5, y: 40.8), (x: -99.8, y: 40.2),
+//! ]
+//! .into();
+//!
+//! let mut grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//! *grid.config_mut() = MarchingSquaresConfig::builder()
+//!     .with_clip_mask(ClipMask::new(&county)?)
+//!     .build();
+//!
+//! let bands = grid.isobands_geo(&[12.0, 18.0, 22.0])?;
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::clip::{clip_segment, ring_area, Rect};
+use crate::error::{Error, Result};
+use geo_types::{LineString, MultiPolygon};
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use std::fmt;
+
+/// Distance (in degrees) within which a point counts as lying on an edge
+const ON_EDGE_TOLERANCE: f64 = 1e-9;
+
+/// Edge parameters this close to 0 or 1 are treated as the edge's endpoints
+const PARAM_TOLERANCE: f64 = 1e-12;
+
+type Xy = (f64, f64);
+type Segment = (Xy, Xy);
+
+/// A polygon mask that contour output is clipped to
+///
+/// Built once from a `MultiPolygon` and shared between runs: the mask's edges
+/// are indexed so cells and polygons far from its boundary are handled
+/// without clipping.
+#[derive(Clone)]
+pub struct ClipMask {
+    /// Every ring edge, with the mask interior on its left
+    edges: Vec<Segment>,
+    /// Bounding box and first vertex of each hole
+    holes: Vec<(Rect, Xy)>,
+    index: EdgeIndex,
+}
+
+impl ClipMask {
+    /// Create a mask from a `MultiPolygon` in lon/lat
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a coordinate is not finite or no polygon has an
+    /// exterior ring with area
+    pub fn new(mask: &MultiPolygon<f64>) -> Result<Self> {
+        let mut edges = Vec::new();
+        let mut holes = Vec::new();
+        for polygon in mask {
+            let Some(exterior) = open_ring(polygon.exterior())? else {
+                continue;
+            };
+            edges.extend(ring_edges(&oriented(exterior, true)));
+            for interior in polygon.interiors() {
+                if let Some(hole) = open_ring(interior)? {
+                    holes.push((Rect::bounding(&hole), hole[0]));
+                    edges.extend(ring_edges(&oriented(hole, false)));
+                }
+            }
+        }
+        if edges.is_empty() {
+            return Err(Error::geometry_error("Clip mask has no polygon with area"));
+        }
+
+        let index = EdgeIndex::new(&edges);
+        Ok(Self {
+            edges,
+            holes,
+            index,
+        })
+    }
+
+    /// Create a mask covering a rectangle, to clip polygons exactly to it
+    pub(crate) fn from_rect(rect: &Rect) -> Self {
+        let corners = [
+            (rect.min_x, rect.min_y),
+            (rect.max_x, rect.min_y),
+            (rect.max_x, rect.max_y),
+            (rect.min_x, rect.max_y),
+        ];
+        let edges: Vec<Segment> = ring_edges(&corners).collect();
+        let index = EdgeIndex::new(&edges);
+        Self {
+            edges,
+            holes: Vec::new(),
+            index,
+        }
+    }
+
+    /// Bounding box of the mask
+    pub(crate) fn bounds(&self) -> Rect {
+        self.index.bounds
+    }
+
+    /// Classify a grid cell by its corner coordinates
+    ///
+    /// Cells whose bounding box a mask edge touches are [`CellMask::Boundary`].
+    pub(crate) fn classify(&self, corners: &[Xy]) -> CellMask {
+        let cell = Rect::bounding(corners);
+        if !cell.overlaps(&self.index.bounds) {
+            return CellMask::Outside;
+        }
+        let near_edge = self
+            .index
+            .candidates(&self.edges, &cell)
+            .any(|e| clip_segment(self.edges[e].0, self.edges[e].1, &cell).is_some());
+        if near_edge {
+            return CellMask::Boundary;
+        }
+        let center = (
+            (cell.min_x + cell.max_x) / 2.0,
+            (cell.min_y + cell.max_y) / 2.0,
+        );
+        match self.locate(center) {
+            Location::Inside => CellMask::Inside,
+            Location::Outside => CellMask::Outside,
+            Location::OnEdge(_) => CellMask::Boundary,
+        }
+    }
+
+    /// Whether a mask hole lies inside `ring`
+    ///
+    /// A polygon traced only through cells inside the mask can still surround
+    /// a hole, and then needs clipping.
+    pub(crate) fn has_hole_inside(&self, ring: &[Xy]) -> bool {
+        let bounds = Rect::bounding(ring);
+        let edges: Vec<Segment> = ring_edges(ring).collect();
+        self.holes.iter().any(|(rect, vertex)| {
+            bounds.overlaps(rect) && locate_in(*vertex, &edges) == Location::Inside
+        })
+    }
+
+    /// Where a point lies relative to the mask
+    fn locate(&self, p: Xy) -> Location {
+        self.index.locate(&self.edges, p)
+    }
+
+    /// Intersect a polygon (an exterior ring and its holes) with the mask
+    ///
+    /// Rings may be open or closed, in any orientation. Returns the resulting
+    /// polygons with open rings, exteriors counter-clockwise and holes
+    /// clockwise.
+    pub(crate) fn clip_polygon(
+        &self,
+        exterior: &[Xy],
+        holes: &[Vec<Xy>],
+    ) -> Vec<(Vec<Xy>, Vec<Vec<Xy>>)> {
+        let subject: Vec<Vec<Xy>> = std::iter::once(exterior)
+            .map(|ring| (ring, true))
+            .chain(holes.iter().map(|hole| (hole.as_slice(), false)))
+            .filter_map(|(ring, is_exterior)| Some(oriented(dedup_ring(ring)?, is_exterior)))
+            .collect();
+        let Some(outer) = subject.first() else {
+            return Vec::new();
+        };
+        let subject_edges: Vec<Segment> =
+            subject.iter().flat_map(|ring| ring_edges(ring)).collect();
+        let subject_index = EdgeIndex::new(&subject_edges);
+
+        // Split both sets of edges where they meet, sharing each split point
+        let mut subject_splits: Vec<Vec<(f64, Xy)>> = vec![Vec::new(); subject_edges.len()];
+        let mut mask_splits: HashMap<usize, Vec<(f64, Xy)>> = HashMap::new();
+        for (i, &s) in subject_edges.iter().enumerate() {
+            let s_rect = segment_rect(s);
+            for e in self.index.candidates(&self.edges, &s_rect) {
+                let m = self.edges[e];
+                if s_rect.overlaps(&segment_rect(m)) {
+                    split_pair(
+                        s,
+                        m,
+                        &mut subject_splits[i],
+                        mask_splits.entry(e).or_default(),
+                    );
+                }
+            }
+        }
+
+        let mut pieces: Vec<Segment> = Vec::new();
+        for (&edge, splits) in subject_edges.iter().zip(subject_splits) {
+            for piece in split_edge(edge, splits) {
+                let keep = match self.locate(midpoint(piece)) {
+                    Location::Inside => true,
+                    Location::Outside => false,
+                    // Shared boundary: keep one copy when both interiors agree
+                    Location::OnEdge(direction) => dot(direction, delta(piece)) > 0.0,
+                };
+                if keep {
+                    pieces.push(piece);
+                }
+            }
+        }
+        let subject_bounds = Rect::bounding(outer);
+        for e in self.index.candidates(&self.edges, &subject_bounds) {
+            let splits = mask_splits.remove(&e).unwrap_or_default();
+            for piece in split_edge(self.edges[e], splits) {
+                if subject_index.locate(&subject_edges, midpoint(piece)) == Location::Inside {
+                    pieces.push(piece);
+                }
+            }
+        }
+
+        nest_clipped(link_rings(pieces))
+    }
+
+    /// Clip a polyline to the mask, returning the pieces inside it in order
+    pub(crate) fn clip_line(&self, line: &[Xy]) -> Vec<Vec<Xy>> {
+        let mut lines: Vec<Vec<Xy>> = Vec::new();
+        let mut current: Vec<Xy> = Vec::new();
+
+        for segment in line.windows(2) {
+            let segment = (segment[0], segment[1]);
+            let s_rect = segment_rect(segment);
+            let mut splits = Vec::new();
+            for e in self.index.candidates(&self.edges, &s_rect) {
+                let m = self.edges[e];
+                if s_rect.overlaps(&segment_rect(m)) {
+                    split_pair(segment, m, &mut splits, &mut Vec::new());
+                }
+            }
+
+            for piece in split_edge(segment, splits) {
+                if self.locate(midpoint(piece)) == Location::Outside {
+                    if current.len() >= 2 {
+                        lines.push(std::mem::take(&mut current));
+                    }
+                    current.clear();
+                    continue;
+                }
+                if current.last() != Some(&piece.0) {
+                    if current.len() >= 2 {
+                        lines.push(std::mem::take(&mut current));
+                    }
+                    current = vec![piece.0];
+                }
+                current.push(piece.1);
+            }
+        }
+
+        if current.len() >= 2 {
+            lines.push(current);
+        }
+        lines
+    }
+}
+
+impl fmt::Debug for ClipMask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClipMask")
+            .field("edges", &self.edges.len())
+            .field("holes", &self.holes.len())
+            .field("bounds", &self.index.bounds)
+            .finish()
+    }
+}
+
+/// How a grid cell relates to a [`ClipMask`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CellMask {
+    /// Entirely inside the mask
+    Inside,
+    /// Entirely outside the mask
+    Outside,
+    /// Possibly crossed by the mask boundary
+    Boundary,
+}
+
+/// Where a point lies relative to a set of rings
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Location {
+    Inside,
+    Outside,
+    /// On an edge, with that edge's direction
+    OnEdge(Xy),
+}
+
+/// Uniform grid of bins over the mask, each listing the edges that overlap it
+#[derive(Debug, Clone)]
+struct EdgeIndex {
+    bounds: Rect,
+    nx: usize,
+    ny: usize,
+    bins: Vec<Vec<usize>>,
+    /// For bins without edges, whether the whole bin is inside the rings
+    inside: Vec<bool>,
+}
+
+impl EdgeIndex {
+    fn new(edges: &[Segment]) -> Self {
+        let points: Vec<Xy> = edges.iter().map(|&(a, _)| a).collect();
+        let bounds = Rect::bounding(&points);
+        // Roughly one edge per bin along each axis
+        let n = ((edges.len() as f64).sqrt().ceil() as usize).clamp(1, 512);
+        let mut index = Self {
+            bounds,
+            nx: n,
+            ny: n,
+            bins: vec![Vec::new(); n * n],
+            inside: Vec::new(),
+        };
+        for (e, &edge) in edges.iter().enumerate() {
+            let rect = segment_rect(edge);
+            let (x0, y0) = index.bin((rect.min_x, rect.min_y));
+            let (x1, y1) = index.bin((rect.max_x, rect.max_y));
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    index.bins[y * index.nx + x].push(e);
+                }
+            }
+        }
+        index.inside = index.sweep_empty_bins(edges);
+        index
+    }
+
+    /// Locate the center of every bin without edges, casting one ray along
+    /// the center line of each row of bins
+    fn sweep_empty_bins(&self, edges: &[Segment]) -> Vec<bool> {
+        let (width, height) = (
+            self.bounds.max_x - self.bounds.min_x,
+            self.bounds.max_y - self.bounds.min_y,
+        );
+        let mut inside = vec![false; self.bins.len()];
+        for y in 0..self.ny {
+            let row = &self.bins[y * self.nx..(y + 1) * self.nx];
+            let center_y = self.bounds.min_y + (y as f64 + 0.5) * height / self.ny as f64;
+            let mut ids: Vec<usize> = row.iter().flatten().copied().collect();
+            ids.sort_unstable();
+            ids.dedup();
+            let mut crossings: Vec<f64> = ids
+                .into_iter()
+                .filter_map(|e| ray_crossing((self.bounds.min_x, center_y), edges[e]))
+                .collect();
+            crossings.sort_by(f64::total_cmp);
+
+            for (x, bin) in row.iter().enumerate() {
+                if bin.is_empty() {
+                    let center_x = self.bounds.min_x + (x as f64 + 0.5) * width / self.nx as f64;
+                    let right = crossings.len() - crossings.partition_point(|&c| c <= center_x);
+                    inside[y * self.nx + x] = right % 2 == 1;
+                }
+            }
+        }
+        inside
+    }
+
+    fn bin_x(&self, x: f64) -> usize {
+        bin_of(x, self.bounds.min_x, self.bounds.max_x, self.nx)
+    }
+
+    fn bin(&self, (x, y): Xy) -> (usize, usize) {
+        (
+            self.bin_x(x),
+            bin_of(y, self.bounds.min_y, self.bounds.max_y, self.ny),
+        )
+    }
+
+    /// Where a point lies relative to the rings made of the indexed `edges`
+    /// (even-odd)
+    fn locate(&self, edges: &[Segment], p: Xy) -> Location {
+        let bounds = &self.bounds;
+        if !bounds.contains(p) {
+            return Location::Outside;
+        }
+
+        let (bx, by) = self.bin(p);
+        if self.bins[by * self.nx + bx].is_empty() {
+            return if self.inside[by * self.nx + bx] {
+                Location::Inside
+            } else {
+                Location::Outside
+            };
+        }
+        for &e in &self.bins[by * self.nx + bx] {
+            if let Some(direction) = on_segment(p, edges[e]) {
+                return Location::OnEdge(direction);
+            }
+        }
+
+        // Cast a ray towards +x through the bins of this row. An edge spanning
+        // several bins is only counted in the bin holding its crossing.
+        let mut inside = false;
+        for ix in bx..self.nx {
+            for &e in &self.bins[by * self.nx + ix] {
+                if let Some(x) = ray_crossing(p, edges[e]) {
+                    if x > p.0 && self.bin_x(x) == ix {
+                        inside = !inside;
+                    }
+                }
+            }
+        }
+        if inside {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+
+    /// Indexed `edges` in the bins overlapping `rect`, each once
+    fn candidates<'a>(
+        &'a self,
+        edges: &'a [Segment],
+        rect: &Rect,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let ((x0, y0), (x1, y1)) = if rect.overlaps(&self.bounds) {
+            (
+                self.bin((rect.min_x, rect.min_y)),
+                self.bin((rect.max_x, rect.max_y)),
+            )
+        } else {
+            ((1, 1), (0, 0))
+        };
+        (y0..=y1)
+            .flat_map(move |y| (x0..=x1).map(move |x| (x, y)))
+            .flat_map(move |(x, y)| self.bins[y * self.nx + x].iter().map(move |&e| (x, y, e)))
+            .filter(move |&(x, y, e)| {
+                // An edge fills a block of bins; report it from the first one
+                // inside the range
+                let rect = segment_rect(edges[e]);
+                let (ex, ey) = self.bin((rect.min_x, rect.min_y));
+                (x, y) == (ex.max(x0), ey.max(y0))
+            })
+            .map(|(_, _, e)| e)
+    }
+}
+
+fn bin_of(v: f64, min: f64, max: f64, n: usize) -> usize {
+    if max <= min {
+        return 0;
+    }
+    (((v - min) / (max - min) * n as f64).floor().max(0.0) as usize).min(n - 1)
+}
+
+/// Open ring from a closed `LineString`, without repeated points
+fn open_ring(line: &LineString<f64>) -> Result<Option<Vec<Xy>>> {
+    let points: Vec<Xy> = line.coords().map(|c| (c.x, c.y)).collect();
+    if points
+        .iter()
+        .any(|&(x, y)| !x.is_finite() || !y.is_finite())
+    {
+        return Err(Error::geometry_error(
+            "Clip mask has a non-finite coordinate",
+        ));
+    }
+    Ok(dedup_ring(&points))
+}
+
+/// Drop repeated and closing points; `None` if the ring has no area
+fn dedup_ring(ring: &[Xy]) -> Option<Vec<Xy>> {
+    let mut points: Vec<Xy> = Vec::with_capacity(ring.len());
+    for &p in ring {
+        if points.last() != Some(&p) {
+            points.push(p);
+        }
+    }
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    (points.len() >= 3 && ring_area(&points) != 0.0).then_some(points)
+}
+
+/// Orient a ring counter-clockwise for exteriors, clockwise for holes
+fn oriented(mut ring: Vec<Xy>, exterior: bool) -> Vec<Xy> {
+    if (ring_area(&ring) > 0.0) != exterior {
+        ring.reverse();
+    }
+    ring
+}
+
+fn ring_edges(ring: &[Xy]) -> impl Iterator<Item = Segment> + '_ {
+    (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()]))
+}
+
+fn segment_rect((a, b): Segment) -> Rect {
+    Rect::new(a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1))
+}
+
+fn delta((a, b): Segment) -> Xy {
+    (b.0 - a.0, b.1 - a.1)
+}
+
+fn midpoint((a, b): Segment) -> Xy {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+fn cross(a: Xy, b: Xy) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: Xy, b: Xy) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+/// Parameter of `p` along `segment` if it lies on it, within tolerance
+fn segment_param(p: Xy, segment: Segment) -> Option<f64> {
+    let d = delta(segment);
+    let length_sq = dot(d, d);
+    if length_sq == 0.0 {
+        return None;
+    }
+    let ap = (p.0 - segment.0 .0, p.1 - segment.0 .1);
+    let length = length_sq.sqrt();
+    if cross(d, ap).abs() / length > ON_EDGE_TOLERANCE {
+        return None;
+    }
+    let t = dot(d, ap) / length_sq;
+    let slack = ON_EDGE_TOLERANCE / length;
+    (-slack..=1.0 + slack).contains(&t).then_some(t)
+}
+
+/// Direction of `segment` if `p` lies on it
+fn on_segment(p: Xy, segment: Segment) -> Option<Xy> {
+    segment_param(p, segment).map(|_| delta(segment))
+}
+
+/// X coordinate where `segment` crosses the horizontal line through `p`
+fn ray_crossing(p: Xy, (c, d): Segment) -> Option<f64> {
+    ((c.1 > p.1) != (d.1 > p.1)).then(|| c.0 + (p.1 - c.1) * (d.0 - c.0) / (d.1 - c.1))
+}
+
+/// Where a point lies relative to rings given by their edges (even-odd)
+fn locate_in(p: Xy, edges: &[Segment]) -> Location {
+    let mut inside = false;
+    for &edge in edges {
+        if let Some(direction) = on_segment(p, edge) {
+            return Location::OnEdge(direction);
+        }
+        if ray_crossing(p, edge).is_some_and(|x| x > p.0) {
+            inside = !inside;
+        }
+    }
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+fn is_interior(t: f64) -> bool {
+    t > PARAM_TOLERANCE && t < 1.0 - PARAM_TOLERANCE
+}
+
+/// Record where segments `a` and `b` split each other
+///
+/// A vertex of one lying on the other splits the other at that exact vertex;
+/// a proper crossing splits both at one shared computed point.
+fn split_pair(
+    a: Segment,
+    b: Segment,
+    a_splits: &mut Vec<(f64, Xy)>,
+    b_splits: &mut Vec<(f64, Xy)>,
+) {
+    let mut touched = false;
+    for vertex in [b.0, b.1] {
+        if let Some(t) = segment_param(vertex, a) {
+            touched = true;
+            if is_interior(t) {
+                a_splits.push((t, vertex));
+            }
+        }
+    }
+    for vertex in [a.0, a.1] {
+        if let Some(u) = segment_param(vertex, b) {
+            touched = true;
+            if is_interior(u) {
+                b_splits.push((u, vertex));
+            }
+        }
+    }
+    if touched {
+        return;
+    }
+
+    let (r, s) = (delta(a), delta(b));
+    let denom = cross(r, s);
+    if denom == 0.0 {
+        return;
+    }
+    let ac = (b.0 .0 - a.0 .0, b.0 .1 - a.0 .1);
+    let t = cross(ac, s) / denom;
+    let u = cross(ac, r) / denom;
+    if is_interior(t) && is_interior(u) {
+        let point = (a.0 .0 + t * r.0, a.0 .1 + t * r.1);
+        a_splits.push((t, point));
+        b_splits.push((u, point));
+    }
+}
+
+/// Cut a segment at its split points, in order along it
+fn split_edge((start, end): Segment, mut splits: Vec<(f64, Xy)>) -> Vec<Segment> {
+    splits.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut points = vec![start];
+    points.extend(splits.into_iter().map(|(_, p)| p));
+    points.push(end);
+    points.dedup();
+    points.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// Join directed pieces into closed rings
+///
+/// Where several pieces leave the same point, the one turning most sharply
+/// clockwise from the incoming piece is taken, so rings that touch at a point
+/// come out as separate simple rings. Chains that cannot be closed are dropped.
+fn link_rings(pieces: Vec<Segment>) -> Vec<Vec<Xy>> {
+    let key = |(x, y): Xy| (x.to_bits(), y.to_bits());
+    let mut outgoing: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+    for (i, piece) in pieces.iter().enumerate() {
+        outgoing.entry(key(piece.0)).or_default().push(i);
+    }
+    let mut used = vec![false; pieces.len()];
+    let mut rings = Vec::new();
+
+    for first in 0..pieces.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let start = pieces[first].0;
+        let mut ring = vec![start];
+        let mut current = pieces[first];
+
+        let closed = loop {
+            if current.1 == start {
+                break true;
+            }
+            ring.push(current.1);
+            let back = delta((current.1, current.0));
+            let next = outgoing
+                .get(&key(current.1))
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|&i| !used[i])
+                .min_by(|&a, &b| {
+                    clockwise_turn(back, delta(pieces[a]))
+                        .total_cmp(&clockwise_turn(back, delta(pieces[b])))
+                });
+            match next {
+                Some(i) => {
+                    used[i] = true;
+                    current = pieces[i];
+                }
+                None => break false,
+            }
+        };
+
+        if closed && ring.len() >= 3 && ring_area(&ring) != 0.0 {
+            rings.push(ring);
+        }
+    }
+
+    rings
+}
+
+/// Give each clipped hole (clockwise) to the smallest exterior holding it
+fn nest_clipped(rings: Vec<Vec<Xy>>) -> Vec<(Vec<Xy>, Vec<Vec<Xy>>)> {
+    let (exteriors, holes): (Vec<_>, Vec<_>) =
+        rings.into_iter().partition(|ring| ring_area(ring) > 0.0);
+    let exterior_edges: Vec<Vec<Segment>> = exteriors
+        .iter()
+        .map(|ring| ring_edges(ring).collect())
+        .collect();
+    let mut polygons: Vec<(Vec<Xy>, Vec<Vec<Xy>>)> = exteriors
+        .into_iter()
+        .map(|ring| (ring, Vec::new()))
+        .collect();
+
+    for hole in holes {
+        // Rings may touch, so decide by the first edge midpoint off the exterior
+        let holds = |edges: &[Segment]| {
+            ring_edges(&hole)
+                .map(|edge| locate_in(midpoint(edge), edges))
+                .find(|location| !matches!(location, Location::OnEdge(_)))
+                == Some(Location::Inside)
+        };
+        let parent = (0..polygons.len())
+            .filter(|&i| holds(&exterior_edges[i]))
+            .min_by(|&a, &b| ring_area(&polygons[a].0).total_cmp(&ring_area(&polygons[b].0)));
+        if let Some(i) = parent {
+            polygons[i].1.push(hole);
+        }
+    }
+
+    polygons
+}
+
+/// Clockwise angle from `from` to `to`, in (0, 2π]
+fn clockwise_turn(from: Xy, to: Xy) -> f64 {
+    let angle = (from.1.atan2(from.0) - to.1.atan2(to.0)).rem_euclid(TAU);
+    if angle == 0.0 {
+        TAU
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::polygon;
+
+    fn square(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Xy> {
+        vec![(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+    }
+
+    #[test]
+    fn test_classify_cells() {
+        let mask = ClipMask::new(
+            &polygon![
+                exterior: [(x: 0.0, y: 0.0), (x: 10.0, y: 0.0), (x: 10.0, y: 10.0), (x: 0.0, y: 10.0)],
+                interiors: [[(x: 4.0, y: 4.0), (x: 6.0, y: 4.0), (x: 6.0, y: 6.0), (x: 4.0, y: 6.0)]],
+            ]
+            .into(),
+        )
+        .unwrap();
+
+        assert_eq!(mask.classify(&square(1.0, 1.0, 2.0, 2.0)), CellMask::Inside);
+        assert_eq!(
+            mask.classify(&square(4.5, 4.5, 5.5, 5.5)),
+            CellMask::Outside
+        );
+        assert_eq!(
+            mask.classify(&square(20.0, 1.0, 21.0, 2.0)),
+            CellMask::Outside
+        );
+        assert_eq!(
+            mask.classify(&square(9.5, 1.0, 10.5, 2.0)),
+            CellMask::Boundary
+        );
+        assert!(ClipMask::new(&MultiPolygon(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_clip_polygon_keeps_holes() {
+        // Mask: a 10 x 10 square with a hole; subject: a square with a hole
+        // straddling the mask's right edge
+        let mask = ClipMask::new(
+            &polygon![
+                exterior: [(x: 0.0, y: 0.0), (x: 10.0, y: 0.0), (x: 10.0, y: 10.0), (x: 0.0, y: 10.0)],
+                interiors: [[(x: 1.0, y: 1.0), (x: 2.0, y: 1.0), (x: 2.0, y: 2.0), (x: 1.0, y: 2.0)]],
+            ]
+            .into(),
+        )
+        .unwrap();
+
+        let clipped =
+            mask.clip_polygon(&square(0.5, 0.5, 15.0, 8.0), &[square(6.0, 3.0, 8.0, 5.0)]);
+        assert_eq!(clipped.len(), 1);
+        let (exterior, holes) = &clipped[0];
+        assert_eq!(ring_area(exterior), 9.5 * 7.5);
+        // The subject's hole and the mask's hole
+        let mut hole_areas: Vec<f64> = holes.iter().map(|ring| ring_area(ring)).collect();
+        hole_areas.sort_by(f64::total_cmp);
+        assert_eq!(hole_areas, vec![-4.0, -1.0]);
+
+        // Entirely outside
+        assert!(mask
+            .clip_polygon(&square(20.0, 20.0, 30.0, 30.0), &[])
+            .is_empty());
+
+        let lines = mask.clip_line(&[(-5.0, 5.0), (15.0, 5.0)]);
+        assert_eq!(lines, vec![vec![(0.0, 5.0), (10.0, 5.0)]]);
+    }
+}
@@ -0,0 +1,288 @@
+//! `ndarray` integration for grid construction and zero-copy contouring
+//!
+//! Scientific stacks usually hold a field as a 2-D value array with matching 2-D
+//! longitude and latitude arrays. [`ArrayGrid`] borrows those arrays as views and
+//! implements [`GridSource`], so contours are generated by reading the views in
+//! place. [`GeoGrid::from_arrays`] copies them into an owned [`GeoGrid`] instead.
+//!
+//! Views of any memory layout are accepted: C (row-major), Fortran (column-major)
+//! and arbitrarily strided slices all index the same way. Axis 0 is the grid row
+//! and axis 1 is the grid column, matching `GeoGrid`'s `[row][col]` layout.
+//!
+//! Requires the `ndarray` feature.
+
+use crate::error::{Error, Result};
+use crate::grid::{validate_dimensions, validate_levels, validate_thresholds, GeoGrid, GridSource};
+use crate::marching_squares::{generate_isobands, generate_isolines};
+use crate::types::{GridPoint, MarchingSquaresConfig};
+use geojson::Feature;
+use ndarray::ArrayView2;
+
+/// A borrowed grid over `ndarray` value, longitude and latitude views
+///
+/// # Example
+///
+/// ```rust
+/// use geo_marching_squares_rs::ArrayGrid;
+/// use ndarray::array;
+///
+/// let values = array![[10.0f32, 20.0], [15.0, 25.0]];
+/// let lon = array![[-100.0, -99.0], [-100.0, -99.0]];
+/// let lat = array![[41.0, 41.0], [40.0, 40.0]];
+///
+/// let grid = ArrayGrid::new(values.view(), lon.view(), lat.view())?;
+/// let isobands = grid.isobands(&[12.0, 18.0, 22.0])?;
+/// # Ok::<(), geo_marching_squares_rs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrayGrid<'a> {
+    /// Data values indexed [row, col]
+    values: ArrayView2<'a, f32>,
+    /// Longitudes in degrees (WGS84) indexed [row, col]
+    lon: ArrayView2<'a, f64>,
+    /// Latitudes in degrees (WGS84) indexed [row, col]
+    lat: ArrayView2<'a, f64>,
+    /// Configuration for marching squares algorithm
+    config: MarchingSquaresConfig,
+}
+
+impl<'a> ArrayGrid<'a> {
+    /// Create a grid view from value, longitude and latitude arrays
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The three arrays do not have the same shape
+    /// - Grid dimensions are less than 2x2
+    /// - Any coordinates are invalid (outside valid lat/lon ranges)
+    pub fn new(
+        values: ArrayView2<'a, f32>,
+        lon: ArrayView2<'a, f64>,
+        lat: ArrayView2<'a, f64>,
+    ) -> Result<Self> {
+        if values.is_empty() {
+            return Err(Error::EmptyGrid);
+        }
+
+        let shape = values.dim();
+        if lon.dim() != shape || lat.dim() != shape {
+            return Err(Error::invalid_dimensions(format!(
+                "Array shapes differ: values {:?}, lon {:?}, lat {:?}",
+                shape,
+                lon.dim(),
+                lat.dim()
+            )));
+        }
+
+        let (rows, cols) = shape;
+        validate_dimensions(rows, cols)?;
+
+        // Validate coordinates
+        for (&lon, &lat) in lon.iter().zip(lat.iter()) {
+            if !GridPoint::new(lon, lat, 0.0).is_valid() {
+                return Err(Error::invalid_coordinates(lat, lon));
+            }
+        }
+
+        Ok(Self {
+            values,
+            lon,
+            lat,
+            config: MarchingSquaresConfig::default(),
+        })
+    }
+
+    /// Create a grid view with custom configuration
+    pub fn new_with_config(
+        values: ArrayView2<'a, f32>,
+        lon: ArrayView2<'a, f64>,
+        lat: ArrayView2<'a, f64>,
+        config: MarchingSquaresConfig,
+    ) -> Result<Self> {
+        let mut grid = Self::new(values, lon, lat)?;
+        grid.config = config;
+        Ok(grid)
+    }
+
+    /// Replace the value view, keeping the coordinate views
+    ///
+    /// Useful when many fields share one coordinate grid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new values do not match the grid shape
+    pub fn with_values(&self, values: ArrayView2<'a, f32>) -> Result<Self> {
+        if values.dim() != self.values.dim() {
+            return Err(Error::invalid_dimensions(format!(
+                "Value array shape {:?} does not match grid shape {:?}",
+                values.dim(),
+                self.values.dim()
+            )));
+        }
+
+        Ok(Self {
+            values,
+            ..self.clone()
+        })
+    }
+
+    /// Get the number of rows in the grid
+    pub fn rows(&self) -> usize {
+        self.values.nrows()
+    }
+
+    /// Get the number of columns in the grid
+    pub fn cols(&self) -> usize {
+        self.values.ncols()
+    }
+
+    /// Get the grid point at a specific position
+    ///
+    /// Returns `None` if indices are out of bounds
+    pub fn get(&self, row: usize, col: usize) -> Option<GridPoint> {
+        if row < self.rows() && col < self.cols() {
+            Some(GridSource::point(self, row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Get a reference to the configuration
+    pub fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+
+    /// Get a mutable reference to the configuration
+    pub fn config_mut(&mut self) -> &mut MarchingSquaresConfig {
+        &mut self.config
+    }
+
+    /// Generate isobands (filled contours) for the given thresholds
+    ///
+    /// See [`GeoGrid::isobands`] for details.
+    pub fn isobands(&self, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        validate_thresholds(thresholds)?;
+        generate_isobands(self, thresholds)
+    }
+
+    /// Generate isolines (contour lines) for the given levels
+    ///
+    /// See [`GeoGrid::isolines`] for details.
+    pub fn isolines(&self, levels: &[f64]) -> Result<Vec<Feature>> {
+        validate_levels(levels)?;
+        generate_isolines(self, levels)
+    }
+}
+
+impl GridSource for ArrayGrid<'_> {
+    fn rows(&self) -> usize {
+        self.values.nrows()
+    }
+
+    fn cols(&self) -> usize {
+        self.values.ncols()
+    }
+
+    fn point(&self, row: usize, col: usize) -> GridPoint {
+        GridPoint::new(
+            self.lon[[row, col]],
+            self.lat[[row, col]],
+            self.values[[row, col]],
+        )
+    }
+
+    fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+}
+
+impl GeoGrid {
+    /// Create a new GeoGrid by copying `ndarray` value, longitude and latitude views
+    ///
+    /// Prefer [`ArrayGrid::new`] to contour the arrays without copying them.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ArrayGrid::new`]
+    pub fn from_arrays<'a>(
+        values: ArrayView2<'a, f32>,
+        lon: ArrayView2<'a, f64>,
+        lat: ArrayView2<'a, f64>,
+    ) -> Result<Self> {
+        let view = ArrayGrid::new(values, lon, lat)?;
+        let points = (0..view.rows())
+            .map(|row| {
+                (0..view.cols())
+                    .map(|col| GridSource::point(&view, row, col))
+                    .collect()
+            })
+            .collect();
+        Self::from_points(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array2, ShapeBuilder};
+
+    fn test_arrays() -> (Array2<f32>, Array2<f64>, Array2<f64>) {
+        let values =
+            Array2::from_shape_fn((3, 3), |(r, c)| 10.0 + r as f32 * 5.0 + c as f32 * 10.0);
+        let lon = Array2::from_shape_fn((3, 3), |(_, c)| -100.0 + c as f64);
+        let lat = Array2::from_shape_fn((3, 3), |(r, _)| 41.0 - r as f64);
+        (values, lon, lat)
+    }
+
+    #[test]
+    fn test_view_matches_owned_grid() {
+        let (values, lon, lat) = test_arrays();
+        let view = ArrayGrid::new(values.view(), lon.view(), lat.view()).unwrap();
+        let owned = GeoGrid::from_arrays(values.view(), lon.view(), lat.view()).unwrap();
+
+        assert_eq!(view.rows(), 3);
+        assert_eq!(view.cols(), 3);
+        assert_eq!(view.get(1, 2), owned.get(1, 2).copied());
+        assert!(view.get(3, 0).is_none());
+
+        let thresholds = [15.0, 25.0, 35.0];
+        let from_view = view.isobands(&thresholds).unwrap();
+        let from_owned = owned.isobands(&thresholds).unwrap();
+        assert_eq!(from_view.len(), from_owned.len());
+        for (a, b) in from_view.iter().zip(&from_owned) {
+            assert_eq!(a.properties, b.properties);
+        }
+    }
+
+    #[test]
+    fn test_fortran_layout() {
+        let (values, lon, lat) = test_arrays();
+        let mut values_f = Array2::zeros((3, 3).f());
+        values_f.assign(&values);
+        let mut lon_f = Array2::zeros((3, 3).f());
+        lon_f.assign(&lon);
+
+        let c_grid = ArrayGrid::new(values.view(), lon.view(), lat.view()).unwrap();
+        let f_grid = ArrayGrid::new(values_f.view(), lon_f.view(), lat.view()).unwrap();
+
+        for row in 0..3 {
+            for col in 0..3 {
+                assert_eq!(c_grid.get(row, col), f_grid.get(row, col));
+            }
+        }
+    }
+
+    #[test]
+    fn test_shape_mismatch() {
+        let (values, lon, _) = test_arrays();
+        let lat = Array2::<f64>::zeros((2, 3));
+        assert!(ArrayGrid::new(values.view(), lon.view(), lat.view()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_coordinates() {
+        let (values, lon, mut lat) = test_arrays();
+        lat[[0, 0]] = 95.0;
+        assert!(ArrayGrid::new(values.view(), lon.view(), lat.view()).is_err());
+    }
+}
@@ -0,0 +1,746 @@
+//! FlatGeobuf writer for isobands and isolines
+//!
+//! [`FgbWriter`] writes one FlatGeobuf feature per band or level, carrying the
+//! same level properties as the GeoJSON output (`lower_level`/`upper_level` for
+//! isobands, `isovalue` for isolines) and EPSG:4326 as the CRS.
+//!
+//! By default the file includes a packed Hilbert R-tree so clients can fetch
+//! spatial subsets with range requests. The index dictates the feature order,
+//! so features are encoded as they are written but held until
+//! [`FgbWriter::finish`]; only the compact binary encoding is kept, never the
+//! traced rings. With the index disabled (`with_index_node_size(0)`) every
+//! feature is written to the output immediately.
+//!
+//! Requires the `flatgeobuf` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use geo_marching_squares_rs::output::flatgeobuf::FgbWriter;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//! use std::fs::File;
+//! use std::io::BufWriter;
+//!
+//! # let grid = GeoGrid::from_points(vec![
+//! #     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//! #     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! # ])?;
+//! let file = BufWriter::new(File::create("bands.fgb")?);
+//! let mut writer = FgbWriter::isobands(file).with_name("temperature");
+//! for band in grid.isobands_geo(&[12.0, 18.0, 22.0])? {
+//!     writer.write_isoband(&band)?;
+//! }
+//! writer.finish()?;
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use crate::types::{Isoband, Isoline};
+use flatbuffers::{FlatBufferBuilder, ForwardsUOffset, TableFinishedWIPOffset, Vector, WIPOffset};
+use geo_types::LineString;
+use std::io::Write;
+
+/// File signature: "fgb", major version 3, "fgb", patch version 0
+const MAGIC: [u8; 8] = [b'f', b'g', b'b', 3, b'f', b'g', b'b', 0];
+
+/// Default number of children per R-tree node
+pub const DEFAULT_INDEX_NODE_SIZE: u16 = 16;
+
+/// Size in bytes of one packed R-tree node (four f64 bounds and a u64 offset)
+const NODE_ITEM_LEN: usize = 40;
+
+/// FlatGeobuf `GeometryType` values
+const GEOMETRY_POLYGON: u8 = 3;
+const GEOMETRY_MULTILINESTRING: u8 = 5;
+const GEOMETRY_MULTIPOLYGON: u8 = 6;
+
+/// FlatGeobuf `ColumnType::Double`
+const COLUMN_DOUBLE: u8 = 10;
+
+/// A finished table in a builder
+type TableOffset = WIPOffset<TableFinishedWIPOffset>;
+
+/// A finished vector of tables in a builder
+type TableVector<'fbb> = WIPOffset<Vector<'fbb, ForwardsUOffset<TableFinishedWIPOffset>>>;
+
+/// Flatbuffer vtable slot of field `n`
+const fn slot(n: u16) -> u16 {
+    4 + 2 * n
+}
+
+/// Which contour output a writer holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layer {
+    Isobands,
+    Isolines,
+}
+
+impl Layer {
+    fn geometry_type(self) -> u8 {
+        match self {
+            Layer::Isobands => GEOMETRY_MULTIPOLYGON,
+            Layer::Isolines => GEOMETRY_MULTILINESTRING,
+        }
+    }
+
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            Layer::Isobands => &["lower_level", "upper_level"],
+            Layer::Isolines => &["isovalue"],
+        }
+    }
+}
+
+/// An encoded feature waiting for the index to be built
+#[derive(Debug)]
+struct EncodedFeature {
+    bbox: [f64; 4],
+    bytes: Vec<u8>,
+}
+
+/// Streaming FlatGeobuf writer for one layer of isobands or isolines
+#[derive(Debug)]
+pub struct FgbWriter<W: Write> {
+    out: W,
+    layer: Layer,
+    name: Option<String>,
+    index_node_size: u16,
+    features: Vec<EncodedFeature>,
+    header_written: bool,
+}
+
+impl<W: Write> FgbWriter<W> {
+    /// Create a writer for isoband (MultiPolygon) features
+    pub fn isobands(out: W) -> Self {
+        Self::new(out, Layer::Isobands)
+    }
+
+    /// Create a writer for isoline (MultiLineString) features
+    pub fn isolines(out: W) -> Self {
+        Self::new(out, Layer::Isolines)
+    }
+
+    fn new(out: W, layer: Layer) -> Self {
+        Self {
+            out,
+            layer,
+            name: None,
+            index_node_size: DEFAULT_INDEX_NODE_SIZE,
+            features: Vec::new(),
+            header_written: false,
+        }
+    }
+
+    /// Set the dataset name stored in the header
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the number of children per R-tree node
+    ///
+    /// `0` disables the spatial index and streams features straight to the
+    /// output. Values of 1 are raised to 2, the smallest valid node size.
+    pub fn with_index_node_size(mut self, node_size: u16) -> Self {
+        self.index_node_size = if node_size == 0 { 0 } else { node_size.max(2) };
+        self
+    }
+
+    /// Write one isoband feature
+    ///
+    /// Bands without polygons are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is an isoline writer, or on I/O failure
+    pub fn write_isoband(&mut self, band: &Isoband) -> Result<()> {
+        self.expect_layer(Layer::Isobands)?;
+
+        let mut bbox = empty_bbox();
+        let mut fbb = FlatBufferBuilder::new();
+        let mut parts = Vec::with_capacity(band.polygons.0.len());
+        for polygon in &band.polygons {
+            let rings: Vec<&LineString<f64>> = std::iter::once(polygon.exterior())
+                .chain(polygon.interiors())
+                .collect();
+            let (xy, ends) = flatten(&rings, &mut bbox);
+            if xy.is_empty() {
+                continue;
+            }
+            parts.push(geometry(&mut fbb, &xy, &ends, Some(GEOMETRY_POLYGON), None));
+        }
+        if parts.is_empty() {
+            return Ok(());
+        }
+
+        let parts = fbb.create_vector(&parts);
+        let geometry = geometry(&mut fbb, &[], &[], Some(GEOMETRY_MULTIPOLYGON), Some(parts));
+        self.push_feature(fbb, geometry, &[band.lower, band.upper], bbox)
+    }
+
+    /// Write one isoline feature
+    ///
+    /// Levels without lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is an isoband writer, or on I/O failure
+    pub fn write_isoline(&mut self, isoline: &Isoline) -> Result<()> {
+        self.expect_layer(Layer::Isolines)?;
+
+        let mut bbox = empty_bbox();
+        let lines: Vec<&LineString<f64>> = isoline.lines.iter().collect();
+        let (xy, ends) = flatten(&lines, &mut bbox);
+        if xy.is_empty() {
+            return Ok(());
+        }
+
+        let mut fbb = FlatBufferBuilder::new();
+        let geometry = geometry(&mut fbb, &xy, &ends, None, None);
+        self.push_feature(fbb, geometry, &[isoline.level], bbox)
+    }
+
+    /// Write the header, index and any held features, returning the output
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn finish(mut self) -> Result<W> {
+        if self.index_node_size == 0 || self.features.is_empty() {
+            self.ensure_streaming_header()?;
+            self.out.flush()?;
+            return Ok(self.out);
+        }
+
+        let features = std::mem::take(&mut self.features);
+        let extent = features
+            .iter()
+            .fold(empty_bbox(), |acc, f| union(acc, f.bbox));
+
+        // Order features along the Hilbert curve over the dataset extent
+        let mut order: Vec<(u32, usize)> = features
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (hilbert_bbox(&f.bbox, &extent), i))
+            .collect();
+        order.sort_by_key(|&(h, _)| h);
+
+        let mut offset = 0u64;
+        let leaves: Vec<NodeItem> = order
+            .iter()
+            .map(|&(_, i)| {
+                let item = NodeItem {
+                    bbox: features[i].bbox,
+                    offset,
+                };
+                offset += features[i].bytes.len() as u64;
+                item
+            })
+            .collect();
+
+        self.out.write_all(&MAGIC)?;
+        self.out.write_all(&header(
+            self.layer,
+            self.name.as_deref(),
+            Some(extent),
+            features.len() as u64,
+            self.index_node_size,
+        ))?;
+        for node in packed_rtree(leaves, self.index_node_size) {
+            let mut item = [0u8; NODE_ITEM_LEN];
+            for (chunk, v) in item.chunks_exact_mut(8).zip(node.bbox) {
+                chunk.copy_from_slice(&v.to_le_bytes());
+            }
+            item[32..].copy_from_slice(&node.offset.to_le_bytes());
+            self.out.write_all(&item)?;
+        }
+        for (_, i) in order {
+            self.out.write_all(&features[i].bytes)?;
+        }
+
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn expect_layer(&self, layer: Layer) -> Result<()> {
+        if self.layer != layer {
+            return Err(Error::geometry_error(format!(
+                "FlatGeobuf writer holds {:?}, cannot write {:?}",
+                self.layer, layer
+            )));
+        }
+        Ok(())
+    }
+
+    fn ensure_streaming_header(&mut self) -> Result<()> {
+        if !self.header_written {
+            self.out.write_all(&MAGIC)?;
+            self.out
+                .write_all(&header(self.layer, self.name.as_deref(), None, 0, 0))?;
+            self.header_written = true;
+        }
+        Ok(())
+    }
+
+    fn push_feature(
+        &mut self,
+        mut fbb: FlatBufferBuilder,
+        geometry: TableOffset,
+        values: &[f64],
+        bbox: [f64; 4],
+    ) -> Result<()> {
+        // Properties: u16 column index followed by the little-endian value
+        let mut properties = Vec::with_capacity(values.len() * 10);
+        for (column, value) in values.iter().enumerate() {
+            properties.extend_from_slice(&(column as u16).to_le_bytes());
+            properties.extend_from_slice(&value.to_le_bytes());
+        }
+        let properties = fbb.create_vector(&properties);
+
+        let start = fbb.start_table();
+        fbb.push_slot_always(slot(0), geometry);
+        fbb.push_slot_always(slot(1), properties);
+        let feature = fbb.end_table(start);
+        fbb.finish_size_prefixed(feature, None);
+
+        if self.index_node_size == 0 {
+            self.ensure_streaming_header()?;
+            self.out.write_all(fbb.finished_data())?;
+        } else {
+            self.features.push(EncodedFeature {
+                bbox,
+                bytes: fbb.finished_data().to_vec(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Build the size-prefixed header flatbuffer
+fn header(
+    layer: Layer,
+    name: Option<&str>,
+    envelope: Option<[f64; 4]>,
+    features_count: u64,
+    index_node_size: u16,
+) -> Vec<u8> {
+    let mut fbb = FlatBufferBuilder::new();
+
+    let name = name.map(|name| fbb.create_string(name));
+    let envelope = envelope.map(|envelope| fbb.create_vector(&envelope));
+
+    let columns: Vec<_> = layer
+        .columns()
+        .iter()
+        .map(|&column| {
+            let column_name = fbb.create_string(column);
+            let start = fbb.start_table();
+            fbb.push_slot_always(slot(0), column_name);
+            fbb.push_slot_always(slot(1), COLUMN_DOUBLE);
+            fbb.end_table(start)
+        })
+        .collect();
+    let columns = fbb.create_vector(&columns);
+
+    let org = fbb.create_string("EPSG");
+    let start = fbb.start_table();
+    fbb.push_slot_always(slot(0), org);
+    fbb.push_slot_always(slot(1), 4326i32);
+    let crs = fbb.end_table(start);
+
+    let start = fbb.start_table();
+    if let Some(name) = name {
+        fbb.push_slot_always(slot(0), name);
+    }
+    if let Some(envelope) = envelope {
+        fbb.push_slot_always(slot(1), envelope);
+    }
+    fbb.push_slot_always(slot(2), layer.geometry_type());
+    fbb.push_slot_always(slot(7), columns);
+    fbb.push_slot(slot(8), features_count, 0);
+    // Always written: readers assume 16 when the field is absent
+    fbb.push_slot_always(slot(9), index_node_size);
+    fbb.push_slot_always(slot(10), crs);
+    let header = fbb.end_table(start);
+    fbb.finish_size_prefixed(header, None);
+
+    fbb.finished_data().to_vec()
+}
+
+/// Build a `Geometry` table from flat coordinates and part ends
+fn geometry<'fbb>(
+    fbb: &mut FlatBufferBuilder<'fbb>,
+    xy: &[f64],
+    ends: &[u32],
+    geometry_type: Option<u8>,
+    parts: Option<TableVector<'fbb>>,
+) -> TableOffset {
+    // Ends are only needed when there is more than one ring or line
+    let ends = (ends.len() > 1).then(|| fbb.create_vector(ends));
+    let xy = (!xy.is_empty()).then(|| fbb.create_vector(xy));
+
+    let start = fbb.start_table();
+    if let Some(ends) = ends {
+        fbb.push_slot_always(slot(0), ends);
+    }
+    if let Some(xy) = xy {
+        fbb.push_slot_always(slot(1), xy);
+    }
+    if let Some(geometry_type) = geometry_type {
+        fbb.push_slot_always(slot(6), geometry_type);
+    }
+    if let Some(parts) = parts {
+        fbb.push_slot_always(slot(7), parts);
+    }
+    fbb.end_table(start)
+}
+
+/// Flatten rings or lines into interleaved xy and cumulative end indices
+fn flatten(lines: &[&LineString<f64>], bbox: &mut [f64; 4]) -> (Vec<f64>, Vec<u32>) {
+    let mut xy = Vec::new();
+    let mut ends = Vec::with_capacity(lines.len());
+    for line in lines {
+        if line.0.is_empty() {
+            continue;
+        }
+        for c in line.coords() {
+            xy.push(c.x);
+            xy.push(c.y);
+            *bbox = union(*bbox, [c.x, c.y, c.x, c.y]);
+        }
+        ends.push((xy.len() / 2) as u32);
+    }
+    (xy, ends)
+}
+
+fn empty_bbox() -> [f64; 4] {
+    [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ]
+}
+
+fn union(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    [
+        a[0].min(b[0]),
+        a[1].min(b[1]),
+        a[2].max(b[2]),
+        a[3].max(b[3]),
+    ]
+}
+
+/// One node of the packed R-tree
+#[derive(Debug, Clone, Copy)]
+struct NodeItem {
+    bbox: [f64; 4],
+    offset: u64,
+}
+
+/// Build the packed Hilbert R-tree over leaves already in Hilbert order
+///
+/// Nodes are laid out root first and leaves last. Leaf offsets are byte
+/// offsets into the feature section; parent offsets are the node index of the
+/// first child.
+fn packed_rtree(leaves: Vec<NodeItem>, node_size: u16) -> Vec<NodeItem> {
+    let level_bounds = level_bounds(leaves.len(), usize::from(node_size));
+    let num_nodes = level_bounds[0].1;
+
+    let mut nodes = vec![
+        NodeItem {
+            bbox: empty_bbox(),
+            offset: 0,
+        };
+        num_nodes
+    ];
+    nodes[level_bounds[0].0..].copy_from_slice(&leaves);
+
+    for level in 0..level_bounds.len() - 1 {
+        let (mut pos, end) = level_bounds[level];
+        let mut parent = level_bounds[level + 1].0;
+        while pos < end {
+            let mut node = NodeItem {
+                bbox: empty_bbox(),
+                offset: pos as u64,
+            };
+            for _ in 0..node_size {
+                if pos == end {
+                    break;
+                }
+                node.bbox = union(node.bbox, nodes[pos].bbox);
+                pos += 1;
+            }
+            nodes[parent] = node;
+            parent += 1;
+        }
+    }
+
+    nodes
+}
+
+/// Node index ranges of each tree level, leaves first
+fn level_bounds(num_items: usize, node_size: usize) -> Vec<(usize, usize)> {
+    let mut n = num_items;
+    let mut num_nodes = n;
+    let mut level_num_nodes = vec![n];
+    loop {
+        n = n.div_ceil(node_size);
+        num_nodes += n;
+        level_num_nodes.push(n);
+        if n == 1 {
+            break;
+        }
+    }
+
+    let mut end = num_nodes;
+    level_num_nodes
+        .into_iter()
+        .map(|count| {
+            end -= count;
+            (end, end + count)
+        })
+        .collect()
+}
+
+/// Hilbert index of a bbox center on a 16-bit grid over the extent
+fn hilbert_bbox(bbox: &[f64; 4], extent: &[f64; 4]) -> u32 {
+    const HILBERT_MAX: f64 = 65535.0;
+    let scale = |center: f64, min: f64, max: f64| {
+        if max > min {
+            (HILBERT_MAX * (center - min) / (max - min)).floor() as u32
+        } else {
+            0
+        }
+    };
+    let x = scale((bbox[0] + bbox[2]) / 2.0, extent[0], extent[2]);
+    let y = scale((bbox[1] + bbox[3]) / 2.0, extent[1], extent[3]);
+    hilbert(x, y)
+}
+
+/// Hilbert curve index of a point on a 16-bit grid
+///
+/// Branch-free prefix-scan formulation, as used by the FlatGeobuf reference
+/// implementations, so index ordering matches other writers.
+fn hilbert(x: u32, y: u32) -> u32 {
+    // Initial prefix scan round, prime with x and y
+    let (mut a, mut b, mut c, mut d);
+    {
+        let a0 = x ^ y;
+        let b0 = 0xFFFF ^ a0;
+        let c0 = 0xFFFF ^ (x | y);
+        let d0 = x & (y ^ 0xFFFF);
+        a = a0 | (b0 >> 1);
+        b = (a0 >> 1) ^ a0;
+        c = ((c0 >> 1) ^ (b0 & (d0 >> 1))) ^ c0;
+        d = ((a0 & (c0 >> 1)) ^ (d0 >> 1)) ^ d0;
+    }
+    for shift in [2, 4] {
+        let (a0, b0, c0, d0) = (a, b, c, d);
+        a = (a0 & (a0 >> shift)) ^ (b0 & (b0 >> shift));
+        b = (a0 & (b0 >> shift)) ^ (b0 & ((a0 ^ b0) >> shift));
+        c ^= (a0 & (c0 >> shift)) ^ (b0 & (d0 >> shift));
+        d ^= (b0 & (c0 >> shift)) ^ ((a0 ^ b0) & (d0 >> shift));
+    }
+    // Final round and projection
+    {
+        let (a0, b0, c0, d0) = (a, b, c, d);
+        c ^= (a0 & (c0 >> 8)) ^ (b0 & (d0 >> 8));
+        d ^= (b0 & (c0 >> 8)) ^ ((a0 ^ b0) & (d0 >> 8));
+    }
+
+    // Undo transformation prefix scan
+    let a = c ^ (c >> 1);
+    let b = d ^ (d >> 1);
+
+    // Recover index bits
+    let interleave = |mut v: u32| {
+        v = (v | (v << 8)) & 0x00FF_00FF;
+        v = (v | (v << 4)) & 0x0F0F_0F0F;
+        v = (v | (v << 2)) & 0x3333_3333;
+        (v | (v << 1)) & 0x5555_5555
+    };
+    let i0 = x ^ y;
+    let i1 = b | (0xFFFF ^ (i0 | a));
+    (interleave(i1) << 1) | interleave(i0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flatbuffers::Table;
+    use geo_types::{MultiLineString, MultiPolygon, Polygon};
+
+    fn band(lower: f64, lon: f64) -> Isoband {
+        let ring = LineString::from(vec![
+            (lon, 40.0),
+            (lon + 1.0, 40.0),
+            (lon + 1.0, 41.0),
+            (lon, 41.0),
+            (lon, 40.0),
+        ]);
+        Isoband {
+            lower,
+            upper: lower + 10.0,
+            polygons: MultiPolygon(vec![Polygon::new(ring, vec![])]),
+        }
+    }
+
+    fn u32_at(bytes: &[u8], pos: usize) -> usize {
+        u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize
+    }
+
+    fn f64_at(bytes: &[u8], pos: usize) -> f64 {
+        f64::from_le_bytes(bytes[pos..pos + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_hilbert_curve_is_continuous() {
+        // The first 256x256 indices fill the corner sub-square, stepping
+        // between neighboring cells
+        let mut cells = vec![(0u32, 0u32); 256 * 256];
+        for x in 0..256 {
+            for y in 0..256 {
+                let h = hilbert(x, y) as usize;
+                assert!(h < cells.len());
+                cells[h] = (x, y);
+            }
+        }
+        for pair in cells.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            assert_eq!(x0.abs_diff(x1) + y0.abs_diff(y1), 1);
+        }
+    }
+
+    #[test]
+    fn test_level_bounds() {
+        assert_eq!(level_bounds(1, 16), vec![(1, 2), (0, 1)]);
+        assert_eq!(level_bounds(20, 16), vec![(3, 23), (1, 3), (0, 1)]);
+    }
+
+    #[test]
+    fn test_indexed_file_layout() {
+        let bands: Vec<Isoband> = (0..20)
+            .map(|i| band(i as f64 * 10.0, -120.0 + i as f64 * 2.0))
+            .collect();
+        let mut writer = FgbWriter::isobands(Vec::new()).with_name("bands");
+        for b in &bands {
+            writer.write_isoband(b).unwrap();
+        }
+        assert!(writer
+            .write_isoline(&Isoline {
+                level: 1.0,
+                lines: MultiLineString(vec![]),
+            })
+            .is_err());
+        let bytes = writer.finish().unwrap();
+
+        assert_eq!(bytes[..8], MAGIC);
+        let header_len = u32_at(&bytes, 8);
+        let header_buf = &bytes[8..12 + header_len];
+        let header = unsafe { flatbuffers::size_prefixed_root_unchecked::<Table>(header_buf) };
+        unsafe {
+            assert_eq!(
+                header.get::<ForwardsUOffset<&str>>(slot(0), None),
+                Some("bands")
+            );
+            assert_eq!(header.get::<u8>(slot(2), None), Some(GEOMETRY_MULTIPOLYGON));
+            assert_eq!(header.get::<u64>(slot(8), None), Some(20));
+            assert_eq!(header.get::<u16>(slot(9), None), Some(16));
+            let envelope = header
+                .get::<ForwardsUOffset<Vector<f64>>>(slot(1), None)
+                .unwrap();
+            assert_eq!(
+                envelope.iter().collect::<Vec<_>>(),
+                vec![-120.0, 40.0, -81.0, 41.0]
+            );
+            let columns = header
+                .get::<ForwardsUOffset<Vector<ForwardsUOffset<Table>>>>(slot(7), None)
+                .unwrap();
+            assert_eq!(columns.len(), 2);
+            assert_eq!(
+                columns.get(1).get::<ForwardsUOffset<&str>>(slot(0), None),
+                Some("upper_level")
+            );
+        }
+
+        // Root node covers the extent; leaves point at consecutive features
+        let index_start = 12 + header_len;
+        let num_nodes = level_bounds(20, 16)[0].1;
+        assert_eq!(f64_at(&bytes, index_start), -120.0);
+        assert_eq!(f64_at(&bytes, index_start + 16), -81.0);
+
+        let features_start = index_start + num_nodes * NODE_ITEM_LEN;
+        let mut pos = features_start;
+        let mut lowers = Vec::new();
+        for leaf in 3..23 {
+            let offset = u64::from_le_bytes(
+                bytes[index_start + leaf * NODE_ITEM_LEN + 32
+                    ..index_start + (leaf + 1) * NODE_ITEM_LEN]
+                    .try_into()
+                    .unwrap(),
+            );
+            assert_eq!(features_start + offset as usize, pos);
+
+            let len = u32_at(&bytes, pos);
+            let feature = unsafe {
+                flatbuffers::size_prefixed_root_unchecked::<Table>(&bytes[pos..pos + 4 + len])
+            };
+            let properties = unsafe {
+                feature
+                    .get::<ForwardsUOffset<Vector<u8>>>(slot(1), None)
+                    .unwrap()
+            };
+            assert_eq!(properties.len(), 20);
+            lowers.push(f64::from_le_bytes(
+                properties.bytes()[2..10].try_into().unwrap(),
+            ));
+            pos += 4 + len;
+        }
+        assert_eq!(pos, bytes.len());
+
+        lowers.sort_by(f64::total_cmp);
+        assert_eq!(lowers, bands.iter().map(|b| b.lower).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_streaming_without_index() {
+        let lines = Isoline {
+            level: 5.0,
+            lines: MultiLineString(vec![
+                LineString::from(vec![(0.0, 0.0), (1.0, 1.0)]),
+                LineString::from(vec![(2.0, 2.0), (3.0, 3.0)]),
+            ]),
+        };
+        let mut writer = FgbWriter::isolines(Vec::new()).with_index_node_size(0);
+        writer.write_isoline(&lines).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let header_len = u32_at(&bytes, 8);
+        let header = unsafe {
+            flatbuffers::size_prefixed_root_unchecked::<Table>(&bytes[8..12 + header_len])
+        };
+        unsafe {
+            assert_eq!(header.get::<u16>(slot(9), None), Some(0));
+            assert_eq!(header.get::<u64>(slot(8), Some(0)), Some(0));
+        }
+
+        let feature_buf = &bytes[12 + header_len..];
+        let feature = unsafe { flatbuffers::size_prefixed_root_unchecked::<Table>(feature_buf) };
+        let geometry = unsafe {
+            feature
+                .get::<ForwardsUOffset<Table>>(slot(0), None)
+                .unwrap()
+        };
+        unsafe {
+            let ends = geometry
+                .get::<ForwardsUOffset<Vector<u32>>>(slot(0), None)
+                .unwrap();
+            assert_eq!(ends.iter().collect::<Vec<_>>(), vec![2, 4]);
+            let xy = geometry
+                .get::<ForwardsUOffset<Vector<f64>>>(slot(1), None)
+                .unwrap();
+            assert_eq!(xy.len(), 8);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Streaming GeoJSON FeatureCollection output
+//!
+//! [`GeoGrid::isobands`](crate::GeoGrid::isobands) returns every band as a
+//! `geojson::Feature`, which the caller then serializes in one piece.
+//! [`GeoJsonWriter`] instead writes a `FeatureCollection` to any
+//! [`io::Write`](std::io::Write) and emits each band or isoline level as soon
+//! as it has been traced. Coordinates are written straight from the traced
+//! rings, so peak memory stays around a single band.
+//!
+//! Bands are traced one after another, in threshold order, and empty bands are
+//! skipped. Features carry the same `lower_level`/`upper_level` and `isovalue`
+//! properties as the in-memory API. Coordinates are rounded with the grid's
+//! configured precision unless overridden, and rings are closed before
+//! rounding, as in every other output path.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::output::geojson::GeoJsonWriter;
+//! use geo_marching_squares_rs::{CoordinatePrecision, GeoGrid, GridPoint};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//!
+//! let mut writer =
+//!     GeoJsonWriter::new(Vec::new()).with_precision(CoordinatePrecision::decimals(4));
+//! writer.write_isobands(&grid, &[12.0, 18.0, 22.0])?;
+//! let json = writer.finish()?;
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::error::Result;
+use crate::grid::{validate_levels, validate_thresholds, GridSource};
+use crate::marching_squares::{closed_ring, isoline_segments, rounded_xy, trace_band, Clipping};
+use crate::types::{CoordinatePrecision, Point};
+use std::io::Write;
+
+/// Streaming GeoJSON FeatureCollection writer
+pub struct GeoJsonWriter<W: Write> {
+    out: W,
+    precision: Option<CoordinatePrecision>,
+    features: usize,
+    header_written: bool,
+}
+
+impl<W: Write> GeoJsonWriter<W> {
+    /// Create a writer rounding coordinates with each grid's configured
+    /// [`precision`](crate::MarchingSquaresConfig::precision)
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            precision: None,
+            features: 0,
+            header_written: false,
+        }
+    }
+
+    /// Override the grid's coordinate precision
+    pub fn with_precision(mut self, precision: CoordinatePrecision) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Number of features written so far
+    pub fn features_written(&self) -> usize {
+        self.features
+    }
+
+    /// Trace and write the isobands between consecutive thresholds
+    ///
+    /// Returns the number of (non-empty) bands written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thresholds are invalid, or on I/O failure
+    pub fn write_isobands<G: GridSource + ?Sized>(
+        &mut self,
+        grid: &G,
+        thresholds: &[f64],
+    ) -> Result<usize> {
+        validate_thresholds(thresholds)?;
+        let precision = self.precision.unwrap_or(grid.config().precision);
+        let clipping = Clipping::new(grid)?;
+
+        let mut written = 0;
+        for pair in thresholds.windows(2) {
+            let (lower, upper) = (pair[0], pair[1]);
+            let Some(polygons) = trace_band(grid, &clipping, lower, upper)? else {
+                continue;
+            };
+
+            self.begin_feature("MultiPolygon")?;
+            for (i, (exterior, holes)) in polygons.iter().enumerate() {
+                self.separator(i)?;
+                self.out.write_all(b"[")?;
+                for (j, ring) in std::iter::once(exterior).chain(holes).enumerate() {
+                    self.separator(j)?;
+                    self.write_positions(closed_ring(ring, precision))?;
+                }
+                self.out.write_all(b"]")?;
+            }
+            self.end_feature(&[("lower_level", lower), ("upper_level", upper)])?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Trace and write the isolines at each level
+    ///
+    /// Returns the number of (non-empty) levels written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no levels are given, or on I/O failure
+    pub fn write_isolines<G: GridSource + ?Sized>(
+        &mut self,
+        grid: &G,
+        levels: &[f64],
+    ) -> Result<usize> {
+        validate_levels(levels)?;
+        let precision = self.precision.unwrap_or(grid.config().precision);
+        let clipping = Clipping::new(grid)?;
+
+        let mut written = 0;
+        for &level in levels {
+            let segments: Vec<Vec<Point>> = isoline_segments(grid, &clipping, level)
+                .into_iter()
+                .filter(|segment| segment.iter().filter(|p| p.is_actual()).count() >= 2)
+                .collect();
+            if segments.is_empty() {
+                continue;
+            }
+
+            self.begin_feature("MultiLineString")?;
+            for (i, segment) in segments.iter().enumerate() {
+                self.separator(i)?;
+                self.write_positions(segment.iter().filter_map(|p| rounded_xy(p, precision)))?;
+            }
+            self.end_feature(&[("isovalue", level)])?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Write an already-built feature into the collection
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O or serialization failure
+    pub fn write_feature(&mut self, feature: &geojson::Feature) -> Result<()> {
+        self.ensure_header()?;
+        self.separator(self.features)?;
+        write_json(&mut self.out, feature)?;
+        self.features += 1;
+        Ok(())
+    }
+
+    /// Close the collection and return the output
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn finish(mut self) -> Result<W> {
+        self.ensure_header()?;
+        self.out.write_all(b"]}\n")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn ensure_header(&mut self) -> Result<()> {
+        if !self.header_written {
+            self.out
+                .write_all(b"{\"type\":\"FeatureCollection\",\"features\":[")?;
+            self.header_written = true;
+        }
+        Ok(())
+    }
+
+    fn begin_feature(&mut self, geometry_type: &str) -> Result<()> {
+        self.ensure_header()?;
+        self.separator(self.features)?;
+        write!(
+            self.out,
+            "{{\"type\":\"Feature\",\"geometry\":{{\"type\":\"{geometry_type}\",\"coordinates\":["
+        )?;
+        Ok(())
+    }
+
+    fn end_feature(&mut self, properties: &[(&str, f64)]) -> Result<()> {
+        self.out.write_all(b"]},\"properties\":{")?;
+        for (i, (name, value)) in properties.iter().enumerate() {
+            self.separator(i)?;
+            write!(self.out, "\"{name}\":")?;
+            write_json(&mut self.out, value)?;
+        }
+        self.out.write_all(b"}}")?;
+        self.features += 1;
+        Ok(())
+    }
+
+    fn write_positions(&mut self, positions: impl Iterator<Item = (f64, f64)>) -> Result<()> {
+        self.out.write_all(b"[")?;
+        for (i, (x, y)) in positions.enumerate() {
+            self.separator(i)?;
+            self.out.write_all(b"[")?;
+            write_json(&mut self.out, &x)?;
+            self.out.write_all(b",")?;
+            write_json(&mut self.out, &y)?;
+            self.out.write_all(b"]")?;
+        }
+        self.out.write_all(b"]")?;
+        Ok(())
+    }
+
+    fn separator(&mut self, index: usize) -> Result<()> {
+        if index > 0 {
+            self.out.write_all(b",")?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialize one JSON value to the output
+fn write_json<W: Write, T: serde::Serialize + ?Sized>(out: &mut W, value: &T) -> Result<()> {
+    serde_json::to_writer(out, value).map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marching_squares::{generate_isobands, generate_isolines};
+    use crate::{GeoGrid, GridPoint};
+    use geojson::{FeatureCollection, GeoJson};
+
+    fn grid() -> GeoGrid {
+        let points = (0..4)
+            .map(|row| {
+                (0..4)
+                    .map(|col| {
+                        let value = if (1..3).contains(&row) && (1..3).contains(&col) {
+                            20.0
+                        } else {
+                            5.0
+                        };
+                        GridPoint::new(
+                            -100.0 + col as f64 * 0.123456789,
+                            40.0 - row as f64 * 0.1,
+                            value,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        GeoGrid::from_points(points).unwrap()
+    }
+
+    fn parse(bytes: Vec<u8>) -> FeatureCollection {
+        match String::from_utf8(bytes)
+            .unwrap()
+            .parse::<GeoJson>()
+            .unwrap()
+        {
+            GeoJson::FeatureCollection(fc) => fc,
+            other => panic!("expected a FeatureCollection, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_streamed_bands_match_in_memory() {
+        let grid = grid();
+        let mut writer = GeoJsonWriter::new(Vec::new());
+        assert_eq!(
+            writer
+                .write_isobands(&grid, &[0.0, 10.0, 30.0, 40.0])
+                .unwrap(),
+            2
+        );
+        let streamed = parse(writer.finish().unwrap());
+
+        let expected = generate_isobands(&grid, &[0.0, 10.0, 30.0, 40.0]).unwrap();
+        assert_eq!(streamed.features.len(), expected.len());
+        for (s, e) in streamed.features.iter().zip(&expected) {
+            assert_eq!(s.properties, e.properties);
+            let count = |f: &geojson::Feature| match &f.geometry.as_ref().unwrap().value {
+                geojson::Value::MultiPolygon(p) => p.iter().flatten().map(Vec::len).sum::<usize>(),
+                _ => panic!("expected a MultiPolygon"),
+            };
+            assert_eq!(count(s), count(e));
+        }
+    }
+
+    #[test]
+    fn test_precision_and_isolines() {
+        let grid = grid();
+        let mut writer =
+            GeoJsonWriter::new(Vec::new()).with_precision(CoordinatePrecision::decimals(2));
+        writer.write_isolines(&grid, &[12.5, 100.0]).unwrap();
+        let json = writer.finish().unwrap();
+        let collection = parse(json);
+        assert_eq!(collection.features.len(), 1);
+        assert_eq!(
+            collection.features[0].property("isovalue"),
+            Some(&serde_json::json!(12.5))
+        );
+        let geojson::Value::MultiLineString(lines) =
+            &collection.features[0].geometry.as_ref().unwrap().value
+        else {
+            panic!("expected a MultiLineString");
+        };
+        for position in lines.iter().flatten() {
+            for &v in position {
+                assert_eq!(v, (v * 100.0).round() / 100.0);
+            }
+        }
+        assert_eq!(
+            generate_isolines(&grid, &[12.5]).unwrap()[0].property("isovalue"),
+            Some(&serde_json::json!(12.5))
+        );
+    }
+
+    #[test]
+    fn test_empty_collection_and_feature_passthrough() {
+        let mut writer = GeoJsonWriter::new(Vec::new());
+        assert_eq!(writer.write_isobands(&grid(), &[100.0, 200.0]).unwrap(), 0);
+        assert!(writer.write_isobands(&grid(), &[1.0]).is_err());
+        let feature = generate_isobands(&grid(), &[10.0, 30.0]).unwrap().remove(0);
+        writer.write_feature(&feature).unwrap();
+        assert_eq!(writer.features_written(), 1);
+        assert_eq!(parse(writer.finish().unwrap()).features, vec![feature]);
+
+        let empty = GeoJsonWriter::new(Vec::new()).finish().unwrap();
+        assert_eq!(empty, b"{\"type\":\"FeatureCollection\",\"features\":[]}\n");
+    }
+}
@@ -0,0 +1,417 @@
+//! GeoParquet and GeoArrow output for analytics pipelines
+//!
+//! Requires the `geoparquet` feature.
+//!
+//! [`isobands_record_batch`] and [`isolines_record_batch`] convert contour
+//! results to Arrow record batches with the band levels as `Float64` columns
+//! and a `geometry` column of little-endian WKB. The geometry field carries the
+//! `geoarrow.wkb` extension type, and the schema carries GeoParquet `geo`
+//! metadata, so batches can be handed straight to DataFusion, DuckDB or any
+//! other Arrow consumer.
+//!
+//! [`GeoParquetWriter`] writes the same layout to a GeoParquet 1.1 file. The
+//! `geo` file metadata (encoding, geometry types and the overall bounding box)
+//! is written when the writer is finished, once every row has been seen.
+//! Coordinates are lon/lat, the GeoParquet default CRS (OGC:CRS84), so no
+//! `crs` key is written.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::output::geoparquet::GeoParquetWriter;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//!
+//! let mut writer = GeoParquetWriter::isobands(Vec::new())?;
+//! writer.write_isobands(&grid.isobands_geo(&[12.0, 18.0, 22.0])?)?;
+//! let parquet = writer.finish()?;
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use super::wkb::WkbFormat;
+use crate::error::{Error, Result};
+use crate::types::{Isoband, Isoline};
+use arrow_array::{ArrayRef, BinaryArray, Float64Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use geo_types::LineString;
+use parquet::arrow::ArrowWriter;
+use parquet::format::KeyValue;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+/// GeoParquet specification version written to the `geo` metadata
+pub const GEOPARQUET_VERSION: &str = "1.1.0";
+
+/// Name of the geometry column
+pub const GEOMETRY_COLUMN: &str = "geometry";
+
+/// Kind of contour geometry stored in a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layer {
+    Isobands,
+    Isolines,
+}
+
+impl Layer {
+    fn geometry_type(self) -> &'static str {
+        match self {
+            Layer::Isobands => "MultiPolygon",
+            Layer::Isolines => "MultiLineString",
+        }
+    }
+
+    fn schema(self) -> Schema {
+        let level = |name: &str| Field::new(name, DataType::Float64, false);
+        let mut fields = match self {
+            Layer::Isobands => vec![level("lower_level"), level("upper_level")],
+            Layer::Isolines => vec![level("isovalue")],
+        };
+        fields.push(
+            Field::new(GEOMETRY_COLUMN, DataType::Binary, false).with_metadata(HashMap::from([(
+                "ARROW:extension:name".to_string(),
+                "geoarrow.wkb".to_string(),
+            )])),
+        );
+        Schema::new(fields)
+    }
+}
+
+/// Arrow schema of isoband tables: `lower_level`, `upper_level`, `geometry`
+pub fn isobands_schema() -> SchemaRef {
+    Arc::new(Layer::Isobands.schema())
+}
+
+/// Arrow schema of isoline tables: `isovalue`, `geometry`
+pub fn isolines_schema() -> SchemaRef {
+    Arc::new(Layer::Isolines.schema())
+}
+
+/// Convert isobands to a record batch with GeoParquet `geo` schema metadata
+///
+/// # Errors
+///
+/// Returns an error if Arrow rejects the columns
+pub fn isobands_record_batch(isobands: &[Isoband]) -> Result<RecordBatch> {
+    let (columns, bbox) = isoband_columns(isobands);
+    record_batch(Layer::Isobands, columns, Some(&bbox))
+}
+
+/// Convert isolines to a record batch with GeoParquet `geo` schema metadata
+///
+/// # Errors
+///
+/// Returns an error if Arrow rejects the columns
+pub fn isolines_record_batch(isolines: &[Isoline]) -> Result<RecordBatch> {
+    let (columns, bbox) = isoline_columns(isolines);
+    record_batch(Layer::Isolines, columns, Some(&bbox))
+}
+
+fn isoband_columns(isobands: &[Isoband]) -> (Vec<ArrayRef>, BoundingBox) {
+    let mut bbox = BoundingBox::default();
+    isobands
+        .iter()
+        .flat_map(|band| &band.polygons)
+        .for_each(|polygon| bbox.extend(polygon.exterior()));
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Float64Array::from_iter_values(
+            isobands.iter().map(|b| b.lower),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            isobands.iter().map(|b| b.upper),
+        )),
+        Arc::new(BinaryArray::from_iter_values(
+            isobands.iter().map(|b| b.to_wkb(WkbFormat::default())),
+        )),
+    ];
+    (columns, bbox)
+}
+
+fn isoline_columns(isolines: &[Isoline]) -> (Vec<ArrayRef>, BoundingBox) {
+    let mut bbox = BoundingBox::default();
+    isolines
+        .iter()
+        .flat_map(|isoline| &isoline.lines)
+        .for_each(|line| bbox.extend(line));
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Float64Array::from_iter_values(
+            isolines.iter().map(|l| l.level),
+        )),
+        Arc::new(BinaryArray::from_iter_values(
+            isolines.iter().map(|l| l.to_wkb(WkbFormat::default())),
+        )),
+    ];
+    (columns, bbox)
+}
+
+/// Assemble a batch, attaching `geo` schema metadata when `bbox` is given
+fn record_batch(
+    layer: Layer,
+    columns: Vec<ArrayRef>,
+    bbox: Option<&BoundingBox>,
+) -> Result<RecordBatch> {
+    let mut schema = layer.schema();
+    if let Some(bbox) = bbox {
+        schema = schema.with_metadata(HashMap::from([(
+            "geo".to_string(),
+            geo_metadata(layer, bbox),
+        )]));
+    }
+    RecordBatch::try_new(Arc::new(schema), columns)
+        .map_err(|e| Error::geometry_error(format!("Failed to build record batch: {e}")))
+}
+
+/// Streaming GeoParquet file writer for one layer of isobands or isolines
+pub struct GeoParquetWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    layer: Layer,
+    bbox: BoundingBox,
+}
+
+impl<W: Write + Send> GeoParquetWriter<W> {
+    /// Create a writer for an isoband table
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Parquet writer cannot be created
+    pub fn isobands(out: W) -> Result<Self> {
+        Self::new(out, Layer::Isobands)
+    }
+
+    /// Create a writer for an isoline table
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Parquet writer cannot be created
+    pub fn isolines(out: W) -> Result<Self> {
+        Self::new(out, Layer::Isolines)
+    }
+
+    fn new(out: W, layer: Layer) -> Result<Self> {
+        let writer = ArrowWriter::try_new(out, Arc::new(layer.schema()), None)
+            .map_err(std::io::Error::other)?;
+        Ok(Self {
+            writer,
+            layer,
+            bbox: BoundingBox::default(),
+        })
+    }
+
+    /// Append isobands as rows
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is an isoline writer, or on write failure
+    pub fn write_isobands(&mut self, isobands: &[Isoband]) -> Result<()> {
+        if self.layer != Layer::Isobands {
+            return Err(Error::geometry_error(
+                "Cannot write isobands to an isoline GeoParquet file",
+            ));
+        }
+        let (columns, bbox) = isoband_columns(isobands);
+        self.write_batch(columns, &bbox)
+    }
+
+    /// Append isolines as rows
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is an isoband writer, or on write failure
+    pub fn write_isolines(&mut self, isolines: &[Isoline]) -> Result<()> {
+        if self.layer != Layer::Isolines {
+            return Err(Error::geometry_error(
+                "Cannot write isolines to an isoband GeoParquet file",
+            ));
+        }
+        let (columns, bbox) = isoline_columns(isolines);
+        self.write_batch(columns, &bbox)
+    }
+
+    fn write_batch(&mut self, columns: Vec<ArrayRef>, bbox: &BoundingBox) -> Result<()> {
+        // The file-level `geo` metadata is written by `finish`
+        let batch = record_batch(self.layer, columns, None)?;
+        self.writer.write(&batch).map_err(std::io::Error::other)?;
+        self.bbox.merge(bbox);
+        Ok(())
+    }
+
+    /// Write the `geo` metadata and footer and return the output
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on write failure
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.append_key_value_metadata(KeyValue::new(
+            "geo".to_string(),
+            geo_metadata(self.layer, &self.bbox),
+        ));
+        Ok(self.writer.into_inner().map_err(std::io::Error::other)?)
+    }
+}
+
+/// GeoParquet `geo` metadata JSON for a layer
+fn geo_metadata(layer: Layer, bbox: &BoundingBox) -> String {
+    let mut column = serde_json::json!({
+        "encoding": "WKB",
+        "geometry_types": [layer.geometry_type()],
+    });
+    if !bbox.is_empty() {
+        column["bbox"] = serde_json::json!([bbox.min_x, bbox.min_y, bbox.max_x, bbox.max_y]);
+    }
+    serde_json::json!({
+        "version": GEOPARQUET_VERSION,
+        "primary_column": GEOMETRY_COLUMN,
+        "columns": { GEOMETRY_COLUMN: column },
+    })
+    .to_string()
+}
+
+/// Running lon/lat extent of written geometries
+#[derive(Debug, Clone, Copy)]
+struct BoundingBox {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+impl Default for BoundingBox {
+    fn default() -> Self {
+        Self {
+            min_x: f64::INFINITY,
+            min_y: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_y: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl BoundingBox {
+    fn is_empty(&self) -> bool {
+        self.min_x > self.max_x
+    }
+
+    fn extend(&mut self, line: &LineString<f64>) {
+        for c in line.coords() {
+            self.min_x = self.min_x.min(c.x);
+            self.min_y = self.min_y.min(c.y);
+            self.max_x = self.max_x.max(c.x);
+            self.max_y = self.max_y.max(c.y);
+        }
+    }
+
+    fn merge(&mut self, other: &BoundingBox) {
+        self.min_x = self.min_x.min(other.min_x);
+        self.min_y = self.min_y.min(other.min_y);
+        self.max_x = self.max_x.max(other.max_x);
+        self.max_y = self.max_y.max(other.max_y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use geo_types::{MultiLineString, MultiPolygon, Polygon};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn band(lower: f64, offset: f64) -> Isoband {
+        let exterior = LineString::from(vec![
+            (offset, 0.0),
+            (offset + 1.0, 0.0),
+            (offset + 1.0, 1.0),
+            (offset, 0.0),
+        ]);
+        Isoband {
+            lower,
+            upper: lower + 5.0,
+            polygons: MultiPolygon(vec![Polygon::new(exterior, vec![])]),
+        }
+    }
+
+    #[test]
+    fn test_record_batch_layout() {
+        let batch = isobands_record_batch(&[band(0.0, 0.0), band(5.0, 2.0)]).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 3);
+
+        let schema = batch.schema();
+        let geometry = schema.field_with_name(GEOMETRY_COLUMN).unwrap();
+        assert_eq!(
+            geometry
+                .metadata()
+                .get("ARROW:extension:name")
+                .map(String::as_str),
+            Some("geoarrow.wkb")
+        );
+        let geo: serde_json::Value = serde_json::from_str(&schema.metadata()["geo"]).unwrap();
+        assert_eq!(geo["primary_column"], "geometry");
+        assert_eq!(geo["columns"]["geometry"]["encoding"], "WKB");
+        assert_eq!(
+            geo["columns"]["geometry"]["bbox"],
+            serde_json::json!([0.0, 0.0, 3.0, 1.0])
+        );
+
+        let wkb = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<BinaryArray>()
+            .unwrap();
+        assert_eq!(wkb.value(1), band(5.0, 2.0).to_wkb(WkbFormat::default()));
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let mut writer = GeoParquetWriter::isolines(Vec::new()).unwrap();
+        let isoline = |level: f64, x: f64| Isoline {
+            level,
+            lines: MultiLineString(vec![LineString::from(vec![(x, -1.0), (x + 1.0, 2.0)])]),
+        };
+        writer.write_isolines(&[isoline(1.0, 0.0)]).unwrap();
+        writer
+            .write_isolines(&[isoline(2.0, 10.0), isoline(3.0, -5.0)])
+            .unwrap();
+        assert!(writer.write_isobands(&[band(0.0, 0.0)]).is_err());
+        let bytes = writer.finish().unwrap();
+
+        let builder = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(bytes)).unwrap();
+        let geo = builder
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .find(|kv| kv.key == "geo")
+            .and_then(|kv| kv.value.clone())
+            .unwrap();
+        let geo: serde_json::Value = serde_json::from_str(&geo).unwrap();
+        assert_eq!(geo["version"], GEOPARQUET_VERSION);
+        assert_eq!(
+            geo["columns"]["geometry"]["geometry_types"],
+            serde_json::json!(["MultiLineString"])
+        );
+        assert_eq!(
+            geo["columns"]["geometry"]["bbox"],
+            serde_json::json!([-5.0, -1.0, 11.0, 2.0])
+        );
+
+        let batches: Vec<RecordBatch> = builder
+            .build()
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        let levels = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels.values().as_ref(), &[1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,325 @@
+//! KML and KMZ export with per-band styling
+//!
+//! [`KmlWriter`] writes a KML `Document` with one `Folder` per isoband or
+//! isoline level. Each folder is named after its levels and holds a single
+//! styled `Placemark`: isobands as a `MultiGeometry` of polygons with a
+//! `PolyStyle` fill, isolines as tessellated `LineString`s with a `LineStyle`.
+//! Colors come from a caller-supplied [`ColorMap`], and the levels are also
+//! written as `ExtendedData` (`lower_level`/`upper_level` or `isovalue`).
+//!
+//! With the `kmz` feature, [`write_kmz`] packages a finished document as a
+//! zipped `.kmz`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::color::{Color, ColorRamp};
+//! use geo_marching_squares_rs::output::kml::KmlWriter;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//! let ramp = ColorRamp::evenly_spaced(10.0, 25.0, &[Color::rgb(0, 0, 255), Color::rgb(255, 0, 0)])?;
+//!
+//! let mut kml = KmlWriter::new(Vec::new(), &ramp).with_name("Temperature");
+//! for band in grid.isobands_geo(&[12.0, 18.0, 22.0])? {
+//!     kml.write_isoband(&band)?;
+//! }
+//! let document = kml.finish()?;
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::color::ColorMap;
+use crate::error::Result;
+use crate::types::{Isoband, Isoline};
+use geo_types::LineString;
+use std::io::Write;
+
+/// Default outline and isoline width in pixels
+pub const DEFAULT_LINE_WIDTH: f64 = 1.0;
+
+/// Streaming KML document writer
+pub struct KmlWriter<'a, W: Write> {
+    out: W,
+    colors: &'a dyn ColorMap,
+    name: Option<String>,
+    line_width: f64,
+    header_written: bool,
+}
+
+impl<'a, W: Write> KmlWriter<'a, W> {
+    /// Create a writer coloring bands and lines with `colors`
+    pub fn new(out: W, colors: &'a dyn ColorMap) -> Self {
+        Self {
+            out,
+            colors,
+            name: None,
+            line_width: DEFAULT_LINE_WIDTH,
+            header_written: false,
+        }
+    }
+
+    /// Set the document name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the band outline and isoline width in pixels
+    pub fn with_line_width(mut self, width: f64) -> Self {
+        self.line_width = width;
+        self
+    }
+
+    /// Write one isoband as a styled folder
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn write_isoband(&mut self, band: &Isoband) -> Result<()> {
+        self.ensure_header()?;
+
+        let color = self.colors.band_color(band.lower, band.upper);
+        let name = format!("{} - {}", band.lower, band.upper);
+
+        let mut kml = String::new();
+        open_placemark(&mut kml, &name);
+        kml.push_str(&format!(
+            "<Style><LineStyle><color>{}</color><width>{}</width></LineStyle>\
+             <PolyStyle><color>{}</color></PolyStyle></Style>",
+            color.to_kml(),
+            self.line_width,
+            color.to_kml()
+        ));
+        extended_data(
+            &mut kml,
+            &[("lower_level", band.lower), ("upper_level", band.upper)],
+        );
+
+        kml.push_str("<MultiGeometry>");
+        for polygon in &band.polygons {
+            kml.push_str("<Polygon><outerBoundaryIs>");
+            linear_ring(&mut kml, polygon.exterior());
+            kml.push_str("</outerBoundaryIs>");
+            for hole in polygon.interiors() {
+                kml.push_str("<innerBoundaryIs>");
+                linear_ring(&mut kml, hole);
+                kml.push_str("</innerBoundaryIs>");
+            }
+            kml.push_str("</Polygon>");
+        }
+        kml.push_str("</MultiGeometry></Placemark></Folder>\n");
+
+        self.out.write_all(kml.as_bytes())?;
+        Ok(())
+    }
+
+    /// Write one isoline level as a styled folder
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn write_isoline(&mut self, isoline: &Isoline) -> Result<()> {
+        self.ensure_header()?;
+
+        let color = self.colors.level_color(isoline.level);
+        let name = isoline.level.to_string();
+
+        let mut kml = String::new();
+        open_placemark(&mut kml, &name);
+        kml.push_str(&format!(
+            "<Style><LineStyle><color>{}</color><width>{}</width></LineStyle></Style>",
+            color.to_kml(),
+            self.line_width
+        ));
+        extended_data(&mut kml, &[("isovalue", isoline.level)]);
+
+        kml.push_str("<MultiGeometry>");
+        for line in &isoline.lines {
+            kml.push_str("<LineString><tessellate>1</tessellate>");
+            coordinates(&mut kml, line);
+            kml.push_str("</LineString>");
+        }
+        kml.push_str("</MultiGeometry></Placemark></Folder>\n");
+
+        self.out.write_all(kml.as_bytes())?;
+        Ok(())
+    }
+
+    /// Close the document and return the output
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn finish(mut self) -> Result<W> {
+        self.ensure_header()?;
+        self.out.write_all(b"</Document>\n</kml>\n")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn ensure_header(&mut self) -> Result<()> {
+        if !self.header_written {
+            self.out.write_all(
+                b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                  <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n",
+            )?;
+            if let Some(name) = &self.name {
+                writeln!(self.out, "<name>{}</name>", escape(name))?;
+            }
+            self.header_written = true;
+        }
+        Ok(())
+    }
+}
+
+fn open_placemark(kml: &mut String, name: &str) {
+    let name = escape(name);
+    kml.push_str(&format!(
+        "<Folder><name>{name}</name><Placemark><name>{name}</name>"
+    ));
+}
+
+fn extended_data(kml: &mut String, values: &[(&str, f64)]) {
+    kml.push_str("<ExtendedData>");
+    for (name, value) in values {
+        kml.push_str(&format!(
+            "<Data name=\"{name}\"><value>{value}</value></Data>"
+        ));
+    }
+    kml.push_str("</ExtendedData>");
+}
+
+fn linear_ring(kml: &mut String, ring: &LineString<f64>) {
+    kml.push_str("<LinearRing>");
+    coordinates(kml, ring);
+    kml.push_str("</LinearRing>");
+}
+
+fn coordinates(kml: &mut String, line: &LineString<f64>) {
+    kml.push_str("<coordinates>");
+    for (i, c) in line.coords().enumerate() {
+        if i > 0 {
+            kml.push(' ');
+        }
+        kml.push_str(&format!("{},{}", c.x, c.y));
+    }
+    kml.push_str("</coordinates>");
+}
+
+/// Escape XML special characters in text content
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Package a KML document as a KMZ archive containing `doc.kml`
+///
+/// Requires the `kmz` feature.
+///
+/// # Errors
+///
+/// Returns an error on I/O or archive failure
+#[cfg(feature = "kmz")]
+pub fn write_kmz<W: Write + std::io::Seek>(out: W, kml: &[u8]) -> Result<W> {
+    use zip::write::SimpleFileOptions;
+    use zip::CompressionMethod;
+
+    let mut zip = zip::ZipWriter::new(out);
+    zip.start_file(
+        "doc.kml",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+    )
+    .map_err(std::io::Error::other)?;
+    zip.write_all(kml)?;
+    Ok(zip.finish().map_err(std::io::Error::other)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use geo_types::{MultiLineString, MultiPolygon, Polygon};
+
+    fn band() -> Isoband {
+        let exterior = LineString::from(vec![(0.0, 0.0), (4.0, 0.0), (0.0, 4.0), (0.0, 0.0)]);
+        let hole = LineString::from(vec![(1.0, 1.0), (1.0, 2.0), (2.0, 1.0), (1.0, 1.0)]);
+        Isoband {
+            lower: 10.0,
+            upper: 20.0,
+            polygons: MultiPolygon(vec![Polygon::new(exterior, vec![hole])]),
+        }
+    }
+
+    #[test]
+    fn test_band_folder() {
+        let colors = |_: f64, _: f64| Color::rgba(0x11, 0x22, 0x33, 0x80);
+        let mut writer = KmlWriter::new(Vec::new(), &colors).with_name("Temp <2m>");
+        writer.write_isoband(&band()).unwrap();
+        let kml = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        assert!(kml.starts_with("<?xml"));
+        assert!(kml.contains("<name>Temp &lt;2m&gt;</name>"));
+        assert!(kml.contains("<Folder><name>10 - 20</name>"));
+        assert!(kml.contains("<PolyStyle><color>80332211</color></PolyStyle>"));
+        assert!(kml.contains("<Data name=\"upper_level\"><value>20</value></Data>"));
+        assert!(kml.contains(
+            "<outerBoundaryIs><LinearRing><coordinates>0,0 4,0 0,4 0,0</coordinates></LinearRing></outerBoundaryIs>"
+        ));
+        assert_eq!(kml.matches("<innerBoundaryIs>").count(), 1);
+        assert!(kml.trim_end().ends_with("</Document>\n</kml>"));
+    }
+
+    #[test]
+    fn test_isoline_folder() {
+        let colors = |_: f64, _: f64| Color::rgb(255, 0, 0);
+        let mut writer = KmlWriter::new(Vec::new(), &colors).with_line_width(2.5);
+        writer
+            .write_isoline(&Isoline {
+                level: 1013.25,
+                lines: MultiLineString(vec![LineString::from(vec![
+                    (-100.5, 40.0),
+                    (-100.0, 40.5),
+                ])]),
+            })
+            .unwrap();
+        let kml = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+        assert!(kml.contains("<Folder><name>1013.25</name>"));
+        assert!(kml.contains("<LineStyle><color>ff0000ff</color><width>2.5</width></LineStyle>"));
+        assert!(kml.contains("<LineString><tessellate>1</tessellate><coordinates>-100.5,40 -100,40.5</coordinates></LineString>"));
+    }
+
+    #[cfg(feature = "kmz")]
+    #[test]
+    fn test_kmz_archive() {
+        use std::io::{Cursor, Read};
+
+        let colors = |_: f64, _: f64| Color::rgb(0, 128, 0);
+        let mut writer = KmlWriter::new(Vec::new(), &colors);
+        writer.write_isoband(&band()).unwrap();
+        let kml = writer.finish().unwrap();
+
+        let kmz = write_kmz(Cursor::new(Vec::new()), &kml).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(kmz.into_inner())).unwrap();
+        let mut doc = String::new();
+        archive
+            .by_name("doc.kml")
+            .unwrap()
+            .read_to_string(&mut doc)
+            .unwrap();
+        assert_eq!(doc.as_bytes(), kml.as_slice());
+    }
+}
@@ -0,0 +1,134 @@
+//! Contour output encoders
+//!
+//! The contour entry points return GeoJSON features or typed `geo-types`
+//! results ([`Isoband`](crate::Isoband) and [`Isoline`](crate::Isoline)). The
+//! encoders in this module take the typed results and write them in formats
+//! that would otherwise need a GeoJSON detour through external tools.
+//! [`geojson::GeoJsonWriter`] traces directly from a grid instead, streaming
+//! one band at a time.
+
+#[cfg(feature = "flatgeobuf")]
+pub mod flatgeobuf;
+pub mod geojson;
+#[cfg(feature = "geoparquet")]
+pub mod geoparquet;
+pub mod kml;
+pub mod mvt;
+pub mod raster;
+pub mod shapefile;
+pub mod svg;
+pub mod wkb;
+
+/// Latitude limit of the Web Mercator projection, in degrees
+pub(crate) const MAX_MERCATOR_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// Project lon/lat to normalized Web Mercator coordinates
+///
+/// Both axes run from 0 to 1 over the world square, with x increasing east and
+/// y increasing south (tile/screen order). Latitudes beyond
+/// [`MAX_MERCATOR_LATITUDE`] are clamped.
+pub(crate) fn web_mercator_unit(lon: f64, lat: f64) -> (f64, f64) {
+    let lat = lat
+        .clamp(-MAX_MERCATOR_LATITUDE, MAX_MERCATOR_LATITUDE)
+        .to_radians();
+    let x = (lon + 180.0) / 360.0;
+    let y = 0.5 - (lat.tan() + 1.0 / lat.cos()).ln() / (2.0 * std::f64::consts::PI);
+    (x, y)
+}
+
+/// Map projection used by the raster and vector renderers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Projection {
+    /// Plate carrée: longitude and latitude scaled linearly
+    #[default]
+    Equirectangular,
+    /// Spherical Web Mercator (EPSG:3857), latitudes clamped to ±85.05°
+    WebMercator,
+}
+
+impl Projection {
+    /// Project lon/lat to planar coordinates with y increasing downward
+    pub(crate) fn project(self, lon: f64, lat: f64) -> (f64, f64) {
+        match self {
+            Projection::Equirectangular => (lon, -lat),
+            Projection::WebMercator => web_mercator_unit(lon, lat),
+        }
+    }
+}
+
+/// Mapping from a lon/lat bounding box to a pixel canvas
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Viewport {
+    projection: Projection,
+    origin: (f64, f64),
+    scale: (f64, f64),
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+impl Viewport {
+    /// Fit `bbox` (min_lon, min_lat, max_lon, max_lat) to `width` pixels,
+    /// deriving the height from the projected aspect ratio
+    pub(crate) fn new(
+        bbox: (f64, f64, f64, f64),
+        width: u32,
+        projection: Projection,
+    ) -> crate::Result<Self> {
+        let (x0, y0, x1, y1) = project_bbox(bbox, projection)?;
+        let height = ((y1 - y0) * f64::from(width) / (x1 - x0)).round().max(1.0) as u32;
+        Self::with_size(bbox, width, height, projection)
+    }
+
+    /// Stretch `bbox` over exactly `width` x `height` pixels, as a WMS
+    /// `GetMap` request does
+    pub(crate) fn with_size(
+        bbox: (f64, f64, f64, f64),
+        width: u32,
+        height: u32,
+        projection: Projection,
+    ) -> crate::Result<Self> {
+        let (x0, y0, x1, y1) = project_bbox(bbox, projection)?;
+        if width == 0 || height == 0 {
+            return Err(crate::Error::invalid_dimensions(
+                "Canvas width and height must be positive",
+            ));
+        }
+        Ok(Self {
+            projection,
+            origin: (x0, y0),
+            scale: (f64::from(width) / (x1 - x0), f64::from(height) / (y1 - y0)),
+            width,
+            height,
+        })
+    }
+
+    /// Pixel position of a lon/lat coordinate
+    pub(crate) fn pixel(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (x, y) = self.projection.project(lon, lat);
+        (
+            (x - self.origin.0) * self.scale.0,
+            (y - self.origin.1) * self.scale.1,
+        )
+    }
+}
+
+/// Validate a lon/lat box and return its projected top-left and bottom-right
+fn project_bbox(
+    bbox: (f64, f64, f64, f64),
+    projection: Projection,
+) -> crate::Result<(f64, f64, f64, f64)> {
+    let (min_lon, min_lat, max_lon, max_lat) = bbox;
+    if ![min_lon, min_lat, max_lon, max_lat]
+        .iter()
+        .all(|v| v.is_finite())
+        || min_lon >= max_lon
+        || min_lat >= max_lat
+    {
+        return Err(crate::Error::geometry_error(format!(
+            "Invalid bounding box {bbox:?}"
+        )));
+    }
+    let (x0, y0) = projection.project(min_lon, max_lat);
+    let (x1, y1) = projection.project(max_lon, min_lat);
+    Ok((x0, y0, x1, y1))
+}
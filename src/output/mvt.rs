@@ -0,0 +1,724 @@
+//! Mapbox Vector Tile encoding of isobands and isolines
+//!
+//! [`MvtTile`] projects contour geometry for one z/x/y tile into Web Mercator,
+//! clips it to the tile extent plus a buffer, quantizes it to the tile grid and
+//! writes the MVT 2.1 protobuf directly, with no intermediate GeoJSON.
+//!
+//! Each call to [`MvtTile::add_isobands`] or [`MvtTile::add_isolines`] adds one
+//! feature per band or level to the named layer. Band levels are written as
+//! `lower_level`/`upper_level` attributes, and isoline levels as `isovalue`,
+//! matching the GeoJSON feature properties.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::output::mvt::{MvtTile, TileId};
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//!
+//! let mut tile = MvtTile::new(TileId::new(4, 3, 6)?);
+//! tile.add_isobands("temperature", &grid.isobands_geo(&[12.0, 18.0, 22.0])?);
+//! tile.add_isolines("temperature_lines", &grid.isolines_geo(&[15.0, 20.0])?);
+//! let bytes = tile.encode();
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use super::web_mercator_unit;
+use crate::clip::{clip_polyline, clip_ring, Rect};
+use crate::error::{Error, Result};
+use crate::types::{Isoband, Isoline};
+use geo_types::LineString;
+use std::collections::HashMap;
+
+/// Default number of tile units along each side of a tile
+pub const DEFAULT_EXTENT: u32 = 4096;
+
+/// Default clipping buffer around the tile, in tile units
+pub const DEFAULT_BUFFER: u32 = 64;
+
+/// Highest zoom level accepted by [`TileId::new`]
+pub const MAX_ZOOM: u8 = 30;
+
+/// MVT geometry types
+const GEOM_LINESTRING: u32 = 2;
+const GEOM_POLYGON: u32 = 3;
+
+/// MVT geometry commands
+const CMD_MOVE_TO: u32 = 1;
+const CMD_LINE_TO: u32 = 2;
+const CMD_CLOSE_PATH: u32 = 7;
+
+/// Address of a tile in the XYZ (slippy map) scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileId {
+    /// Zoom level
+    pub z: u8,
+    /// Column, counted from the antimeridian eastward
+    pub x: u32,
+    /// Row, counted from the north edge southward
+    pub y: u32,
+}
+
+impl TileId {
+    /// Create a tile address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `z` exceeds [`MAX_ZOOM`] or `x`/`y` are not below `2^z`
+    pub fn new(z: u8, x: u32, y: u32) -> Result<Self> {
+        if z > MAX_ZOOM || u64::from(x) >= 1 << z || u64::from(y) >= 1 << z {
+            return Err(Error::invalid_tile(z, x, y));
+        }
+        Ok(Self { z, x, y })
+    }
+}
+
+/// A vector tile under construction
+#[derive(Debug, Clone)]
+pub struct MvtTile {
+    tile: TileId,
+    extent: u32,
+    buffer: u32,
+    layers: Vec<Layer>,
+}
+
+impl MvtTile {
+    /// Create an empty tile with the default extent and buffer
+    pub fn new(tile: TileId) -> Self {
+        Self {
+            tile,
+            extent: DEFAULT_EXTENT,
+            buffer: DEFAULT_BUFFER,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Set the tile extent (tile units per side)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if features have already been added
+    pub fn with_extent(mut self, extent: u32) -> Result<Self> {
+        self.ensure_no_features("extent")?;
+        self.extent = extent.max(1);
+        Ok(self)
+    }
+
+    /// Set the clipping buffer around the tile, in tile units
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if features have already been added
+    pub fn with_buffer(mut self, buffer: u32) -> Result<Self> {
+        self.ensure_no_features("buffer")?;
+        self.buffer = buffer;
+        Ok(self)
+    }
+
+    /// Get the tile address
+    pub fn tile(&self) -> TileId {
+        self.tile
+    }
+
+    /// Whether no features have been added
+    pub fn is_empty(&self) -> bool {
+        self.layers.iter().all(|layer| layer.features.is_empty())
+    }
+
+    /// Features are quantized when added, so the tile grid cannot change later
+    fn ensure_no_features(&self, setting: &str) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::geometry_error(format!(
+                "Tile {setting} must be set before features are added"
+            )))
+        }
+    }
+
+    /// Add one polygon feature per isoband to the named layer
+    ///
+    /// Bands with nothing inside the buffered tile are skipped. Returns the
+    /// number of features added.
+    pub fn add_isobands(&mut self, layer: &str, bands: &[Isoband]) -> usize {
+        let projector = self.projector();
+        let mut encoded = Vec::new();
+
+        for band in bands {
+            let mut geometry = GeometryEncoder::default();
+            for polygon in &band.polygons {
+                let Some(exterior) = projector.ring(polygon.exterior(), true) else {
+                    continue;
+                };
+                geometry.ring(&exterior);
+                for hole in polygon.interiors() {
+                    if let Some(hole) = projector.ring(hole, false) {
+                        geometry.ring(&hole);
+                    }
+                }
+            }
+
+            if !geometry.is_empty() {
+                encoded.push((
+                    [("lower_level", band.lower), ("upper_level", band.upper)].to_vec(),
+                    GEOM_POLYGON,
+                    geometry.finish(),
+                ));
+            }
+        }
+
+        self.push_features(layer, encoded)
+    }
+
+    /// Add one line feature per isoline level to the named layer
+    ///
+    /// Levels with nothing inside the buffered tile are skipped. Returns the
+    /// number of features added.
+    pub fn add_isolines(&mut self, layer: &str, lines: &[Isoline]) -> usize {
+        let projector = self.projector();
+        let mut encoded = Vec::new();
+
+        for isoline in lines {
+            let mut geometry = GeometryEncoder::default();
+            for line in &isoline.lines {
+                for piece in projector.line(line) {
+                    geometry.line(&piece);
+                }
+            }
+
+            if !geometry.is_empty() {
+                encoded.push((
+                    [("isovalue", isoline.level)].to_vec(),
+                    GEOM_LINESTRING,
+                    geometry.finish(),
+                ));
+            }
+        }
+
+        self.push_features(layer, encoded)
+    }
+
+    /// Encode the tile as MVT protobuf bytes
+    ///
+    /// Layers appear in the order they were first added; empty layers are
+    /// omitted.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut tile = Vec::new();
+        for layer in self
+            .layers
+            .iter()
+            .filter(|layer| !layer.features.is_empty())
+        {
+            write_message(&mut tile, 3, &layer.encode(self.extent));
+        }
+        tile
+    }
+
+    fn projector(&self) -> Projector {
+        let extent = f64::from(self.extent);
+        let buffer = f64::from(self.buffer);
+        Projector {
+            scale: (1u64 << self.tile.z) as f64,
+            x: f64::from(self.tile.x),
+            y: f64::from(self.tile.y),
+            extent,
+            clip: Rect::new(-buffer, -buffer, extent + buffer, extent + buffer),
+        }
+    }
+
+    fn push_features(&mut self, name: &str, encoded: Vec<PendingFeature>) -> usize {
+        let index = match self.layers.iter().position(|layer| layer.name == name) {
+            Some(index) => index,
+            None => {
+                self.layers.push(Layer::new(name));
+                self.layers.len() - 1
+            }
+        };
+        let layer = &mut self.layers[index];
+
+        let count = encoded.len();
+        for (attributes, geom_type, geometry) in encoded {
+            let tags = attributes
+                .into_iter()
+                .flat_map(|(key, value)| [layer.key(key), layer.value(value)])
+                .collect();
+            layer.features.push(Feature {
+                tags,
+                geom_type,
+                geometry,
+            });
+        }
+        count
+    }
+}
+
+/// Attributes, geometry type and geometry commands of a feature to be added
+type PendingFeature = (Vec<(&'static str, f64)>, u32, Vec<u32>);
+
+/// A named layer with its key and value tables
+#[derive(Debug, Clone)]
+struct Layer {
+    name: String,
+    features: Vec<Feature>,
+    keys: Vec<String>,
+    values: Vec<f64>,
+    value_index: HashMap<u64, u32>,
+}
+
+#[derive(Debug, Clone)]
+struct Feature {
+    tags: Vec<u32>,
+    geom_type: u32,
+    geometry: Vec<u32>,
+}
+
+impl Layer {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            features: Vec::new(),
+            keys: Vec::new(),
+            values: Vec::new(),
+            value_index: HashMap::new(),
+        }
+    }
+
+    fn key(&mut self, key: &str) -> u32 {
+        match self.keys.iter().position(|k| k == key) {
+            Some(index) => index as u32,
+            None => {
+                self.keys.push(key.to_string());
+                (self.keys.len() - 1) as u32
+            }
+        }
+    }
+
+    fn value(&mut self, value: f64) -> u32 {
+        let values = &mut self.values;
+        *self.value_index.entry(value.to_bits()).or_insert_with(|| {
+            values.push(value);
+            (values.len() - 1) as u32
+        })
+    }
+
+    fn encode(&self, extent: u32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_message(&mut buf, 1, self.name.as_bytes());
+        for feature in &self.features {
+            let mut msg = Vec::new();
+            write_packed(&mut msg, 2, &feature.tags);
+            write_varint_field(&mut msg, 3, u64::from(feature.geom_type));
+            write_packed(&mut msg, 4, &feature.geometry);
+            write_message(&mut buf, 2, &msg);
+        }
+        for key in &self.keys {
+            write_message(&mut buf, 3, key.as_bytes());
+        }
+        for value in &self.values {
+            // Value message with double_value (field 3, fixed64)
+            let mut msg = Vec::with_capacity(9);
+            write_key(&mut msg, 3, 1);
+            msg.extend_from_slice(&value.to_le_bytes());
+            write_message(&mut buf, 4, &msg);
+        }
+        write_varint_field(&mut buf, 5, u64::from(extent));
+        write_varint_field(&mut buf, 15, 2);
+        buf
+    }
+}
+
+/// Projects lon/lat geometry into clipped, quantized tile coordinates
+struct Projector {
+    scale: f64,
+    x: f64,
+    y: f64,
+    extent: f64,
+    clip: Rect,
+}
+
+impl Projector {
+    fn project(&self, line: &LineString<f64>) -> Vec<(f64, f64)> {
+        line.coords()
+            .map(|c| {
+                let (u, v) = web_mercator_unit(c.x, c.y);
+                (
+                    (u * self.scale - self.x) * self.extent,
+                    (v * self.scale - self.y) * self.extent,
+                )
+            })
+            .collect()
+    }
+
+    /// Clip and quantize a ring, orienting it as an exterior or interior ring
+    ///
+    /// MVT requires exterior rings to have positive area under the surveyor's
+    /// formula in tile coordinates (clockwise on screen, since y points down)
+    /// and interior rings negative area. Rings that collapse are dropped.
+    fn ring(&self, ring: &LineString<f64>, exterior: bool) -> Option<Vec<(i32, i32)>> {
+        let projected = self.project(ring);
+        if !self.clip.intersects(&projected) {
+            return None;
+        }
+
+        let mut ring = quantize(&clip_ring(&projected, &self.clip));
+        while ring.len() > 1 && ring.first() == ring.last() {
+            ring.pop();
+        }
+        if ring.len() < 3 {
+            return None;
+        }
+
+        let area = signed_area(&ring);
+        if area == 0 {
+            return None;
+        }
+        if (area > 0) != exterior {
+            ring.reverse();
+        }
+        Some(ring)
+    }
+
+    /// Clip and quantize a line into the pieces inside the buffered tile
+    fn line(&self, line: &LineString<f64>) -> Vec<Vec<(i32, i32)>> {
+        let projected = self.project(line);
+        if !self.clip.intersects(&projected) {
+            return Vec::new();
+        }
+
+        clip_polyline(&projected, &self.clip)
+            .iter()
+            .map(|piece| quantize(piece))
+            .filter(|piece| piece.len() >= 2)
+            .collect()
+    }
+}
+
+/// Round to the tile grid, dropping consecutive duplicates
+fn quantize(points: &[(f64, f64)]) -> Vec<(i32, i32)> {
+    let mut out: Vec<(i32, i32)> = Vec::with_capacity(points.len());
+    for &(x, y) in points {
+        let p = (x.round() as i32, y.round() as i32);
+        if out.last() != Some(&p) {
+            out.push(p);
+        }
+    }
+    out
+}
+
+/// Twice the signed area of an open ring (surveyor's formula)
+fn signed_area(ring: &[(i32, i32)]) -> i64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (x0, y0) = ring[i];
+            let (x1, y1) = ring[(i + 1) % n];
+            i64::from(x0) * i64::from(y1) - i64::from(x1) * i64::from(y0)
+        })
+        .sum()
+}
+
+/// Writes MVT geometry commands with a cursor shared across parts
+#[derive(Default)]
+struct GeometryEncoder {
+    commands: Vec<u32>,
+    cursor: (i32, i32),
+}
+
+impl GeometryEncoder {
+    fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    fn finish(self) -> Vec<u32> {
+        self.commands
+    }
+
+    fn ring(&mut self, ring: &[(i32, i32)]) {
+        self.path(ring);
+        self.commands.push(command(CMD_CLOSE_PATH, 1));
+    }
+
+    fn line(&mut self, line: &[(i32, i32)]) {
+        self.path(line);
+    }
+
+    fn path(&mut self, points: &[(i32, i32)]) {
+        self.commands.push(command(CMD_MOVE_TO, 1));
+        self.point(points[0]);
+        self.commands
+            .push(command(CMD_LINE_TO, (points.len() - 1) as u32));
+        for &p in &points[1..] {
+            self.point(p);
+        }
+    }
+
+    fn point(&mut self, (x, y): (i32, i32)) {
+        self.commands.push(zigzag(x - self.cursor.0));
+        self.commands.push(zigzag(y - self.cursor.1));
+        self.cursor = (x, y);
+    }
+}
+
+fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+fn zigzag(n: i32) -> u32 {
+    ((n << 1) ^ (n >> 31)) as u32
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_key(buf: &mut Vec<u8>, field: u32, wire_type: u32) {
+    write_varint(buf, u64::from((field << 3) | wire_type));
+}
+
+fn write_varint_field(buf: &mut Vec<u8>, field: u32, value: u64) {
+    write_key(buf, field, 0);
+    write_varint(buf, value);
+}
+
+fn write_message(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_key(buf, field, 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn write_packed(buf: &mut Vec<u8>, field: u32, values: &[u32]) {
+    let mut packed = Vec::with_capacity(values.len() * 2);
+    for &value in values {
+        write_varint(&mut packed, u64::from(value));
+    }
+    write_message(buf, field, &packed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{LineString, MultiLineString, MultiPolygon, Polygon};
+
+    /// Minimal protobuf reader: (field, wire type, varint value or bytes)
+    fn fields(mut buf: &[u8]) -> Vec<(u32, u64, Vec<u8>)> {
+        fn varint(buf: &mut &[u8]) -> u64 {
+            let mut value = 0;
+            let mut shift = 0;
+            loop {
+                let byte = buf[0];
+                *buf = &buf[1..];
+                value |= u64::from(byte & 0x7f) << shift;
+                if byte < 0x80 {
+                    return value;
+                }
+                shift += 7;
+            }
+        }
+
+        let mut out = Vec::new();
+        while !buf.is_empty() {
+            let key = varint(&mut buf);
+            let (field, wire) = ((key >> 3) as u32, key & 7);
+            match wire {
+                0 => out.push((field, varint(&mut buf), Vec::new())),
+                1 => {
+                    out.push((field, 0, buf[..8].to_vec()));
+                    buf = &buf[8..];
+                }
+                2 => {
+                    let len = varint(&mut buf) as usize;
+                    out.push((field, 0, buf[..len].to_vec()));
+                    buf = &buf[len..];
+                }
+                _ => panic!("unexpected wire type {wire}"),
+            }
+        }
+        out
+    }
+
+    fn unpack(mut buf: &[u8]) -> Vec<u32> {
+        let mut out = Vec::new();
+        while !buf.is_empty() {
+            let mut value = 0u64;
+            let mut shift = 0;
+            loop {
+                let byte = buf[0];
+                buf = &buf[1..];
+                value |= u64::from(byte & 0x7f) << shift;
+                shift += 7;
+                if byte < 0x80 {
+                    break;
+                }
+            }
+            out.push(value as u32);
+        }
+        out
+    }
+
+    fn square(min: f64, max: f64) -> LineString<f64> {
+        LineString::from(vec![
+            (min, min),
+            (max, min),
+            (max, max),
+            (min, max),
+            (min, min),
+        ])
+    }
+
+    #[test]
+    fn test_tile_id_bounds() {
+        assert!(TileId::new(0, 0, 0).is_ok());
+        assert!(TileId::new(3, 7, 7).is_ok());
+        assert!(TileId::new(3, 8, 0).is_err());
+        assert!(TileId::new(MAX_ZOOM + 1, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_polygon_winding_and_attributes() {
+        // Counter-clockwise exterior in lon/lat with a clockwise hole
+        let mut hole = square(-10.0, 10.0);
+        hole.0.reverse();
+        let band = Isoband {
+            lower: 10.0,
+            upper: 20.0,
+            polygons: MultiPolygon(vec![Polygon::new(square(-40.0, 40.0), vec![hole])]),
+        };
+
+        let mut tile = MvtTile::new(TileId::new(0, 0, 0).unwrap());
+        assert_eq!(tile.add_isobands("bands", &[band]), 1);
+        let bytes = tile.encode();
+
+        let tile_fields = fields(&bytes);
+        assert_eq!(tile_fields.len(), 1);
+        let layer = fields(&tile_fields[0].2);
+
+        let name = layer.iter().find(|f| f.0 == 1).unwrap();
+        assert_eq!(name.2, b"bands");
+        let keys: Vec<_> = layer
+            .iter()
+            .filter(|f| f.0 == 3)
+            .map(|f| f.2.clone())
+            .collect();
+        assert_eq!(keys, vec![b"lower_level".to_vec(), b"upper_level".to_vec()]);
+        let version = layer.iter().find(|f| f.0 == 15).unwrap();
+        assert_eq!(version.1, 2);
+
+        let feature = fields(&layer.iter().find(|f| f.0 == 2).unwrap().2);
+        assert_eq!(
+            feature.iter().find(|f| f.0 == 3).unwrap().1,
+            u64::from(GEOM_POLYGON)
+        );
+        assert_eq!(
+            unpack(&feature.iter().find(|f| f.0 == 2).unwrap().2),
+            vec![0, 0, 1, 1]
+        );
+
+        // Decode the two rings and check their orientation
+        let geometry = unpack(&feature.iter().find(|f| f.0 == 4).unwrap().2);
+        let mut rings: Vec<Vec<(i32, i32)>> = Vec::new();
+        let mut cursor = (0i32, 0i32);
+        let mut i = 0;
+        while i < geometry.len() {
+            let (id, count) = (geometry[i] & 7, geometry[i] >> 3);
+            i += 1;
+            if id == CMD_MOVE_TO {
+                rings.push(Vec::new());
+            }
+            if id == CMD_CLOSE_PATH {
+                continue;
+            }
+            for _ in 0..count {
+                let dx = ((geometry[i] >> 1) as i32) ^ -((geometry[i] & 1) as i32);
+                let dy = ((geometry[i + 1] >> 1) as i32) ^ -((geometry[i + 1] & 1) as i32);
+                cursor = (cursor.0 + dx, cursor.1 + dy);
+                rings.last_mut().unwrap().push(cursor);
+                i += 2;
+            }
+        }
+
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0].len(), 4);
+        assert!(signed_area(&rings[0]) > 0);
+        assert!(signed_area(&rings[1]) < 0);
+        let xs: Vec<i32> = rings[0].iter().map(|p| p.0).collect();
+        assert_eq!(xs.iter().min(), Some(&1593));
+        assert_eq!(xs.iter().max(), Some(&2503));
+    }
+
+    #[test]
+    fn test_clipping_to_buffer() {
+        // World-spanning band in a zoom 2 tile is clipped to the buffered extent
+        let band = Isoband {
+            lower: 0.0,
+            upper: 1.0,
+            polygons: MultiPolygon(vec![Polygon::new(square(-170.0, 80.0), vec![])]),
+        };
+        let mut tile = MvtTile::new(TileId::new(2, 1, 1).unwrap())
+            .with_buffer(16)
+            .unwrap();
+        tile.add_isobands("bands", std::slice::from_ref(&band));
+        let projector = tile.projector();
+        let ring = projector
+            .ring(&band.polygons.0[0].exterior().clone(), true)
+            .unwrap();
+        assert!(ring
+            .iter()
+            .all(|&(x, y)| (-16..=4112).contains(&x) && (-16..=4112).contains(&y)));
+
+        // Features are already quantized, so the tile grid is fixed
+        assert!(tile.clone().with_buffer(0).is_err());
+        assert!(tile.with_extent(512).is_err());
+
+        // A band far away from the tile produces no feature
+        let far = Isoband {
+            lower: 0.0,
+            upper: 1.0,
+            polygons: MultiPolygon(vec![Polygon::new(square(150.0, 160.0), vec![])]),
+        };
+        let mut tile = MvtTile::new(TileId::new(4, 0, 0).unwrap());
+        assert_eq!(tile.add_isobands("bands", &[far]), 0);
+        assert!(tile.is_empty());
+        assert!(tile.encode().is_empty());
+    }
+
+    #[test]
+    fn test_isolines_share_layer_values() {
+        let line = |lat: f64| LineString::from(vec![(-50.0, lat), (50.0, lat)]);
+        let lines = vec![
+            Isoline {
+                level: 5.0,
+                lines: MultiLineString(vec![line(10.0)]),
+            },
+            Isoline {
+                level: 10.0,
+                lines: MultiLineString(vec![line(20.0)]),
+            },
+        ];
+
+        let mut tile = MvtTile::new(TileId::new(0, 0, 0).unwrap());
+        assert_eq!(tile.add_isolines("lines", &lines), 2);
+        assert_eq!(tile.add_isolines("lines", &lines[..1]), 1);
+
+        let layer = fields(&fields(&tile.encode())[0].2);
+        assert_eq!(layer.iter().filter(|f| f.0 == 2).count(), 3);
+        assert_eq!(layer.iter().filter(|f| f.0 == 3).count(), 1);
+        assert_eq!(layer.iter().filter(|f| f.0 == 4).count(), 2);
+
+        let feature = fields(&layer.iter().find(|f| f.0 == 2).unwrap().2);
+        assert_eq!(
+            feature.iter().find(|f| f.0 == 3).unwrap().1,
+            u64::from(GEOM_LINESTRING)
+        );
+        let geometry = unpack(&feature.iter().find(|f| f.0 == 4).unwrap().2);
+        assert_eq!(geometry[0], command(CMD_MOVE_TO, 1));
+        assert_eq!(geometry[3], command(CMD_LINE_TO, 1));
+    }
+}
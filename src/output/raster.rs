@@ -0,0 +1,414 @@
+//! Anti-aliased raster rendering of isobands to RGBA images and PNG
+//!
+//! [`RasterRenderer`] fills isobands into an RGBA image covering a lon/lat
+//! bounding box, stretched to the requested pixel size the way a WMS `GetMap`
+//! request is. Each band is scan-converted with the even-odd rule over several
+//! sub-scanlines per row, with exact horizontal coverage at span ends, and the
+//! resulting coverage is blended over the image in band order using the
+//! color from a [`ColorMap`] such as a [`ColorRamp`](crate::color::ColorRamp).
+//!
+//! With the `png` feature, [`RgbaImage::write_png`] encodes the result, so a
+//! thumbnail service needs no GDAL or Mapnik.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::color::{Color, ColorRamp};
+//! use geo_marching_squares_rs::output::raster::RasterRenderer;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//! let ramp = ColorRamp::evenly_spaced(10.0, 25.0, &[Color::rgb(0, 0, 255), Color::rgb(255, 0, 0)])?;
+//!
+//! let renderer = RasterRenderer::new(grid.bounds(), 256, 256, &ramp)?;
+//! let image = renderer.render(&grid.isobands_geo(&[12.0, 18.0, 22.0])?);
+//! assert_eq!((image.width(), image.height()), (256, 256));
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use super::{Projection, Viewport};
+use crate::color::{Color, ColorMap};
+use crate::error::Result;
+use crate::types::Isoband;
+
+/// Default number of sub-scanlines sampled per pixel row
+pub const DEFAULT_SAMPLES: u8 = 4;
+
+/// An 8-bit RGBA image with straight (non-premultiplied) alpha
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Create an image filled with one color
+    pub fn new(width: u32, height: u32, fill: Color) -> Self {
+        let pixels = [fill.r, fill.g, fill.b, fill.a].repeat(width as usize * height as usize);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Image width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Image height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Row-major RGBA bytes, top row first
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Consume the image and return its RGBA bytes
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Color of the pixel at column `x` and row `y`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside the image
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        assert!(x < self.width && y < self.height, "pixel out of bounds");
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let p = &self.pixels[i..i + 4];
+        Color::rgba(p[0], p[1], p[2], p[3])
+    }
+
+    /// Encode the image as an RGBA PNG
+    ///
+    /// Requires the `png` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O or encoding failure
+    #[cfg(feature = "png")]
+    pub fn write_png<W: std::io::Write>(&self, out: W) -> Result<()> {
+        let mut encoder = ::png::Encoder::new(out, self.width, self.height);
+        encoder.set_color(::png::ColorType::Rgba);
+        encoder.set_depth(::png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(std::io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(std::io::Error::other)?;
+        writer.finish().map_err(std::io::Error::other)?;
+        Ok(())
+    }
+
+    /// Encode the image as an in-memory RGBA PNG
+    ///
+    /// Requires the `png` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on encoding failure
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.write_png(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Blend `color` over the pixel at index `i` with the given coverage
+    fn blend(&mut self, i: usize, color: Color, coverage: f32) {
+        let src_a = f32::from(color.a) / 255.0 * coverage.min(1.0);
+        if src_a <= 0.0 {
+            return;
+        }
+        let p = &mut self.pixels[i * 4..i * 4 + 4];
+        let dst_a = f32::from(p[3]) / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        let mix = |src: u8, dst: u8| {
+            let c = (f32::from(src) * src_a + f32::from(dst) * dst_a * (1.0 - src_a)) / out_a;
+            c.round().clamp(0.0, 255.0) as u8
+        };
+        p[0] = mix(color.r, p[0]);
+        p[1] = mix(color.g, p[1]);
+        p[2] = mix(color.b, p[2]);
+        p[3] = (out_a * 255.0).round() as u8;
+    }
+}
+
+/// Rasterizes filled isobands into an [`RgbaImage`]
+pub struct RasterRenderer<'a> {
+    bbox: (f64, f64, f64, f64),
+    viewport: Viewport,
+    colors: &'a dyn ColorMap,
+    background: Color,
+    samples: u8,
+}
+
+impl<'a> RasterRenderer<'a> {
+    /// Create a renderer drawing `bbox` (min_lon, min_lat, max_lon, max_lat)
+    /// into a `width` x `height` image
+    ///
+    /// The projection defaults to [`Projection::Equirectangular`] and the
+    /// background to transparent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the box is empty or not finite, or a dimension is
+    /// zero
+    pub fn new(
+        bbox: (f64, f64, f64, f64),
+        width: u32,
+        height: u32,
+        colors: &'a dyn ColorMap,
+    ) -> Result<Self> {
+        Ok(Self {
+            bbox,
+            viewport: Viewport::with_size(bbox, width, height, Projection::default())?,
+            colors,
+            background: Color::rgba(0, 0, 0, 0),
+            samples: DEFAULT_SAMPLES,
+        })
+    }
+
+    /// Set the map projection
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the box cannot be projected
+    pub fn with_projection(mut self, projection: Projection) -> Result<Self> {
+        self.viewport = Viewport::with_size(
+            self.bbox,
+            self.viewport.width,
+            self.viewport.height,
+            projection,
+        )?;
+        Ok(self)
+    }
+
+    /// Set the background color
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set the number of sub-scanlines per pixel row (1 disables vertical
+    /// anti-aliasing)
+    pub fn with_samples(mut self, samples: u8) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    /// Render the bands, in order, over the background
+    pub fn render(&self, isobands: &[Isoband]) -> RgbaImage {
+        let (width, height) = (self.viewport.width, self.viewport.height);
+        let mut image = RgbaImage::new(width, height, self.background);
+        let mut coverage = vec![0.0f32; width as usize * height as usize];
+
+        for band in isobands {
+            let edges = self.edges(band);
+            if edges.is_empty() {
+                continue;
+            }
+            coverage.iter_mut().for_each(|c| *c = 0.0);
+            fill_even_odd(&edges, width, height, self.samples, &mut coverage);
+
+            let color = self.colors.band_color(band.lower, band.upper);
+            for (i, &c) in coverage.iter().enumerate() {
+                image.blend(i, color, c);
+            }
+        }
+        image
+    }
+
+    /// Pixel-space edges of every ring in the band
+    fn edges(&self, band: &Isoband) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        for polygon in &band.polygons {
+            for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+                let points: Vec<(f64, f64)> = ring
+                    .coords()
+                    .map(|c| self.viewport.pixel(c.x, c.y))
+                    .collect();
+                // Rings are closed by the wrap-around edge whether or not the
+                // last point repeats the first
+                for (i, &a) in points.iter().enumerate() {
+                    let b = points[(i + 1) % points.len()];
+                    if a.1 != b.1 {
+                        edges.push(Edge::new(a, b));
+                    }
+                }
+            }
+        }
+        edges
+    }
+}
+
+/// A non-horizontal polygon edge in pixel space
+struct Edge {
+    y_min: f64,
+    y_max: f64,
+    x_at_y_min: f64,
+    dx_dy: f64,
+}
+
+impl Edge {
+    fn new((x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> Self {
+        let ((x0, y0), (x1, y1)) = if y0 < y1 {
+            ((x0, y0), (x1, y1))
+        } else {
+            ((x1, y1), (x0, y0))
+        };
+        Self {
+            y_min: y0,
+            y_max: y1,
+            x_at_y_min: x0,
+            dx_dy: (x1 - x0) / (y1 - y0),
+        }
+    }
+}
+
+/// Accumulate even-odd coverage of `edges` into `coverage`
+///
+/// Sample lines are visited top to bottom with an active edge table, so each
+/// line only looks at the edges spanning it.
+fn fill_even_odd(edges: &[Edge], width: u32, height: u32, samples: u8, coverage: &mut [f32]) {
+    let weight = 1.0 / f32::from(samples);
+    let width_f = f64::from(width);
+    let mut crossings = Vec::new();
+
+    let mut pending: Vec<&Edge> = edges.iter().collect();
+    pending.sort_by(|a, b| a.y_min.total_cmp(&b.y_min));
+    let mut pending = pending.into_iter().peekable();
+    let mut active: Vec<&Edge> = Vec::new();
+
+    for row in 0..height {
+        let row_coverage = &mut coverage[row as usize * width as usize..][..width as usize];
+        for s in 0..samples {
+            let y = f64::from(row) + (f64::from(s) + 0.5) / f64::from(samples);
+            while let Some(edge) = pending.next_if(|e| e.y_min <= y) {
+                active.push(edge);
+            }
+            // Half-open in y so a shared vertex is counted once
+            active.retain(|e| y < e.y_max);
+
+            crossings.clear();
+            crossings.extend(
+                active
+                    .iter()
+                    .map(|e| e.x_at_y_min + (y - e.y_min) * e.dx_dy),
+            );
+            crossings.sort_by(f64::total_cmp);
+
+            for span in crossings.chunks_exact(2) {
+                let x0 = span[0].clamp(0.0, width_f);
+                let x1 = span[1].clamp(0.0, width_f);
+                if x1 > x0 {
+                    add_span(row_coverage, x0, x1, weight);
+                }
+            }
+        }
+    }
+}
+
+/// Add `weight` times the horizontal coverage of `[x0, x1)` to a row
+fn add_span(row: &mut [f32], x0: f64, x1: f64, weight: f32) {
+    let first = x0.floor() as usize;
+    let last = (x1.ceil() as usize).min(row.len());
+    for (px, cell) in row.iter_mut().enumerate().take(last).skip(first) {
+        let left = x0.max(px as f64);
+        let right = x1.min(px as f64 + 1.0);
+        if right > left {
+            *cell += weight * (right - left) as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{LineString, MultiPolygon, Polygon};
+
+    fn square_with_hole() -> Isoband {
+        let exterior = LineString::from(vec![
+            (0.0, 0.0),
+            (8.0, 0.0),
+            (8.0, 8.0),
+            (0.0, 8.0),
+            (0.0, 0.0),
+        ]);
+        let hole = LineString::from(vec![
+            (2.0, 2.0),
+            (2.0, 6.0),
+            (6.0, 6.0),
+            (6.0, 2.0),
+            (2.0, 2.0),
+        ]);
+        Isoband {
+            lower: 0.0,
+            upper: 1.0,
+            polygons: MultiPolygon(vec![Polygon::new(exterior, vec![hole])]),
+        }
+    }
+
+    #[test]
+    fn test_fill_with_hole() {
+        let colors = |_: f64, _: f64| Color::rgb(255, 0, 0);
+        let renderer = RasterRenderer::new((0.0, 0.0, 10.0, 10.0), 10, 10, &colors)
+            .unwrap()
+            .with_background(Color::rgb(255, 255, 255));
+        let image = renderer.render(&[square_with_hole()]);
+
+        // Band interior (row 1 is latitude 8..9, above the band)
+        assert_eq!(image.pixel(0, 9), Color::rgb(255, 0, 0));
+        assert_eq!(image.pixel(7, 3), Color::rgb(255, 0, 0));
+        // Hole and outside keep the background
+        assert_eq!(image.pixel(4, 5), Color::rgb(255, 255, 255));
+        assert_eq!(image.pixel(9, 9), Color::rgb(255, 255, 255));
+        assert_eq!(image.pixel(0, 1), Color::rgb(255, 255, 255));
+    }
+
+    #[test]
+    fn test_anti_aliased_edge() {
+        let colors = |_: f64, _: f64| Color::rgb(0, 0, 0);
+        // Right edge at x = 2.5 pixels
+        let band = Isoband {
+            lower: 0.0,
+            upper: 1.0,
+            polygons: MultiPolygon(vec![Polygon::new(
+                LineString::from(vec![(0.0, 0.0), (2.5, 0.0), (2.5, 4.0), (0.0, 4.0)]),
+                vec![],
+            )]),
+        };
+        let image = RasterRenderer::new((0.0, 0.0, 4.0, 4.0), 4, 4, &colors)
+            .unwrap()
+            .render(&[band]);
+
+        assert_eq!(image.pixel(1, 1), Color::rgba(0, 0, 0, 255));
+        assert_eq!(image.pixel(2, 1).a, 128);
+        assert_eq!(image.pixel(3, 1).a, 0);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png_round_trip() {
+        let colors = |_: f64, _: f64| Color::rgba(0, 128, 255, 200);
+        let image = RasterRenderer::new((0.0, 0.0, 10.0, 10.0), 16, 12, &colors)
+            .unwrap()
+            .render(&[square_with_hole()]);
+        let bytes = image.to_png().unwrap();
+
+        let decoder = ::png::Decoder::new(std::io::Cursor::new(bytes));
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (16, 12));
+        assert_eq!(&buf[..info.buffer_size()], image.pixels());
+    }
+}
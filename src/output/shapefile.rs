@@ -0,0 +1,577 @@
+//! ESRI Shapefile writer for isobands and isolines
+//!
+//! Isobands are written as Polygon shapes (type 5) and isolines as PolyLine
+//! shapes (type 3), one record per band or level, with the rings of every
+//! polygon in the band as parts of that record. Following the shapefile
+//! specification, exterior rings are written clockwise and holes
+//! counter-clockwise.
+//!
+//! The attribute table (.dbf) holds `lower_lvl` and `upper_lvl` for isobands,
+//! and `isovalue` for isolines, as numeric fields; dBASE III field names are
+//! limited to 10 characters. A .prj with the WGS84 geographic coordinate
+//! system is written alongside.
+//!
+//! File lengths are stored as 32-bit word counts, so a .shp cannot exceed
+//! 4 GiB; writing a record past that limit is an error.
+//!
+//! Records are streamed as they are written; the headers, which carry the
+//! file length, bounding box and record count, are patched in
+//! [`ShapefileWriter::finish`].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use geo_marching_squares_rs::output::shapefile::ShapefileWriter;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//!
+//! # let grid = GeoGrid::from_points(vec![
+//! #     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//! #     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! # ])?;
+//! // Writes temperature.shp, .shx, .dbf and .prj
+//! let mut writer = ShapefileWriter::create_isobands("out/temperature")?;
+//! for band in grid.isobands_geo(&[12.0, 18.0, 22.0])? {
+//!     writer.write_isoband(&band)?;
+//! }
+//! writer.finish()?;
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use crate::types::{Isoband, Isoline};
+use geo_types::{Coord, LineString};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// ESRI WKT for WGS84 geographic coordinates, written to the .prj file
+pub const WGS84_PRJ: &str = "GEOGCS[\"GCS_WGS_1984\",DATUM[\"D_WGS_1984\",SPHEROID[\"WGS_1984\",6378137.0,298.257223563]],PRIMEM[\"Greenwich\",0.0],UNIT[\"Degree\",0.0174532925199433]]";
+
+/// Shapefile header file code and version
+const FILE_CODE: i32 = 9994;
+const VERSION: i32 = 1000;
+
+/// Shape type codes
+const SHAPE_POLYLINE: i32 = 3;
+const SHAPE_POLYGON: i32 = 5;
+
+/// Length in bytes of the .shp/.shx header
+const HEADER_LEN: u64 = 100;
+
+/// Longest dBASE III field name; the name field is NUL-terminated
+const MAX_FIELD_NAME: usize = 10;
+
+/// Width and decimal places of the numeric level fields
+const FIELD_WIDTH: usize = 19;
+const FIELD_DECIMALS: usize = 8;
+
+/// Which contour output a writer holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layer {
+    Isobands,
+    Isolines,
+}
+
+impl Layer {
+    fn shape_type(self) -> i32 {
+        match self {
+            Layer::Isobands => SHAPE_POLYGON,
+            Layer::Isolines => SHAPE_POLYLINE,
+        }
+    }
+
+    fn fields(self) -> &'static [&'static str] {
+        match self {
+            Layer::Isobands => &["lower_lvl", "upper_lvl"],
+            Layer::Isolines => &["isovalue"],
+        }
+    }
+}
+
+/// Streaming writer for the .shp, .shx and .dbf files of one layer
+#[derive(Debug)]
+pub struct ShapefileWriter<W: Write + Seek> {
+    shp: W,
+    shx: W,
+    dbf: W,
+    layer: Layer,
+    records: u32,
+    /// Current .shp length in bytes
+    shp_len: u64,
+    bbox: Option<[f64; 4]>,
+}
+
+impl ShapefileWriter<BufWriter<File>> {
+    /// Create `<path>.shp`, `.shx`, `.dbf` and `.prj` for isobands
+    ///
+    /// Any extension on `path` is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files cannot be created
+    pub fn create_isobands(path: impl AsRef<Path>) -> Result<Self> {
+        Self::create(path.as_ref(), Layer::Isobands)
+    }
+
+    /// Create `<path>.shp`, `.shx`, `.dbf` and `.prj` for isolines
+    ///
+    /// Any extension on `path` is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files cannot be created
+    pub fn create_isolines(path: impl AsRef<Path>) -> Result<Self> {
+        Self::create(path.as_ref(), Layer::Isolines)
+    }
+
+    fn create(path: &Path, layer: Layer) -> Result<Self> {
+        let open = |ext: &str| -> Result<BufWriter<File>> {
+            Ok(BufWriter::new(File::create(path.with_extension(ext))?))
+        };
+        std::fs::write(path.with_extension("prj"), WGS84_PRJ)?;
+        Self::new(open("shp")?, open("shx")?, open("dbf")?, layer)
+    }
+}
+
+impl<W: Write + Seek> ShapefileWriter<W> {
+    /// Start an isoband layer on the given .shp, .shx and .dbf outputs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn isobands(shp: W, shx: W, dbf: W) -> Result<Self> {
+        Self::new(shp, shx, dbf, Layer::Isobands)
+    }
+
+    /// Start an isoline layer on the given .shp, .shx and .dbf outputs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn isolines(shp: W, shx: W, dbf: W) -> Result<Self> {
+        Self::new(shp, shx, dbf, Layer::Isolines)
+    }
+
+    fn new(shp: W, shx: W, dbf: W, layer: Layer) -> Result<Self> {
+        let mut writer = Self {
+            shp,
+            shx,
+            dbf,
+            layer,
+            records: 0,
+            shp_len: HEADER_LEN,
+            bbox: None,
+        };
+        // Placeholder headers, rewritten by `finish`
+        writer.write_headers()?;
+        Ok(writer)
+    }
+
+    /// Write one isoband as a Polygon record
+    ///
+    /// Bands without polygons are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is an isoline writer, or on I/O failure
+    pub fn write_isoband(&mut self, band: &Isoband) -> Result<()> {
+        self.expect_layer(Layer::Isobands)?;
+
+        let mut parts: Vec<Vec<Coord<f64>>> = Vec::new();
+        for polygon in &band.polygons {
+            if polygon.exterior().0.is_empty() {
+                continue;
+            }
+            parts.push(oriented(polygon.exterior(), true));
+            for hole in polygon.interiors() {
+                if !hole.0.is_empty() {
+                    parts.push(oriented(hole, false));
+                }
+            }
+        }
+        if parts.is_empty() {
+            return Ok(());
+        }
+
+        self.write_record(&parts, &[band.lower, band.upper])
+    }
+
+    /// Write one isoline as a PolyLine record
+    ///
+    /// Levels without lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is an isoband writer, or on I/O failure
+    pub fn write_isoline(&mut self, isoline: &Isoline) -> Result<()> {
+        self.expect_layer(Layer::Isolines)?;
+
+        let parts: Vec<Vec<Coord<f64>>> = isoline
+            .lines
+            .iter()
+            .filter(|line| !line.0.is_empty())
+            .map(|line| line.0.clone())
+            .collect();
+        if parts.is_empty() {
+            return Ok(());
+        }
+
+        self.write_record(&parts, &[isoline.level])
+    }
+
+    /// Patch the file headers and flush, returning the .shp, .shx and .dbf outputs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn finish(mut self) -> Result<(W, W, W)> {
+        // dBASE end-of-file marker
+        self.dbf.write_all(&[0x1A])?;
+
+        for out in [&mut self.shp, &mut self.shx, &mut self.dbf] {
+            out.seek(SeekFrom::Start(0))?;
+        }
+        self.write_headers()?;
+
+        for out in [&mut self.shp, &mut self.shx, &mut self.dbf] {
+            out.seek(SeekFrom::End(0))?;
+            out.flush()?;
+        }
+        Ok((self.shp, self.shx, self.dbf))
+    }
+
+    fn expect_layer(&self, layer: Layer) -> Result<()> {
+        if self.layer != layer {
+            return Err(Error::geometry_error(format!(
+                "Shapefile writer holds {:?}, cannot write {:?}",
+                self.layer, layer
+            )));
+        }
+        Ok(())
+    }
+
+    fn write_headers(&mut self) -> Result<()> {
+        let bbox = self.bbox.unwrap_or([0.0; 4]);
+        let shx_len = HEADER_LEN + 8 * u64::from(self.records);
+        let shape_type = self.layer.shape_type();
+        self.shp
+            .write_all(&main_header(word_count(self.shp_len)?, shape_type, bbox))?;
+        self.shx
+            .write_all(&main_header(word_count(shx_len)?, shape_type, bbox))?;
+        self.dbf
+            .write_all(&dbf_header(self.layer.fields(), self.records))?;
+        Ok(())
+    }
+
+    fn write_record(&mut self, parts: &[Vec<Coord<f64>>], values: &[f64]) -> Result<()> {
+        let num_points: usize = parts.iter().map(Vec::len).sum();
+        let mut bbox = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for c in parts.iter().flatten() {
+            bbox = [
+                bbox[0].min(c.x),
+                bbox[1].min(c.y),
+                bbox[2].max(c.x),
+                bbox[3].max(c.y),
+            ];
+        }
+
+        // Shape type, box, part and point counts, part starts, points
+        let mut content = Vec::with_capacity(44 + 4 * parts.len() + 16 * num_points);
+        content.extend_from_slice(&self.layer.shape_type().to_le_bytes());
+        for v in bbox {
+            content.extend_from_slice(&v.to_le_bytes());
+        }
+        content.extend_from_slice(&(parts.len() as i32).to_le_bytes());
+        content.extend_from_slice(&(num_points as i32).to_le_bytes());
+        let mut start = 0i32;
+        for part in parts {
+            content.extend_from_slice(&start.to_le_bytes());
+            start += part.len() as i32;
+        }
+        for c in parts.iter().flatten() {
+            content.extend_from_slice(&c.x.to_le_bytes());
+            content.extend_from_slice(&c.y.to_le_bytes());
+        }
+
+        let offset_words = word_count(self.shp_len)?;
+        let content_words = word_count(content.len() as u64)?;
+        word_count(self.shp_len + 8 + content.len() as u64)?;
+        self.records += 1;
+
+        self.shp.write_all(&(self.records as i32).to_be_bytes())?;
+        self.shp.write_all(&content_words.to_be_bytes())?;
+        self.shp.write_all(&content)?;
+        self.shp_len += 8 + content.len() as u64;
+
+        self.shx.write_all(&offset_words.to_be_bytes())?;
+        self.shx.write_all(&content_words.to_be_bytes())?;
+
+        // Deletion flag, then right-aligned numeric fields
+        let mut record = Vec::with_capacity(1 + FIELD_WIDTH * values.len());
+        record.push(b' ');
+        for &value in values {
+            record.extend_from_slice(format_numeric(value).as_bytes());
+        }
+        self.dbf.write_all(&record)?;
+
+        self.bbox = Some(match self.bbox {
+            Some(b) => [
+                b[0].min(bbox[0]),
+                b[1].min(bbox[1]),
+                b[2].max(bbox[2]),
+                b[3].max(bbox[3]),
+            ],
+            None => bbox,
+        });
+        Ok(())
+    }
+}
+
+/// Copy a ring, reversing it if needed so exteriors run clockwise and holes
+/// counter-clockwise
+fn oriented(ring: &LineString<f64>, exterior: bool) -> Vec<Coord<f64>> {
+    let mut coords = ring.0.clone();
+    let clockwise = signed_area(&coords) < 0.0;
+    if clockwise != exterior {
+        coords.reverse();
+    }
+    coords
+}
+
+/// Twice the signed area of a ring; positive when counter-clockwise
+fn signed_area(coords: &[Coord<f64>]) -> f64 {
+    coords
+        .windows(2)
+        .map(|w| w[0].x * w[1].y - w[1].x * w[0].y)
+        .sum::<f64>()
+        + match (coords.last(), coords.first()) {
+            (Some(last), Some(first)) => last.x * first.y - first.x * last.y,
+            _ => 0.0,
+        }
+}
+
+/// A length in bytes as the 16-bit word count stored in .shp/.shx files
+fn word_count(len_bytes: u64) -> Result<i32> {
+    i32::try_from(len_bytes / 2)
+        .map_err(|_| Error::geometry_error("Shapefile exceeds the 4 GiB size limit"))
+}
+
+/// 100-byte .shp/.shx header
+fn main_header(len_words: i32, shape_type: i32, bbox: [f64; 4]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_LEN as usize);
+    header.extend_from_slice(&FILE_CODE.to_be_bytes());
+    header.extend_from_slice(&[0u8; 20]);
+    header.extend_from_slice(&len_words.to_be_bytes());
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.extend_from_slice(&shape_type.to_le_bytes());
+    for v in bbox {
+        header.extend_from_slice(&v.to_le_bytes());
+    }
+    // Z and M ranges are unused
+    header.extend_from_slice(&[0u8; 32]);
+    header
+}
+
+/// dBASE III header with numeric fields
+fn dbf_header(fields: &[&str], records: u32) -> Vec<u8> {
+    let header_len = 32 + 32 * fields.len() + 1;
+    let record_len = 1 + FIELD_WIDTH * fields.len();
+
+    let mut header = Vec::with_capacity(header_len);
+    // Version, then a fixed last-update date (1995-07-26) for reproducible output
+    header.extend_from_slice(&[0x03, 95, 7, 26]);
+    header.extend_from_slice(&records.to_le_bytes());
+    header.extend_from_slice(&(header_len as u16).to_le_bytes());
+    header.extend_from_slice(&(record_len as u16).to_le_bytes());
+    header.extend_from_slice(&[0u8; 20]);
+
+    for name in fields {
+        let mut descriptor = [0u8; 32];
+        let name = &name.as_bytes()[..name.len().min(MAX_FIELD_NAME)];
+        descriptor[..name.len()].copy_from_slice(name);
+        descriptor[11] = b'N';
+        descriptor[16] = FIELD_WIDTH as u8;
+        descriptor[17] = FIELD_DECIMALS as u8;
+        header.extend_from_slice(&descriptor);
+    }
+    header.push(0x0D);
+    header
+}
+
+/// Right-aligned fixed-width numeric field; NaN is written as null (blank)
+fn format_numeric(value: f64) -> String {
+    if !value.is_finite() {
+        return " ".repeat(FIELD_WIDTH);
+    }
+    let text = format!(
+        "{:>width$.prec$}",
+        value,
+        width = FIELD_WIDTH,
+        prec = FIELD_DECIMALS
+    );
+    if text.len() > FIELD_WIDTH {
+        // dBASE marks numeric overflow with asterisks
+        return "*".repeat(FIELD_WIDTH);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{MultiLineString, MultiPolygon, Polygon};
+    use std::io::Cursor;
+
+    fn i32_be(bytes: &[u8], pos: usize) -> i32 {
+        i32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap())
+    }
+
+    fn i32_le(bytes: &[u8], pos: usize) -> i32 {
+        i32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+    }
+
+    fn f64_le(bytes: &[u8], pos: usize) -> f64 {
+        f64::from_le_bytes(bytes[pos..pos + 8].try_into().unwrap())
+    }
+
+    fn square(min: f64, max: f64, counter_clockwise: bool) -> LineString<f64> {
+        let mut coords = vec![(min, min), (max, min), (max, max), (min, max), (min, min)];
+        if !counter_clockwise {
+            coords.reverse();
+        }
+        LineString::from(coords)
+    }
+
+    fn writer(layer: Layer) -> ShapefileWriter<Cursor<Vec<u8>>> {
+        let out = || Cursor::new(Vec::new());
+        ShapefileWriter::new(out(), out(), out(), layer).unwrap()
+    }
+
+    #[test]
+    fn test_polygon_records() {
+        // GeoJSON-style orientation: counter-clockwise exterior, clockwise hole
+        let band = Isoband {
+            lower: 10.0,
+            upper: 20.5,
+            polygons: MultiPolygon(vec![Polygon::new(
+                square(-100.0, -90.0, true),
+                vec![square(-98.0, -95.0, false)],
+            )]),
+        };
+
+        let mut w = writer(Layer::Isobands);
+        w.write_isoband(&band).unwrap();
+        assert!(w
+            .write_isoline(&Isoline {
+                level: 1.0,
+                lines: MultiLineString(vec![]),
+            })
+            .is_err());
+        let (shp, shx, dbf) = w.finish().unwrap();
+        let (shp, shx, dbf) = (shp.into_inner(), shx.into_inner(), dbf.into_inner());
+
+        // Main header
+        assert_eq!(i32_be(&shp, 0), FILE_CODE);
+        assert_eq!(i32_be(&shp, 24) as usize * 2, shp.len());
+        assert_eq!(i32_le(&shp, 28), VERSION);
+        assert_eq!(i32_le(&shp, 32), SHAPE_POLYGON);
+        assert_eq!(f64_le(&shp, 36), -100.0);
+        assert_eq!(f64_le(&shp, 60), -90.0);
+
+        // Record: 2 parts of 5 points, exterior clockwise, hole counter-clockwise
+        assert_eq!(i32_be(&shp, 100), 1);
+        assert_eq!(i32_be(&shp, 104) as usize * 2, shp.len() - 108);
+        assert_eq!(i32_le(&shp, 108 + 36), 2);
+        assert_eq!(i32_le(&shp, 108 + 40), 10);
+        assert_eq!(i32_le(&shp, 108 + 48), 5);
+        let points: Vec<Coord<f64>> = (0..10)
+            .map(|i| {
+                let pos = 108 + 52 + i * 16;
+                Coord {
+                    x: f64_le(&shp, pos),
+                    y: f64_le(&shp, pos + 8),
+                }
+            })
+            .collect();
+        assert!(signed_area(&points[..5]) < 0.0);
+        assert!(signed_area(&points[5..]) > 0.0);
+
+        // Index
+        assert_eq!(shx.len(), 108);
+        assert_eq!(i32_be(&shx, 24), 54);
+        assert_eq!(i32_be(&shx, 100), 50);
+        assert_eq!(i32_be(&shx, 104), i32_be(&shp, 104));
+
+        // Attributes
+        assert_eq!(u32::from_le_bytes(dbf[4..8].try_into().unwrap()), 1);
+        assert_eq!(&dbf[32..43], b"lower_lvl\0\0");
+        assert_eq!(dbf[43], b'N');
+        let header_len = u16::from_le_bytes(dbf[8..10].try_into().unwrap()) as usize;
+        let record = std::str::from_utf8(&dbf[header_len..dbf.len() - 1]).unwrap();
+        assert_eq!(record.len(), 1 + 2 * FIELD_WIDTH);
+        assert_eq!(
+            record[1..].split_whitespace().collect::<Vec<_>>(),
+            ["10.00000000", "20.50000000"]
+        );
+        assert_eq!(*dbf.last().unwrap(), 0x1A);
+
+        // Word counts must fit the header's i32 fields
+        assert_eq!(word_count(100).unwrap(), 50);
+        assert!(word_count(1 << 32).is_err());
+    }
+
+    #[test]
+    fn test_polyline_records() {
+        let isoline = Isoline {
+            level: -4.0,
+            lines: MultiLineString(vec![
+                LineString::from(vec![(0.0, 0.0), (1.0, 1.0)]),
+                LineString::from(vec![(2.0, 2.0), (3.0, 3.0), (4.0, 2.0)]),
+            ]),
+        };
+
+        let mut w = writer(Layer::Isolines);
+        w.write_isoline(&isoline).unwrap();
+        w.write_isoline(&isoline).unwrap();
+        let (shp, shx, dbf) = w.finish().unwrap();
+        let (shp, shx, dbf) = (shp.into_inner(), shx.into_inner(), dbf.into_inner());
+
+        assert_eq!(i32_le(&shp, 32), SHAPE_POLYLINE);
+        assert_eq!(
+            i32_be(&shx, 100 + 8),
+            (shp.len() as i32 - 8 - i32_be(&shp, 104) * 2) / 2
+        );
+        assert_eq!(u32::from_le_bytes(dbf[4..8].try_into().unwrap()), 2);
+        assert_eq!(&dbf[32..40], b"isovalue");
+    }
+
+    #[test]
+    fn test_create_files() {
+        let dir = std::env::temp_dir().join(format!("gms_shapefile_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let base = dir.join("bands");
+
+        let mut w = ShapefileWriter::create_isobands(&base).unwrap();
+        w.write_isoband(&Isoband {
+            lower: 0.0,
+            upper: 1.0,
+            polygons: MultiPolygon(vec![Polygon::new(square(0.0, 1.0, true), vec![])]),
+        })
+        .unwrap();
+        w.finish().unwrap();
+
+        for ext in ["shp", "shx", "dbf", "prj"] {
+            assert!(base.with_extension(ext).exists(), "missing .{ext}");
+        }
+        assert_eq!(
+            std::fs::read_to_string(base.with_extension("prj")).unwrap(),
+            WGS84_PRJ
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,437 @@
+//! Standalone SVG rendering of isobands and isolines
+//!
+//! [`SvgRenderer`] projects contour geometry inside a lon/lat bounding box onto
+//! a pixel canvas and writes a self-contained SVG document. Each isoband is one
+//! filled `<path>` using the even-odd fill rule, so interior rings cut holes
+//! without depending on ring orientation. Isolines are drawn as unfilled
+//! strokes on top of the bands. Colors come from a [`ColorMap`], and an
+//! optional legend lists every band and level to the right of the map.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::color::{Color, ColorRamp};
+//! use geo_marching_squares_rs::output::svg::SvgRenderer;
+//! use geo_marching_squares_rs::output::Projection;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//! let ramp = ColorRamp::evenly_spaced(10.0, 25.0, &[Color::rgb(0, 0, 255), Color::rgb(255, 0, 0)])?;
+//!
+//! let renderer = SvgRenderer::new(grid.bounds(), 400, &ramp)?
+//!     .with_projection(Projection::WebMercator)?
+//!     .with_legend(true);
+//! let svg = renderer.render(
+//!     &grid.isobands_geo(&[12.0, 18.0, 22.0])?,
+//!     &grid.isolines_geo(&[15.0, 20.0])?,
+//! );
+//! assert!(svg.starts_with("<svg"));
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use super::{Projection, Viewport};
+use crate::clip::Rect;
+use crate::color::{Color, ColorMap};
+use crate::error::Result;
+use crate::types::{Isoband, Isoline};
+use geo_types::LineString;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Default isoline stroke width in pixels
+pub const DEFAULT_STROKE_WIDTH: f64 = 1.0;
+
+/// Width of the legend column in pixels
+const LEGEND_WIDTH: u32 = 160;
+
+/// Height of one legend row in pixels
+const LEGEND_ROW: u32 = 18;
+
+/// Renders contour output as a standalone SVG document
+pub struct SvgRenderer<'a> {
+    bbox: (f64, f64, f64, f64),
+    viewport: Viewport,
+    colors: &'a dyn ColorMap,
+    stroke_width: f64,
+    band_outline: Option<Color>,
+    background: Option<Color>,
+    title: Option<String>,
+    legend: bool,
+}
+
+impl<'a> SvgRenderer<'a> {
+    /// Create a renderer for `bbox` (min_lon, min_lat, max_lon, max_lat)
+    ///
+    /// The map is `width` pixels wide and its height follows from the
+    /// projected aspect ratio of the box. The projection defaults to
+    /// [`Projection::Equirectangular`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the box is empty or not finite, or `width` is zero
+    pub fn new(bbox: (f64, f64, f64, f64), width: u32, colors: &'a dyn ColorMap) -> Result<Self> {
+        Ok(Self {
+            bbox,
+            viewport: Viewport::new(bbox, width, Projection::default())?,
+            colors,
+            stroke_width: DEFAULT_STROKE_WIDTH,
+            band_outline: None,
+            background: None,
+            title: None,
+            legend: false,
+        })
+    }
+
+    /// Set the map projection
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the box cannot be projected
+    pub fn with_projection(mut self, projection: Projection) -> Result<Self> {
+        self.viewport = Viewport::new(self.bbox, self.viewport.width, projection)?;
+        Ok(self)
+    }
+
+    /// Set the isoline stroke width in pixels
+    pub fn with_stroke_width(mut self, width: f64) -> Self {
+        self.stroke_width = width;
+        self
+    }
+
+    /// Outline every isoband in a fixed color (bands are unstroked by default)
+    pub fn with_band_outline(mut self, color: Color) -> Self {
+        self.band_outline = Some(color);
+        self
+    }
+
+    /// Fill the map area with a background color
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Set the document title
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Draw a legend of bands and levels to the right of the map
+    pub fn with_legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Size of the map area in pixels, excluding the legend
+    pub fn map_size(&self) -> (u32, u32) {
+        (self.viewport.width, self.viewport.height)
+    }
+
+    /// Render the bands and lines to an SVG document
+    pub fn render(&self, isobands: &[Isoband], isolines: &[Isoline]) -> String {
+        let (map_width, map_height) = self.map_size();
+        let legend_rows = (isobands.len() + isolines.len()) as u32;
+        let (width, height) = if self.legend && legend_rows > 0 {
+            (
+                map_width + LEGEND_WIDTH,
+                map_height.max((legend_rows + 1) * LEGEND_ROW),
+            )
+        } else {
+            (map_width, map_height)
+        };
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">"
+        );
+        if let Some(title) = &self.title {
+            let _ = writeln!(svg, "<title>{}</title>", escape(title));
+        }
+        let _ = writeln!(
+            svg,
+            "<defs><clipPath id=\"map\"><rect width=\"{map_width}\" height=\"{map_height}\"/></clipPath></defs>"
+        );
+        if let Some(background) = self.background {
+            let _ = writeln!(
+                svg,
+                "<rect width=\"{map_width}\" height=\"{map_height}\"{}/>",
+                paint("fill", background)
+            );
+        }
+
+        svg.push_str("<g clip-path=\"url(#map)\">\n");
+        let canvas = Rect::new(0.0, 0.0, f64::from(map_width), f64::from(map_height));
+        for band in isobands {
+            self.push_band(&mut svg, band, &canvas);
+        }
+        for isoline in isolines {
+            self.push_isoline(&mut svg, isoline, &canvas);
+        }
+        svg.push_str("</g>\n");
+
+        if self.legend && legend_rows > 0 {
+            self.push_legend(&mut svg, isobands, isolines);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Render the bands and lines and write the document to `out`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on I/O failure
+    pub fn write<W: Write>(
+        &self,
+        mut out: W,
+        isobands: &[Isoband],
+        isolines: &[Isoline],
+    ) -> Result<()> {
+        out.write_all(self.render(isobands, isolines).as_bytes())?;
+        out.flush()?;
+        Ok(())
+    }
+
+    fn push_band(&self, svg: &mut String, band: &Isoband, canvas: &Rect) {
+        let mut data = String::new();
+        for polygon in &band.polygons {
+            let exterior = self.pixels(polygon.exterior());
+            if !canvas.intersects(&exterior) {
+                continue;
+            }
+            push_subpath(&mut data, &exterior, true);
+            for hole in polygon.interiors() {
+                push_subpath(&mut data, &self.pixels(hole), true);
+            }
+        }
+        if data.is_empty() {
+            return;
+        }
+
+        let fill = self.colors.band_color(band.lower, band.upper);
+        let stroke = match self.band_outline {
+            Some(color) => format!(
+                "{} stroke-width=\"{}\"",
+                paint("stroke", color),
+                self.stroke_width
+            ),
+            None => String::from(" stroke=\"none\""),
+        };
+        let _ = writeln!(
+            svg,
+            "<path d=\"{data}\"{} fill-rule=\"evenodd\"{stroke} data-lower=\"{}\" data-upper=\"{}\"/>",
+            paint("fill", fill),
+            band.lower,
+            band.upper
+        );
+    }
+
+    fn push_isoline(&self, svg: &mut String, isoline: &Isoline, canvas: &Rect) {
+        let mut data = String::new();
+        for line in &isoline.lines {
+            let pixels = self.pixels(line);
+            if pixels.len() >= 2 && canvas.intersects(&pixels) {
+                push_subpath(&mut data, &pixels, false);
+            }
+        }
+        if data.is_empty() {
+            return;
+        }
+
+        let stroke = self.colors.level_color(isoline.level);
+        let _ = writeln!(
+            svg,
+            "<path d=\"{data}\" fill=\"none\"{} stroke-width=\"{}\" stroke-linejoin=\"round\" data-level=\"{}\"/>",
+            paint("stroke", stroke),
+            self.stroke_width,
+            isoline.level
+        );
+    }
+
+    fn push_legend(&self, svg: &mut String, isobands: &[Isoband], isolines: &[Isoline]) {
+        let x = self.viewport.width + 10;
+        let _ = writeln!(
+            svg,
+            "<g font-family=\"sans-serif\" font-size=\"11\" transform=\"translate({x},{})\">",
+            LEGEND_ROW / 2
+        );
+        let mut y = 0;
+        for band in isobands {
+            let color = self.colors.band_color(band.lower, band.upper);
+            let _ = writeln!(
+                svg,
+                "<rect y=\"{y}\" width=\"12\" height=\"12\"{} stroke=\"#333333\" stroke-width=\"0.5\"/>\
+                 <text x=\"18\" y=\"{}\">{} – {}</text>",
+                paint("fill", color),
+                y + 10,
+                band.lower,
+                band.upper
+            );
+            y += LEGEND_ROW;
+        }
+        for isoline in isolines {
+            let color = self.colors.level_color(isoline.level);
+            let _ = writeln!(
+                svg,
+                "<line y1=\"{0}\" x2=\"12\" y2=\"{0}\"{1} stroke-width=\"{2}\"/>\
+                 <text x=\"18\" y=\"{3}\">{4}</text>",
+                y + 6,
+                paint("stroke", color),
+                self.stroke_width,
+                y + 10,
+                isoline.level
+            );
+            y += LEGEND_ROW;
+        }
+        svg.push_str("</g>\n");
+    }
+
+    fn pixels(&self, line: &LineString<f64>) -> Vec<(f64, f64)> {
+        line.coords()
+            .map(|c| self.viewport.pixel(c.x, c.y))
+            .collect()
+    }
+}
+
+/// Append `M x y L ...` for one ring or line, closing rings with `Z`
+fn push_subpath(data: &mut String, points: &[(f64, f64)], close: bool) {
+    // Closed rings repeat their first point, which `Z` already implies
+    let points = match points {
+        [first, .., last] if close && first == last => &points[..points.len() - 1],
+        _ => points,
+    };
+    if points.is_empty() {
+        return;
+    }
+    for (i, &(x, y)) in points.iter().enumerate() {
+        if !data.is_empty() {
+            data.push(' ');
+        }
+        data.push(if i == 0 { 'M' } else { 'L' });
+        let _ = write!(data, "{} {}", round(x), round(y));
+    }
+    if close {
+        data.push_str(" Z");
+    }
+}
+
+/// Round pixel coordinates to two decimals
+fn round(v: f64) -> f64 {
+    let r = (v * 100.0).round() / 100.0;
+    if r == 0.0 {
+        0.0
+    } else {
+        r
+    }
+}
+
+/// A fill or stroke attribute, with opacity when the color is translucent
+fn paint(attribute: &str, color: Color) -> String {
+    let rgb = format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b);
+    if color.a == 255 {
+        format!(" {attribute}=\"{rgb}\"")
+    } else {
+        format!(
+            " {attribute}=\"{rgb}\" {attribute}-opacity=\"{}\"",
+            round(f64::from(color.a) / 255.0)
+        )
+    }
+}
+
+/// Escape XML special characters in text content
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{MultiLineString, MultiPolygon, Polygon};
+
+    fn band() -> Isoband {
+        let exterior = LineString::from(vec![
+            (0.0, 0.0),
+            (10.0, 0.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (0.0, 0.0),
+        ]);
+        let hole = LineString::from(vec![
+            (2.0, 2.0),
+            (2.0, 8.0),
+            (8.0, 8.0),
+            (8.0, 2.0),
+            (2.0, 2.0),
+        ]);
+        Isoband {
+            lower: 0.0,
+            upper: 5.0,
+            polygons: MultiPolygon(vec![Polygon::new(exterior, vec![hole])]),
+        }
+    }
+
+    #[test]
+    fn test_equirectangular_band_path() {
+        let colors = |_: f64, _: f64| Color::rgba(255, 0, 0, 128);
+        let renderer = SvgRenderer::new((0.0, 0.0, 10.0, 10.0), 100, &colors).unwrap();
+        assert_eq!(renderer.map_size(), (100, 100));
+
+        let svg = renderer.render(&[band()], &[]);
+        assert!(svg.contains("width=\"100\" height=\"100\""));
+        assert!(svg.contains("d=\"M0 100 L100 100 L100 0 L0 0 Z M20 80 L20 20 L80 20 L80 80 Z\""));
+        assert!(svg.contains("fill=\"#ff0000\" fill-opacity=\"0.5\" fill-rule=\"evenodd\""));
+        assert!(!svg.contains("<text"));
+    }
+
+    #[test]
+    fn test_isolines_and_legend() {
+        let colors = |lower: f64, _: f64| {
+            if lower < 3.0 {
+                Color::rgb(0, 0, 255)
+            } else {
+                Color::rgb(0, 255, 0)
+            }
+        };
+        let isoline = Isoline {
+            level: 3.0,
+            lines: MultiLineString(vec![LineString::from(vec![(0.0, 5.0), (10.0, 5.0)])]),
+        };
+        let renderer = SvgRenderer::new((0.0, 0.0, 10.0, 10.0), 100, &colors)
+            .unwrap()
+            .with_stroke_width(2.0)
+            .with_title("a & b")
+            .with_legend(true);
+        let svg = renderer.render(&[band()], &[isoline]);
+
+        assert!(svg.contains("width=\"260\" height=\"100\""));
+        assert!(svg.contains("<title>a &amp; b</title>"));
+        assert!(
+            svg.contains("d=\"M0 50 L100 50\" fill=\"none\" stroke=\"#00ff00\" stroke-width=\"2\"")
+        );
+        assert!(svg.contains("<text x=\"18\" y=\"10\">0 – 5</text>"));
+        assert!(svg.contains("<text x=\"18\" y=\"28\">3</text>"));
+    }
+
+    #[test]
+    fn test_web_mercator_aspect_and_invalid_bbox() {
+        let colors = |_: f64, _: f64| Color::rgb(0, 0, 0);
+        let renderer = SvgRenderer::new((-10.0, 50.0, 10.0, 70.0), 200, &colors)
+            .unwrap()
+            .with_projection(Projection::WebMercator)
+            .unwrap();
+        // Mercator stretches high latitudes, so the map is taller than wide
+        let (width, height) = renderer.map_size();
+        assert_eq!(width, 200);
+        assert!(height > 300);
+
+        assert!(SvgRenderer::new((10.0, 0.0, 0.0, 10.0), 100, &colors).is_err());
+        assert!(SvgRenderer::new((0.0, 0.0, 10.0, 10.0), 0, &colors).is_err());
+    }
+}
@@ -0,0 +1,599 @@
+//! WKB and WKT serialization of isoband and isoline geometries
+//!
+//! Isobands serialize as `MULTIPOLYGON` and isolines as `MULTILINESTRING`, in
+//! OGC WKB (either byte order), PostGIS EWKB with SRID 4326, or WKT.
+//!
+//! [`isobands_wkb`], [`isolines_wkb`] and their WKT counterparts trace the grid
+//! and encode straight from the traced rings, like
+//! [`GeoJsonWriter`](super::geojson::GeoJsonWriter), without building
+//! `geo-types` geometry. The `to_wkb`/`to_wkt` methods on [`Isoband`] and
+//! [`Isoline`] encode geometries that already exist. Both encode the same
+//! rings for the same grid.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::output::wkb::{isobands_wkb, ByteOrder, WkbFormat};
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//!
+//! let format = WkbFormat::Ewkb(ByteOrder::LittleEndian);
+//! for band in isobands_wkb(&grid, &[12.0, 18.0, 22.0], format)? {
+//!     assert_eq!(band.geometry[0], 1);
+//! }
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::error::Result;
+use crate::grid::{validate_levels, validate_thresholds, GridSource};
+use crate::marching_squares::{closed_ring, isoline_segments, rounded_xy, trace_band, Clipping};
+use crate::types::{Isoband, Isoline};
+use geo_types::LineString;
+use std::fmt::Write;
+
+/// SRID written into EWKB and EWKT output (WGS84 lon/lat)
+pub const SRID_WGS84: u32 = 4326;
+
+/// WKB geometry type codes
+const WKB_LINESTRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+const WKB_MULTILINESTRING: u32 = 5;
+const WKB_MULTIPOLYGON: u32 = 6;
+
+/// EWKB flag marking that an SRID follows the geometry type
+const EWKB_SRID_FLAG: u32 = 0x2000_0000;
+
+/// Byte order of WKB numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+    /// XDR, byte order marker 0
+    BigEndian,
+    /// NDR, byte order marker 1
+    #[default]
+    LittleEndian,
+}
+
+/// Binary serialization flavor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WkbFormat {
+    /// OGC Well-Known Binary without SRID
+    Wkb(ByteOrder),
+    /// PostGIS Extended WKB carrying SRID 4326
+    Ewkb(ByteOrder),
+}
+
+impl Default for WkbFormat {
+    fn default() -> Self {
+        WkbFormat::Wkb(ByteOrder::default())
+    }
+}
+
+/// An isoband encoded straight from its traced rings
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedIsoband<T> {
+    /// Lower threshold of the band
+    pub lower: f64,
+    /// Upper threshold of the band
+    pub upper: f64,
+    /// `MULTIPOLYGON` as WKB bytes or WKT text
+    pub geometry: T,
+}
+
+/// An isoline encoded straight from its traced segments
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedIsoline<T> {
+    /// Contour level
+    pub level: f64,
+    /// `MULTILINESTRING` as WKB bytes or WKT text
+    pub geometry: T,
+}
+
+/// Trace the isobands between consecutive thresholds and encode each as WKB
+///
+/// Empty bands are skipped. Coordinates are rounded with the grid's
+/// configured precision.
+///
+/// # Errors
+///
+/// Returns an error if the thresholds are invalid
+pub fn isobands_wkb<G: GridSource + ?Sized>(
+    grid: &G,
+    thresholds: &[f64],
+    format: WkbFormat,
+) -> Result<Vec<EncodedIsoband<Vec<u8>>>> {
+    encode_isobands(grid, thresholds, |polygons| {
+        let mut buf = Vec::new();
+        write_multipolygon_wkb(&mut buf, format, traced_rings(polygons));
+        buf
+    })
+}
+
+/// Trace the isobands between consecutive thresholds and encode each as WKT
+///
+/// # Errors
+///
+/// Returns an error if the thresholds are invalid
+pub fn isobands_wkt<G: GridSource + ?Sized>(
+    grid: &G,
+    thresholds: &[f64],
+) -> Result<Vec<EncodedIsoband<String>>> {
+    encode_isobands(grid, thresholds, |polygons| {
+        let mut wkt = String::new();
+        write_multipolygon_wkt(&mut wkt, traced_rings(polygons));
+        wkt
+    })
+}
+
+/// Trace the isolines at each level and encode each as WKB
+///
+/// Empty levels are skipped. Coordinates are rounded with the grid's
+/// configured precision.
+///
+/// # Errors
+///
+/// Returns an error if no levels are given
+pub fn isolines_wkb<G: GridSource + ?Sized>(
+    grid: &G,
+    levels: &[f64],
+    format: WkbFormat,
+) -> Result<Vec<EncodedIsoline<Vec<u8>>>> {
+    encode_isolines(grid, levels, |lines| {
+        let mut buf = Vec::new();
+        write_multilinestring_wkb(&mut buf, format, traced_lines(lines));
+        buf
+    })
+}
+
+/// Trace the isolines at each level and encode each as WKT
+///
+/// # Errors
+///
+/// Returns an error if no levels are given
+pub fn isolines_wkt<G: GridSource + ?Sized>(
+    grid: &G,
+    levels: &[f64],
+) -> Result<Vec<EncodedIsoline<String>>> {
+    encode_isolines(grid, levels, |lines| {
+        let mut wkt = String::new();
+        write_multilinestring_wkt(&mut wkt, traced_lines(lines));
+        wkt
+    })
+}
+
+/// Rounded, closed rings of one traced polygon (exterior first)
+type PolygonCoords = Vec<Vec<(f64, f64)>>;
+
+fn encode_isobands<G, T>(
+    grid: &G,
+    thresholds: &[f64],
+    encode: impl Fn(&[PolygonCoords]) -> T,
+) -> Result<Vec<EncodedIsoband<T>>>
+where
+    G: GridSource + ?Sized,
+{
+    validate_thresholds(thresholds)?;
+    let precision = grid.config().precision;
+    let clipping = Clipping::new(grid)?;
+
+    let mut bands = Vec::new();
+    for pair in thresholds.windows(2) {
+        let (lower, upper) = (pair[0], pair[1]);
+        let Some(polygons) = trace_band(grid, &clipping, lower, upper)? else {
+            continue;
+        };
+        let polygons: Vec<PolygonCoords> = polygons
+            .iter()
+            .map(|(exterior, holes)| {
+                std::iter::once(exterior)
+                    .chain(holes)
+                    .map(|ring| closed_ring(ring, precision).collect())
+                    .collect()
+            })
+            .collect();
+        bands.push(EncodedIsoband {
+            lower,
+            upper,
+            geometry: encode(&polygons),
+        });
+    }
+    Ok(bands)
+}
+
+fn encode_isolines<G, T>(
+    grid: &G,
+    levels: &[f64],
+    encode: impl Fn(&[Vec<(f64, f64)>]) -> T,
+) -> Result<Vec<EncodedIsoline<T>>>
+where
+    G: GridSource + ?Sized,
+{
+    validate_levels(levels)?;
+    let precision = grid.config().precision;
+    let clipping = Clipping::new(grid)?;
+
+    let mut isolines = Vec::new();
+    for &level in levels {
+        let lines: Vec<Vec<(f64, f64)>> = isoline_segments(grid, &clipping, level)
+            .iter()
+            .map(|segment| {
+                segment
+                    .iter()
+                    .filter_map(|p| rounded_xy(p, precision))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|line| line.len() >= 2)
+            .collect();
+        if !lines.is_empty() {
+            isolines.push(EncodedIsoline {
+                level,
+                geometry: encode(&lines),
+            });
+        }
+    }
+    Ok(isolines)
+}
+
+fn traced_rings(
+    polygons: &[PolygonCoords],
+) -> impl ExactSizeIterator<
+    Item = impl ExactSizeIterator<Item = impl ExactSizeIterator<Item = (f64, f64)> + '_>,
+> {
+    polygons
+        .iter()
+        .map(|rings| rings.iter().map(|ring| ring.iter().copied()))
+}
+
+fn traced_lines(
+    lines: &[Vec<(f64, f64)>],
+) -> impl ExactSizeIterator<Item = impl ExactSizeIterator<Item = (f64, f64)> + '_> {
+    lines.iter().map(|line| line.iter().copied())
+}
+
+impl Isoband {
+    /// Serialize the band polygons as a WKB `MULTIPOLYGON`
+    pub fn to_wkb(&self, format: WkbFormat) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_multipolygon_wkb(&mut buf, format, polygon_rings(self));
+        buf
+    }
+
+    /// Serialize the band polygons as a WKT `MULTIPOLYGON`
+    pub fn to_wkt(&self) -> String {
+        let mut wkt = String::new();
+        write_multipolygon_wkt(&mut wkt, polygon_rings(self));
+        wkt
+    }
+
+    /// Serialize the band polygons as EWKT with SRID 4326
+    pub fn to_ewkt(&self) -> String {
+        format!("SRID={};{}", SRID_WGS84, self.to_wkt())
+    }
+}
+
+impl Isoline {
+    /// Serialize the lines as a WKB `MULTILINESTRING`
+    pub fn to_wkb(&self, format: WkbFormat) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_multilinestring_wkb(&mut buf, format, self.lines.0.iter().map(line_coords));
+        buf
+    }
+
+    /// Serialize the lines as a WKT `MULTILINESTRING`
+    pub fn to_wkt(&self) -> String {
+        let mut wkt = String::new();
+        write_multilinestring_wkt(&mut wkt, self.lines.0.iter().map(line_coords));
+        wkt
+    }
+
+    /// Serialize the lines as EWKT with SRID 4326
+    pub fn to_ewkt(&self) -> String {
+        format!("SRID={};{}", SRID_WGS84, self.to_wkt())
+    }
+}
+
+/// Each polygon of a band as its exterior ring followed by its holes
+fn polygon_rings(
+    band: &Isoband,
+) -> impl ExactSizeIterator<
+    Item = impl ExactSizeIterator<Item = impl ExactSizeIterator<Item = (f64, f64)> + '_>,
+> {
+    band.polygons.0.iter().map(|polygon| {
+        let rings: Vec<&LineString<f64>> = std::iter::once(polygon.exterior())
+            .chain(polygon.interiors())
+            .collect();
+        rings.into_iter().map(line_coords)
+    })
+}
+
+fn line_coords(line: &LineString<f64>) -> impl ExactSizeIterator<Item = (f64, f64)> + '_ {
+    line.0.iter().map(|c| (c.x, c.y))
+}
+
+/// WKB number writer for one byte order
+struct WkbWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    order: ByteOrder,
+}
+
+impl WkbWriter<'_> {
+    fn header(&mut self, geometry_type: u32, srid: Option<u32>) {
+        self.buf.push(match self.order {
+            ByteOrder::BigEndian => 0,
+            ByteOrder::LittleEndian => 1,
+        });
+        match srid {
+            Some(srid) => {
+                self.u32(geometry_type | EWKB_SRID_FLAG);
+                self.u32(srid);
+            }
+            None => self.u32(geometry_type),
+        }
+    }
+
+    fn u32(&mut self, v: u32) {
+        match self.order {
+            ByteOrder::BigEndian => self.buf.extend_from_slice(&v.to_be_bytes()),
+            ByteOrder::LittleEndian => self.buf.extend_from_slice(&v.to_le_bytes()),
+        }
+    }
+
+    fn f64(&mut self, v: f64) {
+        match self.order {
+            ByteOrder::BigEndian => self.buf.extend_from_slice(&v.to_be_bytes()),
+            ByteOrder::LittleEndian => self.buf.extend_from_slice(&v.to_le_bytes()),
+        }
+    }
+
+    fn points(&mut self, coords: impl ExactSizeIterator<Item = (f64, f64)>) {
+        self.u32(coords.len() as u32);
+        for (x, y) in coords {
+            self.f64(x);
+            self.f64(y);
+        }
+    }
+}
+
+fn split(format: WkbFormat) -> (ByteOrder, Option<u32>) {
+    match format {
+        WkbFormat::Wkb(order) => (order, None),
+        WkbFormat::Ewkb(order) => (order, Some(SRID_WGS84)),
+    }
+}
+
+/// Write a WKB `MULTIPOLYGON` from polygons given as rings (exterior first)
+fn write_multipolygon_wkb<P, R, C>(buf: &mut Vec<u8>, format: WkbFormat, polygons: P)
+where
+    P: ExactSizeIterator<Item = R>,
+    R: ExactSizeIterator<Item = C>,
+    C: ExactSizeIterator<Item = (f64, f64)>,
+{
+    let (order, srid) = split(format);
+    let mut w = WkbWriter { buf, order };
+    w.header(WKB_MULTIPOLYGON, srid);
+    w.u32(polygons.len() as u32);
+    for rings in polygons {
+        // Member geometries never repeat the SRID
+        w.header(WKB_POLYGON, None);
+        w.u32(rings.len() as u32);
+        for ring in rings {
+            w.points(ring);
+        }
+    }
+}
+
+/// Write a WKB `MULTILINESTRING` from its lines
+fn write_multilinestring_wkb<L, C>(buf: &mut Vec<u8>, format: WkbFormat, lines: L)
+where
+    L: ExactSizeIterator<Item = C>,
+    C: ExactSizeIterator<Item = (f64, f64)>,
+{
+    let (order, srid) = split(format);
+    let mut w = WkbWriter { buf, order };
+    w.header(WKB_MULTILINESTRING, srid);
+    w.u32(lines.len() as u32);
+    for line in lines {
+        w.header(WKB_LINESTRING, None);
+        w.points(line);
+    }
+}
+
+/// Write a WKT `MULTIPOLYGON` from polygons given as rings (exterior first)
+fn write_multipolygon_wkt<P, R, C>(wkt: &mut String, polygons: P)
+where
+    P: ExactSizeIterator<Item = R>,
+    R: ExactSizeIterator<Item = C>,
+    C: Iterator<Item = (f64, f64)>,
+{
+    wkt.push_str("MULTIPOLYGON");
+    if polygons.len() == 0 {
+        wkt.push_str(" EMPTY");
+        return;
+    }
+    wkt.push_str(" (");
+    for (i, rings) in polygons.enumerate() {
+        if i > 0 {
+            wkt.push_str(", ");
+        }
+        wkt.push('(');
+        for (j, ring) in rings.enumerate() {
+            if j > 0 {
+                wkt.push_str(", ");
+            }
+            write_coords_wkt(wkt, ring);
+        }
+        wkt.push(')');
+    }
+    wkt.push(')');
+}
+
+/// Write a WKT `MULTILINESTRING` from its lines
+fn write_multilinestring_wkt<L, C>(wkt: &mut String, lines: L)
+where
+    L: ExactSizeIterator<Item = C>,
+    C: Iterator<Item = (f64, f64)>,
+{
+    wkt.push_str("MULTILINESTRING");
+    if lines.len() == 0 {
+        wkt.push_str(" EMPTY");
+        return;
+    }
+    wkt.push_str(" (");
+    for (i, line) in lines.enumerate() {
+        if i > 0 {
+            wkt.push_str(", ");
+        }
+        write_coords_wkt(wkt, line);
+    }
+    wkt.push(')');
+}
+
+fn write_coords_wkt(wkt: &mut String, coords: impl Iterator<Item = (f64, f64)>) {
+    wkt.push('(');
+    for (i, (x, y)) in coords.enumerate() {
+        if i > 0 {
+            wkt.push_str(", ");
+        }
+        // Writing to a String cannot fail
+        let _ = write!(wkt, "{x} {y}");
+    }
+    wkt.push(')');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{MultiLineString, MultiPolygon, Polygon};
+
+    fn triangle_band() -> Isoband {
+        let exterior = LineString::from(vec![(0.0, 0.0), (4.0, 0.0), (0.0, 4.0), (0.0, 0.0)]);
+        let hole = LineString::from(vec![(1.0, 1.0), (1.0, 2.0), (2.0, 1.0), (1.0, 1.0)]);
+        Isoband {
+            lower: 0.0,
+            upper: 1.0,
+            polygons: MultiPolygon(vec![Polygon::new(exterior, vec![hole])]),
+        }
+    }
+
+    #[test]
+    fn test_wkt() {
+        assert_eq!(
+            triangle_band().to_wkt(),
+            "MULTIPOLYGON (((0 0, 4 0, 0 4, 0 0), (1 1, 1 2, 2 1, 1 1)))"
+        );
+
+        let isoline = Isoline {
+            level: 5.0,
+            lines: MultiLineString(vec![
+                LineString::from(vec![(-100.5, 40.25), (-100.0, 40.0)]),
+                LineString::from(vec![(1.0, 2.0), (3.0, 4.0)]),
+            ]),
+        };
+        assert_eq!(
+            isoline.to_ewkt(),
+            "SRID=4326;MULTILINESTRING ((-100.5 40.25, -100 40), (1 2, 3 4))"
+        );
+
+        let empty = Isoband {
+            lower: 0.0,
+            upper: 1.0,
+            polygons: MultiPolygon(vec![]),
+        };
+        assert_eq!(empty.to_wkt(), "MULTIPOLYGON EMPTY");
+    }
+
+    #[test]
+    fn test_wkb_layout() {
+        let band = triangle_band();
+        let le = band.to_wkb(WkbFormat::Wkb(ByteOrder::LittleEndian));
+
+        // 1+4+4 multipolygon header, 1+4+4 polygon header, 2 rings of 4 points
+        assert_eq!(le.len(), 9 + 9 + 2 * (4 + 4 * 16));
+        assert_eq!(le[0], 1);
+        assert_eq!(le[1..5], 6u32.to_le_bytes());
+        assert_eq!(le[5..9], 1u32.to_le_bytes());
+        assert_eq!(le[9], 1);
+        assert_eq!(le[10..14], 3u32.to_le_bytes());
+        assert_eq!(le[14..18], 2u32.to_le_bytes());
+        assert_eq!(le[18..22], 4u32.to_le_bytes());
+        assert_eq!(le[30..38], 0.0f64.to_le_bytes());
+        assert_eq!(le[38..46], 4.0f64.to_le_bytes());
+
+        let be = band.to_wkb(WkbFormat::Wkb(ByteOrder::BigEndian));
+        assert_eq!(be.len(), le.len());
+        assert_eq!(be[0], 0);
+        assert_eq!(be[1..5], 6u32.to_be_bytes());
+        assert_eq!(be[38..46], 4.0f64.to_be_bytes());
+    }
+
+    #[test]
+    fn test_ewkb_srid() {
+        let isoline = Isoline {
+            level: 5.0,
+            lines: MultiLineString(vec![LineString::from(vec![(1.0, 2.0), (3.0, 4.0)])]),
+        };
+        let ewkb = isoline.to_wkb(WkbFormat::Ewkb(ByteOrder::LittleEndian));
+
+        assert_eq!(ewkb[1..5], (5u32 | EWKB_SRID_FLAG).to_le_bytes());
+        assert_eq!(ewkb[5..9], 4326u32.to_le_bytes());
+        assert_eq!(ewkb[9..13], 1u32.to_le_bytes());
+        // Member linestring has a plain type code
+        assert_eq!(ewkb[14..18], 2u32.to_le_bytes());
+        assert_eq!(ewkb.len(), 13 + 9 + 2 * 16);
+    }
+
+    #[test]
+    fn test_traced_encoding_matches_geometry() {
+        use crate::{GeoGrid, GridPoint};
+
+        let points = (0..5)
+            .map(|row| {
+                (0..5)
+                    .map(|col| {
+                        let (dr, dc) = (row as f32 - 2.0, col as f32 - 2.0);
+                        GridPoint::new(col as f64, -(row as f64), 20.0 - (dr * dr + dc * dc) * 2.0)
+                    })
+                    .collect()
+            })
+            .collect();
+        let grid = GeoGrid::from_points(points).unwrap();
+        let format = WkbFormat::Ewkb(ByteOrder::BigEndian);
+
+        // Ring start vertices depend on tracing order, so compare distinct vertices
+        let vertices = |wkt: &str| {
+            let mut v: Vec<String> = wkt
+                .split(['(', ')', ','])
+                .map(|s| s.trim().to_string())
+                .filter(|s| s.starts_with(|c: char| c.is_ascii_digit() || c == '-'))
+                .collect();
+            v.sort();
+            v.dedup();
+            v
+        };
+
+        let thresholds = [0.0, 8.0, 15.0, 30.0];
+        let bands = grid.isobands_geo(&thresholds).unwrap();
+        let wkb = isobands_wkb(&grid, &thresholds, format).unwrap();
+        let wkt = isobands_wkt(&grid, &thresholds).unwrap();
+        assert_eq!(wkb.len(), bands.len());
+        for ((band, wkb), wkt) in bands.iter().zip(&wkb).zip(&wkt) {
+            assert_eq!((wkb.lower, wkb.upper), (band.lower, band.upper));
+            assert_eq!(wkb.geometry.len(), band.to_wkb(format).len());
+            assert_eq!(vertices(&wkt.geometry), vertices(&band.to_wkt()));
+        }
+
+        let levels = [5.0, 12.0, 50.0];
+        let lines = grid.isolines_geo(&levels).unwrap();
+        let wkb = isolines_wkb(&grid, &levels, format).unwrap();
+        let wkt = isolines_wkt(&grid, &levels).unwrap();
+        assert_eq!(wkb.len(), 2);
+        for ((line, wkb), wkt) in lines.iter().zip(&wkb).zip(&wkt) {
+            assert_eq!(wkb.level, line.level);
+            assert_eq!(wkb.geometry, line.to_wkb(format));
+            assert_eq!(wkt.geometry, line.to_wkt());
+        }
+    }
+}
@@ -0,0 +1,710 @@
+//! Color palettes with standard meteorological scales
+//!
+//! A [`Palette`] is a list of value segments, each with a solid or gradient
+//! color. Its segment boundaries are the isoband thresholds, so one palette
+//! defines both the contour levels and their styling:
+//!
+//! - [`Palette::thresholds`] gives the threshold list for
+//!   [`GeoGrid::isobands`](crate::GeoGrid::isobands)
+//! - [`Palette::style_features`] writes `fill`/`stroke` properties
+//!   (simplestyle) onto the generated features
+//! - [`Palette::band_descriptors`] builds [`BandDescriptor`]s carrying the
+//!   same properties
+//! - [`Palette`] implements [`ColorMap`] for the KML, SVG and PNG outputs
+//!
+//! Built-in scales cover NWS reflectivity, temperature and precipitation, and
+//! the viridis/cividis colormaps over any threshold list. Palettes can also be
+//! read from GR2Analyst `.pal` files ([`Palette::from_pal`]) and GMT `.cpt`
+//! files ([`Palette::from_cpt`]).
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::palette::Palette;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint};
+//!
+//! let palette = Palette::from_pal(
+//!     "Units: dBZ
+//!      SolidColor: 20 2 253 2
+//!      SolidColor: 35 253 248 2
+//!      SolidColor: 50 253 0 0
+//!      Color: 65 248 0 253",
+//! )?;
+//! assert_eq!(palette.thresholds(), vec![20.0, 35.0, 50.0, 65.0]);
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 40.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 30.0), GridPoint::new(-99.0, 40.0, 60.0)],
+//! ])?;
+//! let mut features = grid.isobands(&palette.thresholds())?;
+//! palette.style_features(&mut features);
+//! assert_eq!(features[0].property("fill"), Some(&serde_json::json!("#02fd02")));
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::bands::BandDescriptor;
+use crate::color::{Color, ColorMap, ColorRamp};
+use crate::error::{Error, Result};
+use geojson::{Feature, JsonObject};
+use serde_json::json;
+
+/// A palette segment from `lower` to `upper`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaletteSegment {
+    /// Lower bound of the segment (inclusive)
+    pub lower: f64,
+    /// Upper bound of the segment (exclusive)
+    pub upper: f64,
+    /// Color at `lower`
+    pub start: Color,
+    /// Color approaching `upper`; equal to `start` for solid segments
+    pub end: Color,
+}
+
+impl PaletteSegment {
+    /// A segment with a single color
+    pub fn solid(lower: f64, upper: f64, color: Color) -> Self {
+        Self::gradient(lower, upper, color, color)
+    }
+
+    /// A segment blending from `start` to `end`
+    pub fn gradient(lower: f64, upper: f64, start: Color, end: Color) -> Self {
+        Self {
+            lower,
+            upper,
+            start,
+            end,
+        }
+    }
+
+    /// Color at a value, clamped to the segment
+    pub fn color_at(&self, value: f64) -> Color {
+        self.start
+            .lerp(self.end, (value - self.lower) / (self.upper - self.lower))
+    }
+}
+
+/// Ordered palette segments with optional out-of-range colors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    segments: Vec<PaletteSegment>,
+    below: Option<Color>,
+    above: Option<Color>,
+    units: Option<String>,
+}
+
+impl Palette {
+    /// Create a palette from ascending, non-overlapping segments
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no segments, a segment does not have
+    /// finite `lower < upper`, or segments overlap or are out of order
+    pub fn new(segments: Vec<PaletteSegment>) -> Result<Self> {
+        if segments.is_empty() {
+            return Err(Error::invalid_color("Palette needs at least one segment"));
+        }
+        for segment in &segments {
+            if !(segment.lower.is_finite() && segment.upper.is_finite())
+                || segment.lower >= segment.upper
+            {
+                return Err(Error::invalid_color(format!(
+                    "Palette segment must have finite lower < upper, got {} to {}",
+                    segment.lower, segment.upper
+                )));
+            }
+        }
+        if segments.windows(2).any(|w| w[1].lower < w[0].upper) {
+            return Err(Error::invalid_color(
+                "Palette segments must be ascending and must not overlap",
+            ));
+        }
+        Ok(Self {
+            segments,
+            below: None,
+            above: None,
+            units: None,
+        })
+    }
+
+    /// Create a palette of solid bands between consecutive thresholds
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless there is exactly one color per band and the
+    /// thresholds are strictly ascending
+    pub fn stepped(thresholds: &[f64], colors: &[Color]) -> Result<Self> {
+        if thresholds.len() != colors.len() + 1 {
+            return Err(Error::invalid_color(format!(
+                "Stepped palette needs one color per band: {} thresholds, {} colors",
+                thresholds.len(),
+                colors.len()
+            )));
+        }
+        Self::new(
+            thresholds
+                .windows(2)
+                .zip(colors)
+                .map(|(pair, &color)| PaletteSegment::solid(pair[0], pair[1], color))
+                .collect(),
+        )
+    }
+
+    /// Color for values below the first segment
+    pub fn with_below(mut self, color: Color) -> Self {
+        self.below = Some(color);
+        self
+    }
+
+    /// Color for values at or above the end of the last segment
+    pub fn with_above(mut self, color: Color) -> Self {
+        self.above = Some(color);
+        self
+    }
+
+    /// Set the units the palette values are in
+    pub fn with_units(mut self, units: impl Into<String>) -> Self {
+        self.units = Some(units.into());
+        self
+    }
+
+    /// Get the palette segments
+    pub fn segments(&self) -> &[PaletteSegment] {
+        &self.segments
+    }
+
+    /// Get the units, if known
+    pub fn units(&self) -> Option<&str> {
+        self.units.as_deref()
+    }
+
+    /// Segment boundaries, ascending, for use as isoband thresholds
+    pub fn thresholds(&self) -> Vec<f64> {
+        let mut thresholds = Vec::with_capacity(self.segments.len() + 1);
+        for segment in &self.segments {
+            if thresholds.last() != Some(&segment.lower) {
+                thresholds.push(segment.lower);
+            }
+            thresholds.push(segment.upper);
+        }
+        thresholds
+    }
+
+    /// Color at a value
+    ///
+    /// Values outside the palette take the below/above colors if set, and the
+    /// nearest segment's color otherwise. Values in a gap between segments
+    /// take the end color of the segment before the gap. NaN, such as the
+    /// midpoint of a band from -inf to inf, is colored like values below.
+    pub fn color_at(&self, value: f64) -> Color {
+        let first = &self.segments[0];
+        let last = &self.segments[self.segments.len() - 1];
+        if value.is_nan() || value < first.lower {
+            return self.below.unwrap_or(first.start);
+        }
+        if value >= last.upper {
+            return self.above.unwrap_or(last.end);
+        }
+        let i = self.segments.partition_point(|s| s.lower <= value);
+        self.segments[i - 1].color_at(value)
+    }
+
+    /// Add `fill`/`stroke` style properties to generated features
+    ///
+    /// Isobands (features with `lower_level`/`upper_level`) get `fill`,
+    /// `fill-opacity` and `stroke`; isolines (features with `isovalue`) get
+    /// `stroke` and `stroke-opacity`. Other features are left unchanged.
+    pub fn style_features(&self, features: &mut [Feature]) {
+        for feature in features {
+            let level = |name: &str| feature.property(name).and_then(|v| v.as_f64());
+            let style = match (
+                level("lower_level"),
+                level("upper_level"),
+                level("isovalue"),
+            ) {
+                (Some(lower), Some(upper), _) => band_style(self.band_color(lower, upper)),
+                (_, _, Some(level)) => line_style(self.level_color(level)),
+                _ => continue,
+            };
+            feature
+                .properties
+                .get_or_insert_with(JsonObject::new)
+                .extend(style);
+        }
+    }
+
+    /// Band descriptors for the palette's bands, carrying their style
+    /// properties
+    pub fn band_descriptors(&self) -> Vec<BandDescriptor> {
+        BandDescriptor::from_thresholds(&self.thresholds(), |_, lower, upper| {
+            band_style(self.band_color(lower, upper))
+        })
+    }
+
+    /// NWS base reflectivity, 5 to 80 dBZ in 5 dBZ steps
+    pub fn nws_reflectivity() -> Self {
+        const COLORS: [u32; 15] = [
+            0x04e9e7, 0x019ff4, 0x0300f4, 0x02fd02, 0x01c501, 0x008e00, 0xfdf802, 0xe5bc00,
+            0xfd9500, 0xfd0000, 0xd40000, 0xbc0000, 0xf800fd, 0x9854c6, 0xfdfdfd,
+        ];
+        let thresholds: Vec<f64> = (1..=16).map(|i| f64::from(i) * 5.0).collect();
+        builtin(&thresholds, &COLORS, "dBZ")
+    }
+
+    /// Surface temperature, -40 to 45 °C in 5 °C steps
+    pub fn temperature() -> Self {
+        const COLORS: [u32; 17] = [
+            0x4b0082, 0x6a0dad, 0x8a2be2, 0x4040ff, 0x1e64ff, 0x3296ff, 0x50c8ff, 0x78e6f0,
+            0xa0f0a0, 0x64d264, 0x32b432, 0xc8e632, 0xfff032, 0xffc828, 0xff9620, 0xff5a14,
+            0xe01e0a,
+        ];
+        let thresholds: Vec<f64> = (-8..=9).map(|i| f64::from(i) * 5.0).collect();
+        builtin(&thresholds, &COLORS, "°C")
+    }
+
+    /// Accumulated precipitation, 0.25 to 250 mm
+    pub fn precipitation() -> Self {
+        const THRESHOLDS: [f64; 16] = [
+            0.25, 2.5, 5.0, 10.0, 15.0, 20.0, 25.0, 35.0, 50.0, 65.0, 75.0, 100.0, 125.0, 150.0,
+            200.0, 250.0,
+        ];
+        const COLORS: [u32; 15] = [
+            0xc1e9c0, 0x7cc87a, 0x31a354, 0x006d2c, 0xfff7a3, 0xfec44f, 0xfe9929, 0xec7014,
+            0xd7301f, 0xb30000, 0x7f0000, 0xdd3497, 0xae017e, 0x7a0177, 0xf2d9ff,
+        ];
+        builtin(&THRESHOLDS, &COLORS, "mm")
+    }
+
+    /// Viridis colors for the bands between consecutive thresholds
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless there are at least 2 strictly ascending
+    /// thresholds
+    pub fn viridis(thresholds: &[f64]) -> Result<Self> {
+        sampled(
+            thresholds,
+            &[
+                0x440154, 0x472d7b, 0x3b528b, 0x2c728e, 0x21918c, 0x28ae80, 0x5ec962, 0xaddc30,
+                0xfde725,
+            ],
+        )
+    }
+
+    /// Cividis colors for the bands between consecutive thresholds
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless there are at least 2 strictly ascending
+    /// thresholds
+    pub fn cividis(thresholds: &[f64]) -> Result<Self> {
+        sampled(
+            thresholds,
+            &[
+                0x00224e, 0x123570, 0x3b496c, 0x575d6d, 0x707173, 0x8a8779, 0xa69d75, 0xc4b56c,
+                0xfee838,
+            ],
+        )
+    }
+
+    /// Parse a GR2Analyst `.pal` color table
+    ///
+    /// Reads `Units:`, `Color:`, `Color4:`, `SolidColor:` and `SolidColor4:`
+    /// entries; other keys are ignored, and `Scale`/`Offset` are not applied.
+    /// Each entry starts a segment that runs to the next entry's value.
+    /// A `Color` entry blends to its second color if given, or else to the
+    /// next entry's color. The last entry ends the palette and colors values
+    /// above it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on malformed entries or fewer than 2 distinct values
+    pub fn from_pal(text: &str) -> Result<Self> {
+        struct Entry {
+            value: f64,
+            start: Color,
+            end: Option<Color>,
+        }
+
+        let mut entries = Vec::new();
+        let mut units = None;
+        for (index, raw) in text.lines().enumerate() {
+            let line_no = index + 1;
+            let line = raw.split(';').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, rest)) = line.split_once(':') else {
+                return Err(Error::invalid_palette(line_no, "Expected `Key: value`"));
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let (channels, solid) = match key.as_str() {
+                "units" => {
+                    units = Some(rest.trim().to_string());
+                    continue;
+                }
+                "color" => (3, false),
+                "color4" => (4, false),
+                "solidcolor" => (3, true),
+                "solidcolor4" => (4, true),
+                _ => continue,
+            };
+
+            let numbers = rest
+                .split_whitespace()
+                .map(|t| t.parse::<f64>())
+                .collect::<std::result::Result<Vec<f64>, _>>()
+                .map_err(|_| Error::invalid_palette(line_no, "Expected numbers"))?;
+            let colors = numbers.len().saturating_sub(1) / channels;
+            if numbers.len() != 1 + colors * channels || colors == 0 || colors > 2 - solid as usize
+            {
+                return Err(Error::invalid_palette(
+                    line_no,
+                    format!("Expected a value and {channels} color channels per color"),
+                ));
+            }
+            let color = |i: usize| -> Result<Color> {
+                let c = &numbers[1 + i * channels..1 + (i + 1) * channels];
+                let alpha = c.get(3).copied().unwrap_or(255.0);
+                Ok(Color::rgba(
+                    channel(c[0], line_no)?,
+                    channel(c[1], line_no)?,
+                    channel(c[2], line_no)?,
+                    channel(alpha, line_no)?,
+                ))
+            };
+            let start = color(0)?;
+            let end = if solid {
+                Some(start)
+            } else if colors == 2 {
+                Some(color(1)?)
+            } else {
+                None
+            };
+            if !numbers[0].is_finite() {
+                return Err(Error::invalid_palette(line_no, "Value must be finite"));
+            }
+            entries.push(Entry {
+                value: numbers[0],
+                start,
+                end,
+            });
+        }
+
+        entries.sort_by(|a, b| a.value.total_cmp(&b.value));
+        let segments: Vec<PaletteSegment> = entries
+            .windows(2)
+            .filter(|pair| pair[0].value < pair[1].value)
+            .map(|pair| {
+                let end = pair[0].end.unwrap_or(pair[1].start);
+                PaletteSegment::gradient(pair[0].value, pair[1].value, pair[0].start, end)
+            })
+            .collect();
+        if segments.is_empty() {
+            return Err(Error::invalid_palette(
+                text.lines().count(),
+                "Palette needs at least 2 distinct values",
+            ));
+        }
+
+        let mut palette = Self::new(segments)?;
+        palette.above = entries.last().map(|e| e.start);
+        palette.units = units.filter(|u| !u.is_empty());
+        Ok(palette)
+    }
+
+    /// Parse a GMT `.cpt` color palette table
+    ///
+    /// Segment lines are `z0 color z1 color`, where a color is `r g b`,
+    /// `r/g/b`, `#rrggbb` or a gray level; trailing `L`/`U`/`B` flags and
+    /// `;` labels are ignored. `B` and `F` lines set the below and above
+    /// colors. Only the RGB color model is supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on malformed lines, other color models, or
+    /// overlapping segments
+    pub fn from_cpt(text: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let (mut below, mut above) = (None, None);
+        for (index, raw) in text.lines().enumerate() {
+            let line_no = index + 1;
+            let line = raw.trim();
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some((key, model)) = comment.split_once('=') {
+                    let model = model.trim();
+                    if key.trim() == "COLOR_MODEL"
+                        && !model.eq_ignore_ascii_case("RGB")
+                        && !model.eq_ignore_ascii_case("+RGB")
+                    {
+                        return Err(Error::invalid_palette(
+                            line_no,
+                            format!("Unsupported color model {model}"),
+                        ));
+                    }
+                }
+                continue;
+            }
+            let line = line.split(';').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens[0] {
+                "B" | "F" | "N" => {
+                    let color = cpt_color(&tokens[1..], line_no)?;
+                    match tokens[0] {
+                        "B" => below = Some(color),
+                        "F" => above = Some(color),
+                        _ => {}
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            if matches!(tokens.last(), Some(&("L" | "U" | "B"))) {
+                tokens.pop();
+            }
+            let (z0, c0, z1, c1) = match tokens.len() {
+                8 => (tokens[0], &tokens[1..4], tokens[4], &tokens[5..8]),
+                4 => (tokens[0], &tokens[1..2], tokens[2], &tokens[3..4]),
+                _ => {
+                    return Err(Error::invalid_palette(
+                        line_no,
+                        "Expected `z0 color z1 color`",
+                    ))
+                }
+            };
+            let value = |t: &str| {
+                t.parse::<f64>()
+                    .map_err(|_| Error::invalid_palette(line_no, format!("Invalid value {t:?}")))
+            };
+            segments.push(PaletteSegment::gradient(
+                value(z0)?,
+                value(z1)?,
+                cpt_color(c0, line_no)?,
+                cpt_color(c1, line_no)?,
+            ));
+        }
+
+        let mut palette = Self::new(segments)
+            .map_err(|e| Error::invalid_palette(text.lines().count(), e.to_string()))?;
+        palette.below = below;
+        palette.above = above;
+        Ok(palette)
+    }
+}
+
+impl ColorMap for Palette {
+    fn band_color(&self, lower: f64, upper: f64) -> Color {
+        self.color_at((lower + upper) / 2.0)
+    }
+
+    fn level_color(&self, level: f64) -> Color {
+        self.color_at(level)
+    }
+}
+
+fn builtin(thresholds: &[f64], colors: &[u32], units: &str) -> Palette {
+    let colors: Vec<Color> = colors.iter().map(|&c| rgb_hex(c)).collect();
+    Palette::stepped(thresholds, &colors)
+        .expect("built-in palette is valid")
+        .with_units(units)
+}
+
+fn sampled(thresholds: &[f64], colormap: &[u32]) -> Result<Palette> {
+    let bands = thresholds.len().saturating_sub(1);
+    if bands == 0 {
+        return Err(Error::invalid_color("Palette needs at least 2 thresholds"));
+    }
+    let colors: Vec<Color> = colormap.iter().map(|&c| rgb_hex(c)).collect();
+    let ramp = ColorRamp::evenly_spaced(0.0, 1.0, &colors)?;
+    let colors: Vec<Color> = (0..bands)
+        .map(|i| {
+            ramp.color_at(if bands > 1 {
+                i as f64 / (bands - 1) as f64
+            } else {
+                0.5
+            })
+        })
+        .collect();
+    Palette::stepped(thresholds, &colors)
+}
+
+fn rgb_hex(c: u32) -> Color {
+    Color::rgb((c >> 16) as u8, (c >> 8) as u8, c as u8)
+}
+
+/// Parse a 0-255 color channel
+fn channel(value: f64, line: usize) -> Result<u8> {
+    if !(0.0..=255.0).contains(&value) {
+        return Err(Error::invalid_palette(
+            line,
+            format!("Color channel {value} out of range 0-255"),
+        ));
+    }
+    Ok(value.round() as u8)
+}
+
+/// Parse a `.cpt` color from one token (`r/g/b`, `#hex`, gray) or three
+fn cpt_color(tokens: &[&str], line: usize) -> Result<Color> {
+    let number = |t: &str| {
+        t.parse::<f64>()
+            .map_err(|_| Error::invalid_palette(line, format!("Invalid color {t:?}")))
+            .and_then(|v| channel(v, line))
+    };
+    match tokens {
+        [r, g, b] => Ok(Color::rgb(number(r)?, number(g)?, number(b)?)),
+        [hex] if hex.starts_with('#') => {
+            Color::from_hex(hex).map_err(|e| Error::invalid_palette(line, e.to_string()))
+        }
+        [rgb] if rgb.contains('/') => match rgb.split('/').collect::<Vec<_>>()[..] {
+            [r, g, b] => Ok(Color::rgb(number(r)?, number(g)?, number(b)?)),
+            _ => Err(Error::invalid_palette(
+                line,
+                format!("Invalid color {rgb:?}"),
+            )),
+        },
+        [gray] => {
+            let v = number(gray)?;
+            Ok(Color::rgb(v, v, v))
+        }
+        _ => Err(Error::invalid_palette(line, "Expected a color")),
+    }
+}
+
+fn band_style(color: Color) -> JsonObject {
+    let hex = color.with_alpha(255).to_hex();
+    JsonObject::from_iter([
+        ("fill".to_string(), json!(hex)),
+        ("fill-opacity".to_string(), json!(opacity(color))),
+        ("stroke".to_string(), json!(hex)),
+    ])
+}
+
+fn line_style(color: Color) -> JsonObject {
+    JsonObject::from_iter([
+        ("stroke".to_string(), json!(color.with_alpha(255).to_hex())),
+        ("stroke-opacity".to_string(), json!(opacity(color))),
+    ])
+}
+
+fn opacity(color: Color) -> f64 {
+    (f64::from(color.a) / 255.0 * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pal() {
+        let palette = Palette::from_pal(
+            "; velocity-style table\n\
+             Product: BR\n\
+             Units: dBZ\n\
+             Step: 5\n\
+             Color: 10 0 0 0\n\
+             SolidColor: 20 0 255 0 ; solid band\n\
+             Color4: 30 255 0 0 128 255 255 0 255\n\
+             Color: 40 255 255 255\n",
+        )
+        .unwrap();
+        assert_eq!(palette.units(), Some("dBZ"));
+        assert_eq!(palette.thresholds(), vec![10.0, 20.0, 30.0, 40.0]);
+        // Gradient to the next entry's color
+        assert_eq!(palette.color_at(15.0), Color::rgb(0, 128, 0));
+        assert_eq!(palette.color_at(25.0), Color::rgb(0, 255, 0));
+        // Gradient to the entry's own second color, with alpha
+        assert_eq!(palette.color_at(30.0), Color::rgba(255, 0, 0, 128));
+        assert_eq!(palette.color_at(50.0), Color::rgb(255, 255, 255));
+        assert_eq!(palette.color_at(0.0), Color::rgb(0, 0, 0));
+
+        assert!(Palette::from_pal("Color: 10 0 0").is_err());
+        assert!(Palette::from_pal("Color: 10 0 0 0").is_err());
+        assert!(matches!(
+            Palette::from_pal("Units: mm\nColor: 1 0 0 300\nColor: 2 0 0 0"),
+            Err(Error::InvalidPalette { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_cpt() {
+        let palette = Palette::from_cpt(
+            "# COLOR_MODEL = RGB\n\
+             -10 0 0 255 0 0 255 255 L\n\
+             0 0/255/0 10 #ff0000 ; warm\n\
+             10 128 20 128 U\n\
+             B 0 0 0\n\
+             F 255/255/255\n\
+             N 128 128 128\n",
+        )
+        .unwrap();
+        assert_eq!(palette.thresholds(), vec![-10.0, 0.0, 10.0, 20.0]);
+        assert_eq!(palette.color_at(-5.0), Color::rgb(0, 128, 255));
+        assert_eq!(palette.color_at(5.0), Color::rgb(128, 128, 0));
+        assert_eq!(palette.color_at(15.0), Color::rgb(128, 128, 128));
+        assert_eq!(palette.color_at(-20.0), Color::rgb(0, 0, 0));
+        assert_eq!(palette.color_at(20.0), Color::rgb(255, 255, 255));
+
+        assert!(Palette::from_cpt("# COLOR_MODEL = HSV\n0 0-1-1 1 0-1-1").is_err());
+        assert!(Palette::from_cpt("0 0 0 0 1").is_err());
+        assert!(Palette::from_cpt("0 0 5 0\n4 0 8 0").is_err());
+    }
+
+    #[test]
+    fn test_builtins_and_styling() {
+        let reflectivity = Palette::nws_reflectivity();
+        assert_eq!(reflectivity.thresholds().len(), 16);
+        assert_eq!(reflectivity.band_color(50.0, 55.0), Color::rgb(0xfd, 0, 0));
+        assert_eq!(Palette::temperature().thresholds()[0], -40.0);
+        assert_eq!(Palette::precipitation().units(), Some("mm"));
+
+        let viridis = Palette::viridis(&[0.0, 1.0, 2.0, 3.0]).unwrap();
+        assert_eq!(viridis.color_at(0.5), Color::rgb(0x44, 0x01, 0x54));
+        assert_eq!(viridis.color_at(2.5), Color::rgb(0xfd, 0xe7, 0x25));
+        assert!(Palette::cividis(&[1.0]).is_err());
+
+        let mut features = vec![
+            Feature {
+                properties: Some(JsonObject::from_iter([
+                    ("lower_level".to_string(), json!(1.0)),
+                    ("upper_level".to_string(), json!(2.0)),
+                ])),
+                ..Feature::default()
+            },
+            Feature {
+                properties: Some(JsonObject::from_iter([(
+                    "isovalue".to_string(),
+                    json!(0.0),
+                )])),
+                ..Feature::default()
+            },
+        ];
+        let half = Palette::stepped(&[0.0, 2.0], &[Color::rgba(255, 0, 0, 128)]).unwrap();
+        half.style_features(&mut features);
+        assert_eq!(features[0].property("fill"), Some(&json!("#ff0000")));
+        assert_eq!(features[0].property("fill-opacity"), Some(&json!(0.502)));
+        assert_eq!(features[1].property("stroke"), Some(&json!("#ff0000")));
+        assert_eq!(features[1].property("fill"), None);
+
+        let descriptors = viridis.band_descriptors();
+        assert_eq!(descriptors.len(), 3);
+        assert_eq!(descriptors[2].properties["fill"], json!("#fde725"));
+    }
+
+    #[test]
+    fn test_non_finite_values() {
+        let (red, blue) = (Color::rgb(255, 0, 0), Color::rgb(0, 0, 255));
+        let palette = Palette::stepped(&[0.0, 1.0, 2.0], &[red, blue]).unwrap();
+        assert_eq!(palette.color_at(f64::NAN), red);
+        assert_eq!(palette.band_color(f64::NEG_INFINITY, f64::INFINITY), red);
+        assert_eq!(palette.band_color(f64::NEG_INFINITY, 0.5), red);
+        assert_eq!(palette.band_color(1.5, f64::INFINITY), blue);
+
+        let black = Color::rgb(0, 0, 0);
+        let clamped = palette.with_below(black);
+        assert_eq!(clamped.band_color(f64::NEG_INFINITY, f64::INFINITY), black);
+    }
+}
@@ -3,6 +3,7 @@
 //! Implements point-in-polygon testing and polygon nesting organization
 
 use crate::types::Point;
+use std::collections::VecDeque;
 
 /// Test if a point is inside a polygon using ray casting algorithm
 ///
@@ -55,43 +56,38 @@ pub fn polygon_in_polygon(subject: &[Point], polygon: &[Point]) -> bool {
 /// Organize a list of polygon rings into properly nested structures
 ///
 /// Returns Vec<(exterior_ring, Vec<interior_rings>)>
-pub fn organize_polygons(mut rings: Vec<Vec<Point>>) -> Vec<(Vec<Point>, Vec<Vec<Point>>)> {
-    // Debug: Check input rings for long segments
-    for (idx, ring) in rings.iter().enumerate() {
-        for i in 0..ring.len().saturating_sub(1) {
-            let p1 = &ring[i];
-            let p2 = &ring[i + 1];
-            if let (Some(x1), Some(y1), Some(x2), Some(y2)) = (p1.x, p1.y, p2.x, p2.y) {
-                let seg_len = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
-                if seg_len > 10.0 {
-                    eprintln!("🚨 LONG SEGMENT IN INPUT RING {}: segment {} from ({:.6},{:.6}) to ({:.6},{:.6}), length={:.2}°",
-                        idx, i, x1, y1, x2, y2, seg_len);
-                }
-            }
-        }
-    }
+pub fn organize_polygons(rings: Vec<Vec<Point>>) -> Vec<(Vec<Point>, Vec<Vec<Point>>)> {
+    let nested = nest_rings(&rings);
+    let mut rings: Vec<Option<Vec<Point>>> = rings.into_iter().map(Some).collect();
+    let mut take = |i: usize| rings[i].take().unwrap_or_default();
+
+    nested
+        .into_iter()
+        .map(|(exterior, holes)| (take(exterior), holes.into_iter().map(&mut take).collect()))
+        .collect()
+}
 
-    let mut result: Vec<(Vec<Point>, Vec<Vec<Point>>)> = Vec::new();
+/// Nest polygon rings like [`organize_polygons`], by index into `rings`
+///
+/// Returns Vec<(exterior_index, Vec<interior_indices>)>
+pub fn nest_rings(rings: &[Vec<Point>]) -> Vec<(usize, Vec<usize>)> {
+    let mut queue: VecDeque<usize> = (0..rings.len()).collect();
+    let mut result: Vec<(usize, Vec<usize>)> = Vec::new();
 
-    while !rings.is_empty() {
-        let subject = rings.remove(0);
+    while let Some(subject) = queue.pop_front() {
         let mut found_parent = false;
 
         // Check if this polygon is inside any existing polygon
         for (exterior, interior_rings) in result.iter_mut() {
-            if polygon_in_polygon(&subject, exterior) {
+            if polygon_in_polygon(&rings[subject], &rings[*exterior]) {
                 // Check if it's inside any of the interior rings (holes)
-                let mut inside_hole = false;
-                for hole in interior_rings.iter() {
-                    if polygon_in_polygon(&subject, hole) {
-                        inside_hole = true;
-                        break;
-                    }
-                }
+                let inside_hole = interior_rings
+                    .iter()
+                    .any(|&hole| polygon_in_polygon(&rings[subject], &rings[hole]));
 
                 if !inside_hole {
                     // It's a hole in the exterior polygon
-                    interior_rings.push(subject.clone());
+                    interior_rings.push(subject);
                     found_parent = true;
                     break;
                 }
@@ -107,20 +103,14 @@ pub fn organize_polygons(mut rings: Vec<Vec<Point>>) -> Vec<(Vec<Point>, Vec<Vec
         // Check if any existing polygons should be inside this one
         let mut i = 0;
         while i < result.len() {
-            let (existing_exterior, _existing_holes) = &result[i];
+            let existing_exterior = result[i].0;
 
-            if polygon_in_polygon(existing_exterior, &subject) {
+            if polygon_in_polygon(&rings[existing_exterior], &rings[subject]) {
                 // This existing polygon should be a child of subject
-                // Remove it and we'll re-process
-                let removed = result.remove(i);
-
-                // Put the exterior back into rings for reprocessing
-                rings.push(removed.0);
-
-                // Put all its holes back too
-                for hole in removed.1 {
-                    rings.push(hole);
-                }
+                // Remove it and we'll re-process its exterior and holes
+                let (exterior, holes) = result.remove(i);
+                queue.push_back(exterior);
+                queue.extend(holes);
 
                 // Don't increment i since we removed an element
             } else {
@@ -135,36 +125,6 @@ pub fn organize_polygons(mut rings: Vec<Vec<Point>>) -> Vec<(Vec<Point>, Vec<Vec
         }
     }
 
-    // Debug: Check output polygons for long segments
-    for (poly_idx, (exterior, holes)) in result.iter().enumerate() {
-        // Check exterior ring
-        for i in 0..exterior.len().saturating_sub(1) {
-            let p1 = &exterior[i];
-            let p2 = &exterior[i + 1];
-            if let (Some(x1), Some(y1), Some(x2), Some(y2)) = (p1.x, p1.y, p2.x, p2.y) {
-                let seg_len = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
-                if seg_len > 10.0 {
-                    eprintln!("🚨 LONG SEGMENT IN OUTPUT POLYGON {} EXTERIOR: segment {} from ({:.6},{:.6}) to ({:.6},{:.6}), length={:.2}°",
-                        poly_idx, i, x1, y1, x2, y2, seg_len);
-                }
-            }
-        }
-        // Check hole rings
-        for (hole_idx, hole) in holes.iter().enumerate() {
-            for i in 0..hole.len().saturating_sub(1) {
-                let p1 = &hole[i];
-                let p2 = &hole[i + 1];
-                if let (Some(x1), Some(y1), Some(x2), Some(y2)) = (p1.x, p1.y, p2.x, p2.y) {
-                    let seg_len = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
-                    if seg_len > 10.0 {
-                        eprintln!("🚨 LONG SEGMENT IN OUTPUT POLYGON {} HOLE {}: segment {} from ({:.6},{:.6}) to ({:.6},{:.6}), length={:.2}°",
-                            poly_idx, hole_idx, i, x1, y1, x2, y2, seg_len);
-                    }
-                }
-            }
-        }
-    }
-
     result
 }
 
@@ -0,0 +1,362 @@
+//! Grid refinement (upsampling) before contouring
+//!
+//! On coarse grids (e.g. 0.25° GFS) contours look angular even with cosine easing,
+//! because every vertex lies on a cell edge. Refining the grid N times before
+//! contouring places vertices on a denser lattice and gives visibly smoother rings.
+//!
+//! [`RefinedGrid`] is a lazy view: each refined point is interpolated from the source
+//! grid when the contouring pipeline reads it, so the refined grid points are never
+//! stored. Tracing a band still builds the cell shapes of every refined cell in the
+//! contoured window, so per-band memory grows with the refined cell count (16x for a
+//! 4x refinement); use a clip box to bound it on large grids. Values and coordinates
+//! are interpolated with the same kernel, so curvilinear (projected) grids refine
+//! correctly.
+
+use crate::error::{Error, Result};
+use crate::grid::{validate_levels, validate_thresholds, GeoGrid, GridSource};
+use crate::marching_squares::{generate_isobands, generate_isolines};
+use crate::types::{GridPoint, MarchingSquaresConfig};
+use geojson::Feature;
+
+/// Interpolation kernel used to refine a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum RefineMethod {
+    /// Bilinear interpolation within each cell (fast, never overshoots)
+    #[default]
+    Bilinear,
+    /// Bicubic (Catmull-Rom) interpolation over a 4x4 neighborhood (smoother,
+    /// may slightly overshoot the source values near sharp gradients)
+    Bicubic,
+}
+
+/// A lazily refined view of another grid
+///
+/// For a source grid of `rows x cols` and a factor `n`, the refined grid has
+/// `(rows - 1) * n + 1` rows and `(cols - 1) * n + 1` columns. Every `n`-th refined
+/// point coincides with a source point.
+///
+/// # Example
+///
+/// ```rust
+/// # use geo_marching_squares_rs::{GeoGrid, GridPoint};
+/// use geo_marching_squares_rs::refine::RefineMethod;
+///
+/// # let grid = GeoGrid::from_points(vec![
+/// #     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+/// #     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+/// # ])?;
+/// let refined = grid.refine(4, RefineMethod::Bicubic)?;
+/// assert_eq!(refined.rows(), 5);
+///
+/// let isobands = refined.isobands(&[12.0, 18.0, 22.0])?;
+/// # Ok::<(), geo_marching_squares_rs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RefinedGrid<'a, G: GridSource + ?Sized> {
+    /// The grid being refined
+    source: &'a G,
+    /// Refinement factor (refined cells per source cell along each axis)
+    factor: usize,
+    /// Interpolation kernel
+    method: RefineMethod,
+    /// Configuration for marching squares algorithm
+    config: MarchingSquaresConfig,
+}
+
+impl<'a, G: GridSource + ?Sized> RefinedGrid<'a, G> {
+    /// Create a refined view of `source`
+    ///
+    /// The view inherits the source grid's configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `factor` is zero
+    pub fn new(source: &'a G, factor: usize, method: RefineMethod) -> Result<Self> {
+        if factor == 0 {
+            return Err(Error::invalid_dimensions(
+                "Refinement factor must be at least 1",
+            ));
+        }
+
+        Ok(Self {
+            source,
+            factor,
+            method,
+            config: source.config().clone(),
+        })
+    }
+
+    /// Get the number of rows in the refined grid
+    pub fn rows(&self) -> usize {
+        (self.source.rows() - 1) * self.factor + 1
+    }
+
+    /// Get the number of columns in the refined grid
+    pub fn cols(&self) -> usize {
+        (self.source.cols() - 1) * self.factor + 1
+    }
+
+    /// Get the refinement factor
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Get the interpolation kernel
+    pub fn method(&self) -> RefineMethod {
+        self.method
+    }
+
+    /// Get the refined grid point at a specific position
+    ///
+    /// Returns `None` if indices are out of bounds
+    pub fn get(&self, row: usize, col: usize) -> Option<GridPoint> {
+        if row < self.rows() && col < self.cols() {
+            Some(GridSource::point(self, row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Get a reference to the configuration
+    pub fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+
+    /// Get a mutable reference to the configuration
+    pub fn config_mut(&mut self) -> &mut MarchingSquaresConfig {
+        &mut self.config
+    }
+
+    /// Compute every refined point into an owned [`GeoGrid`]
+    ///
+    /// This allocates the full refined grid; contour the view directly to avoid it.
+    pub fn to_geo_grid(&self) -> Result<GeoGrid> {
+        let points = (0..self.rows())
+            .map(|row| {
+                (0..self.cols())
+                    .map(|col| GridSource::point(self, row, col))
+                    .collect()
+            })
+            .collect();
+        GeoGrid::from_points_with_config(points, self.config.clone())
+    }
+
+    /// Generate isobands (filled contours) on the refined grid
+    ///
+    /// See [`GeoGrid::isobands`] for details.
+    pub fn isobands(&self, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        validate_thresholds(thresholds)?;
+        generate_isobands(self, thresholds)
+    }
+
+    /// Generate isolines (contour lines) on the refined grid
+    ///
+    /// See [`GeoGrid::isolines`] for details.
+    pub fn isolines(&self, levels: &[f64]) -> Result<Vec<Feature>> {
+        validate_levels(levels)?;
+        generate_isolines(self, levels)
+    }
+
+    /// Split a refined index into (source index, fractional offset)
+    fn locate(&self, index: usize, source_len: usize) -> (usize, f64) {
+        let base = index / self.factor;
+        if base >= source_len - 1 {
+            // Last refined row/column lands exactly on the last source point
+            (source_len - 2, 1.0)
+        } else {
+            (base, (index % self.factor) as f64 / self.factor as f64)
+        }
+    }
+
+    /// Bilinear interpolation within the source cell at (row, col)
+    fn bilinear(&self, row: usize, col: usize, ty: f64, tx: f64) -> GridPoint {
+        let tl = self.source.point(row, col);
+        let tr = self.source.point(row, col + 1);
+        let bl = self.source.point(row + 1, col);
+        let br = self.source.point(row + 1, col + 1);
+
+        let lerp2 = |a: f64, b: f64, c: f64, d: f64| {
+            let top = a + (b - a) * tx;
+            let bottom = c + (d - c) * tx;
+            top + (bottom - top) * ty
+        };
+
+        GridPoint::new(
+            lerp2(tl.lon, tr.lon, bl.lon, br.lon),
+            lerp2(tl.lat, tr.lat, bl.lat, br.lat),
+            lerp2(
+                tl.value as f64,
+                tr.value as f64,
+                bl.value as f64,
+                br.value as f64,
+            ) as f32,
+        )
+    }
+
+    /// Bicubic (Catmull-Rom) interpolation around the source cell at (row, col)
+    fn bicubic(&self, row: usize, col: usize, ty: f64, tx: f64) -> GridPoint {
+        let wy = catmull_rom_weights(ty);
+        let wx = catmull_rom_weights(tx);
+
+        let (mut lon, mut lat, mut value) = (0.0, 0.0, 0.0);
+        for (i, wy) in wy.iter().enumerate() {
+            for (j, wx) in wx.iter().enumerate() {
+                let (p_lon, p_lat, p_value) =
+                    self.sample(row as isize + i as isize - 1, col as isize + j as isize - 1);
+                let w = wy * wx;
+                lon += w * p_lon;
+                lat += w * p_lat;
+                value += w * p_value;
+            }
+        }
+
+        GridPoint::new(lon, lat.clamp(-90.0, 90.0), value as f32)
+    }
+
+    /// Read a source point as (lon, lat, value), extrapolating linearly past the edges
+    ///
+    /// Linear ghost points (rather than clamped ones) keep the bicubic kernel exact
+    /// for linear fields all the way to the grid boundary.
+    fn sample(&self, row: isize, col: isize) -> (f64, f64, f64) {
+        let last_row = self.source.rows() as isize - 1;
+        let last_col = self.source.cols() as isize - 1;
+
+        let ghost = |inner: (f64, f64, f64), next: (f64, f64, f64)| {
+            (
+                2.0 * inner.0 - next.0,
+                2.0 * inner.1 - next.1,
+                2.0 * inner.2 - next.2,
+            )
+        };
+
+        if row < 0 {
+            ghost(self.sample(0, col), self.sample(1, col))
+        } else if row > last_row {
+            ghost(self.sample(last_row, col), self.sample(last_row - 1, col))
+        } else if col < 0 {
+            ghost(self.sample(row, 0), self.sample(row, 1))
+        } else if col > last_col {
+            ghost(self.sample(row, last_col), self.sample(row, last_col - 1))
+        } else {
+            let p = self.source.point(row as usize, col as usize);
+            (p.lon, p.lat, p.value as f64)
+        }
+    }
+}
+
+impl<G: GridSource + ?Sized> GridSource for RefinedGrid<'_, G> {
+    fn rows(&self) -> usize {
+        RefinedGrid::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        RefinedGrid::cols(self)
+    }
+
+    fn point(&self, row: usize, col: usize) -> GridPoint {
+        if row.is_multiple_of(self.factor) && col.is_multiple_of(self.factor) {
+            return self.source.point(row / self.factor, col / self.factor);
+        }
+
+        let (src_row, ty) = self.locate(row, self.source.rows());
+        let (src_col, tx) = self.locate(col, self.source.cols());
+        match self.method {
+            RefineMethod::Bilinear => self.bilinear(src_row, src_col, ty, tx),
+            RefineMethod::Bicubic => self.bicubic(src_row, src_col, ty, tx),
+        }
+    }
+
+    fn config(&self) -> &MarchingSquaresConfig {
+        &self.config
+    }
+}
+
+impl GeoGrid {
+    /// Get a lazily refined view of this grid for smoother contours
+    ///
+    /// See [`RefinedGrid`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `factor` is zero
+    pub fn refine(&self, factor: usize, method: RefineMethod) -> Result<RefinedGrid<'_, Self>> {
+        RefinedGrid::new(self, factor, method)
+    }
+}
+
+/// Catmull-Rom cubic convolution weights for the 4 samples around offset `t`
+fn catmull_rom_weights(t: f64) -> [f64; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_grid() -> GeoGrid {
+        let points = (0..4)
+            .map(|row| {
+                (0..4)
+                    .map(|col| {
+                        GridPoint::new(
+                            -100.0 + col as f64,
+                            41.0 - row as f64,
+                            (row * 4 + col) as f32,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        GeoGrid::from_points(points).unwrap()
+    }
+
+    #[test]
+    fn test_refined_dimensions() {
+        let grid = create_test_grid();
+        let refined = grid.refine(4, RefineMethod::Bilinear).unwrap();
+        assert_eq!(refined.rows(), 13);
+        assert_eq!(refined.cols(), 13);
+        assert!(grid.refine(0, RefineMethod::Bilinear).is_err());
+    }
+
+    #[test]
+    fn test_source_points_preserved() {
+        let grid = create_test_grid();
+        for method in [RefineMethod::Bilinear, RefineMethod::Bicubic] {
+            let refined = grid.refine(3, method).unwrap();
+            assert_eq!(refined.get(0, 0), grid.get(0, 0).copied());
+            assert_eq!(refined.get(3, 6), grid.get(1, 2).copied());
+            assert_eq!(refined.get(9, 9), grid.get(3, 3).copied());
+        }
+    }
+
+    #[test]
+    fn test_linear_field_is_reproduced() {
+        // Both kernels reproduce a linear field exactly
+        let grid = create_test_grid();
+        for method in [RefineMethod::Bilinear, RefineMethod::Bicubic] {
+            let refined = grid.refine(2, method).unwrap();
+            let p = refined.get(3, 1).unwrap();
+            assert!((p.lon - -99.5).abs() < 1e-9);
+            assert!((p.lat - 39.5).abs() < 1e-9);
+            assert!((p.value - 6.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_refined_isobands() {
+        let grid = create_test_grid();
+        let refined = grid.refine(4, RefineMethod::Bicubic).unwrap();
+        let bands = refined.isobands(&[2.0, 8.0, 14.0]).unwrap();
+        assert_eq!(bands.len(), 2);
+        assert_eq!(refined.to_geo_grid().unwrap().rows(), 13);
+    }
+}
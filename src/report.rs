@@ -0,0 +1,323 @@
+//! Contour run reports for data-quality monitoring
+//!
+//! [`GridSource::isobands_with_report`](crate::GridSource::isobands_with_report)
+//! and [`GridSource::isolines_with_report`](crate::GridSource::isolines_with_report)
+//! return a [`ContourReport`] alongside the features. For each band it records
+//! the rings traced, every failed or abandoned trace with its cell location,
+//! the cells processed per shape class, vertex counts and timing. Empty bands
+//! and levels are reported too.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint, GridSource};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 10.0), GridPoint::new(-99.0, 41.0, 20.0)],
+//!     vec![GridPoint::new(-100.0, 40.0, 15.0), GridPoint::new(-99.0, 40.0, 25.0)],
+//! ])?;
+//!
+//! let (features, report) = grid.isobands_with_report(&[12.0, 18.0, 22.0])?;
+//! assert_eq!(report.bands.len(), 2);
+//! assert!(report.is_clean());
+//! for band in &report.bands {
+//!     println!(
+//!         "{}-{}: {} rings, {} vertices, {} failures in {:?}",
+//!         band.lower, band.upper, band.rings, band.vertices, band.failures.len(), band.elapsed
+//!     );
+//! }
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+/// Report of one isoband or isoline run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContourReport {
+    /// One entry per band, in threshold order (empty for isoline runs)
+    pub bands: Vec<BandReport>,
+    /// One entry per level, in input order (empty for isoband runs)
+    pub levels: Vec<LevelReport>,
+    /// Wall-clock time of the whole run
+    pub elapsed: Duration,
+}
+
+impl ContourReport {
+    /// Total rings traced over all bands
+    pub fn rings(&self) -> usize {
+        self.bands.iter().map(|b| b.rings).sum()
+    }
+
+    /// Total output vertices over all bands and levels
+    pub fn vertices(&self) -> usize {
+        self.bands.iter().map(|b| b.vertices).sum::<usize>()
+            + self.levels.iter().map(|l| l.vertices).sum::<usize>()
+    }
+
+    /// All trace failures, band by band
+    pub fn failures(&self) -> impl Iterator<Item = &TraceFailure> {
+        self.bands.iter().flat_map(|b| &b.failures)
+    }
+
+    /// Whether every trace completed
+    ///
+    /// Degenerate rings close normally and lose no output, so they do not
+    /// count against a clean run.
+    pub fn is_clean(&self) -> bool {
+        !self.failures().any(|f| f.kind.is_incomplete())
+    }
+}
+
+/// Tracing and geometry statistics for one isoband
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BandReport {
+    /// Lower threshold of the band
+    pub lower: f64,
+    /// Upper threshold of the band
+    pub upper: f64,
+    /// Rings traced (exteriors and holes)
+    pub rings: usize,
+    /// Polygons after hole assignment
+    pub polygons: usize,
+    /// Holes after hole assignment
+    pub holes: usize,
+    /// Ring traces started
+    pub trace_attempts: usize,
+    /// Traces that failed or were abandoned; warnings unless
+    /// [`strict`](crate::MarchingSquaresConfig::strict) is set
+    pub failures: Vec<TraceFailure>,
+    /// Cells processed, by shape class
+    pub cells: CellCounts,
+    /// Traced ring vertices (before ring closing)
+    pub vertices: usize,
+    /// Time spent tracing and building the band's output
+    pub elapsed: Duration,
+}
+
+/// Statistics for one isoline level
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelReport {
+    /// Contour level
+    pub level: f64,
+    /// Cells the level crosses
+    pub cells_crossed: usize,
+    /// Line segments output
+    pub lines: usize,
+    /// Line vertices output
+    pub vertices: usize,
+    /// Time spent generating the level
+    pub elapsed: Duration,
+}
+
+/// A ring trace that did not close cleanly
+///
+/// Failed traces produce no ring: a partial ring is dropped rather than
+/// closed with a chord across the band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceFailure {
+    /// What went wrong
+    pub kind: TraceFailureKind,
+    /// Cell row where the trace started
+    pub start_row: usize,
+    /// Cell column where the trace started
+    pub start_col: usize,
+    /// Cell row where the trace stopped
+    pub row: usize,
+    /// Cell column where the trace stopped
+    pub col: usize,
+    /// Configuration of the cell where the trace stopped (0-170), if it
+    /// exists
+    pub config: Option<u8>,
+    /// Edges collected before stopping
+    pub edges: usize,
+}
+
+impl fmt::Display for TraceFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at cell ({}, {}) after {} edges, started at ({}, {})",
+            self.kind, self.row, self.col, self.edges, self.start_row, self.start_col
+        )?;
+        if let Some(config) = self.config {
+            write!(f, ", config {config}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a ring trace failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TraceFailureKind {
+    /// The trace walked off the grid
+    LeftGrid,
+    /// The trace reached a cell whose edges were all used
+    ClearedCell,
+    /// The next cell had no edge continuing from the current point
+    DeadEnd,
+    /// The trace reached [`max_ring_steps`](crate::MarchingSquaresConfig::max_ring_steps)
+    IterationLimit,
+    /// The trace closed with fewer than three points
+    Degenerate,
+}
+
+impl TraceFailureKind {
+    /// Whether the trace stopped before its ring closed
+    ///
+    /// These are the failures that lose output and that strict mode rejects.
+    pub fn is_incomplete(&self) -> bool {
+        !matches!(self, Self::Degenerate)
+    }
+}
+
+impl fmt::Display for TraceFailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::LeftGrid => "left the grid",
+            Self::ClearedCell => "reached a cleared cell",
+            Self::DeadEnd => "dead end",
+            Self::IterationLimit => "ring step limit reached",
+            Self::Degenerate => "degenerate ring",
+        })
+    }
+}
+
+/// Marching squares cell shape classes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellClass {
+    /// All corners below or all above the band (no edges)
+    Empty,
+    Triangle,
+    Pentagon,
+    Rectangle,
+    Trapezoid,
+    Hexagon,
+    Saddle,
+    /// All corners inside the band
+    Square,
+}
+
+impl CellClass {
+    /// All classes, in [`CellCounts`] field order
+    pub const ALL: [CellClass; 8] = [
+        Self::Empty,
+        Self::Triangle,
+        Self::Pentagon,
+        Self::Rectangle,
+        Self::Trapezoid,
+        Self::Hexagon,
+        Self::Saddle,
+        Self::Square,
+    ];
+}
+
+/// Cells processed per [`CellClass`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CellCounts {
+    pub empty: usize,
+    pub triangle: usize,
+    pub pentagon: usize,
+    pub rectangle: usize,
+    pub trapezoid: usize,
+    pub hexagon: usize,
+    pub saddle: usize,
+    pub square: usize,
+}
+
+impl CellCounts {
+    /// Count for one class
+    pub fn get(&self, class: CellClass) -> usize {
+        match class {
+            CellClass::Empty => self.empty,
+            CellClass::Triangle => self.triangle,
+            CellClass::Pentagon => self.pentagon,
+            CellClass::Rectangle => self.rectangle,
+            CellClass::Trapezoid => self.trapezoid,
+            CellClass::Hexagon => self.hexagon,
+            CellClass::Saddle => self.saddle,
+            CellClass::Square => self.square,
+        }
+    }
+
+    /// Total cells processed
+    pub fn total(&self) -> usize {
+        CellClass::ALL.iter().map(|&c| self.get(c)).sum()
+    }
+
+    pub(crate) fn record(&mut self, class: CellClass) {
+        let count = match class {
+            CellClass::Empty => &mut self.empty,
+            CellClass::Triangle => &mut self.triangle,
+            CellClass::Pentagon => &mut self.pentagon,
+            CellClass::Rectangle => &mut self.rectangle,
+            CellClass::Trapezoid => &mut self.trapezoid,
+            CellClass::Hexagon => &mut self.hexagon,
+            CellClass::Saddle => &mut self.saddle,
+            CellClass::Square => &mut self.square,
+        };
+        *count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_totals() {
+        let failure = TraceFailure {
+            kind: TraceFailureKind::DeadEnd,
+            start_row: 1,
+            start_col: 2,
+            row: 3,
+            col: 4,
+            config: Some(85),
+            edges: 7,
+        };
+        let mut cells = CellCounts::default();
+        cells.record(CellClass::Saddle);
+        cells.record(CellClass::Saddle);
+        cells.record(CellClass::Empty);
+        assert_eq!(cells.get(CellClass::Saddle), 2);
+        assert_eq!(cells.total(), 3);
+
+        let report = ContourReport {
+            bands: vec![
+                BandReport {
+                    rings: 2,
+                    vertices: 10,
+                    ..BandReport::default()
+                },
+                BandReport {
+                    rings: 1,
+                    vertices: 5,
+                    failures: vec![failure],
+                    cells,
+                    ..BandReport::default()
+                },
+            ],
+            ..ContourReport::default()
+        };
+        assert_eq!(report.rings(), 3);
+        assert_eq!(report.vertices(), 15);
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.failures().next().unwrap().to_string(),
+            "dead end at cell (3, 4) after 7 edges, started at (1, 2), config 85"
+        );
+
+        let degenerate = ContourReport {
+            bands: vec![BandReport {
+                failures: vec![TraceFailure {
+                    kind: TraceFailureKind::Degenerate,
+                    ..failure
+                }],
+                ..BandReport::default()
+            }],
+            ..ContourReport::default()
+        };
+        assert!(degenerate.is_clean());
+    }
+}
@@ -0,0 +1,448 @@
+//! Threshold generation strategies
+//!
+//! [`ThresholdStrategy`] computes an ascending list of contour levels from the
+//! grid itself, ready to pass to [`GeoGrid::isobands`] or
+//! [`GeoGrid::isolines`]:
+//!
+//! - [`NiceInterval`](ThresholdStrategy::NiceInterval): equal intervals on
+//!   round steps (1, 2, 2.5 or 5 × 10ⁿ) covering the value range
+//! - [`FixedStep`](ThresholdStrategy::FixedStep): every `step` units anchored
+//!   at a base value, e.g. isobars every 4 hPa on 1000
+//! - [`Logarithmic`](ThresholdStrategy::Logarithmic): round steps per decade
+//!   for positive, skewed fields such as precipitation
+//! - [`Quantile`](ThresholdStrategy::Quantile): equal-count (equal-area)
+//!   breaks
+//! - [`Jenks`](ThresholdStrategy::Jenks): Jenks natural breaks, minimizing
+//!   the variance within each class
+//!
+//! Range-based strategies use [`GeoGrid::value_range`]; the distribution-based
+//! ones read every value. Non-finite values are ignored.
+//!
+//! # Example
+//!
+//! ```rust
+//! use geo_marching_squares_rs::thresholds::ThresholdStrategy;
+//! use geo_marching_squares_rs::{GeoGrid, GridPoint};
+//!
+//! let grid = GeoGrid::from_points(vec![
+//!     vec![GridPoint::new(-100.0, 41.0, 997.3), GridPoint::new(-99.0, 41.0, 1006.1)],
+//!     vec![GridPoint::new(-100.0, 40.0, 1002.0), GridPoint::new(-99.0, 40.0, 1011.8)],
+//! ])?;
+//!
+//! let isobars = ThresholdStrategy::FixedStep { step: 4.0, base: 1000.0 }.thresholds(&grid)?;
+//! assert_eq!(isobars, vec![996.0, 1000.0, 1004.0, 1008.0, 1012.0]);
+//!
+//! let nice = ThresholdStrategy::NiceInterval { count: 5 }.thresholds(&grid)?;
+//! assert_eq!(nice, vec![995.0, 1000.0, 1005.0, 1010.0, 1015.0]);
+//! # Ok::<(), geo_marching_squares_rs::Error>(())
+//! ```
+
+use crate::error::{Error, Result};
+use crate::grid::GeoGrid;
+
+/// Largest number of values Jenks breaks are computed over
+///
+/// The optimization is quadratic in the number of values, so larger inputs
+/// are reduced to this many evenly spaced order statistics first.
+pub const JENKS_MAX_VALUES: usize = 2000;
+
+/// How to choose contour levels from a grid's values
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum ThresholdStrategy {
+    /// About `count` equal intervals on a round step size
+    NiceInterval { count: usize },
+    /// Every `step` units, on multiples of `step` away from `base`
+    FixedStep { step: f64, base: f64 },
+    /// `per_decade` round levels per power of ten (positive values only)
+    Logarithmic { per_decade: usize },
+    /// `classes` classes holding roughly equal numbers of grid values
+    Quantile { classes: usize },
+    /// `classes` classes by Jenks natural breaks
+    Jenks { classes: usize },
+}
+
+impl ThresholdStrategy {
+    /// Compute ascending thresholds for the grid's values
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grid has no finite values, the strategy's
+    /// parameters are invalid, or (for [`Logarithmic`](Self::Logarithmic))
+    /// the values are not all positive
+    pub fn thresholds(&self, grid: &GeoGrid) -> Result<Vec<f64>> {
+        match self {
+            Self::NiceInterval { .. } | Self::FixedStep { .. } | Self::Logarithmic { .. } => {
+                let (min, max) = grid.value_range();
+                self.from_range(f64::from(min), f64::from(max))
+            }
+            Self::Quantile { .. } | Self::Jenks { .. } => {
+                let values: Vec<f64> = grid.iter().map(|p| f64::from(p.value)).collect();
+                self.from_values(&values)
+            }
+        }
+    }
+
+    /// Compute ascending thresholds from a list of values
+    ///
+    /// # Errors
+    ///
+    /// Same as [`thresholds`](Self::thresholds)
+    pub fn from_values(&self, values: &[f64]) -> Result<Vec<f64>> {
+        let mut finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        if finite.is_empty() {
+            return Err(Error::invalid_thresholds(
+                "No finite values to compute thresholds from",
+            ));
+        }
+        finite.sort_by(f64::total_cmp);
+
+        match *self {
+            Self::Quantile { classes } => quantile_breaks(&finite, classes),
+            Self::Jenks { classes } => jenks_breaks(&finite, classes),
+            _ => self.from_range(finite[0], finite[finite.len() - 1]),
+        }
+    }
+
+    /// Compute ascending thresholds covering `min..=max`
+    ///
+    /// Distribution-based strategies treat the range as uniformly filled.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`thresholds`](Self::thresholds)
+    pub fn from_range(&self, min: f64, max: f64) -> Result<Vec<f64>> {
+        if !(min.is_finite() && max.is_finite()) || min > max {
+            return Err(Error::invalid_thresholds(format!(
+                "Invalid value range {min} to {max}"
+            )));
+        }
+
+        match *self {
+            Self::NiceInterval { count } => nice_interval(min, max, count),
+            Self::FixedStep { step, base } => fixed_step(min, max, step, base),
+            Self::Logarithmic { per_decade } => logarithmic(min, max, per_decade),
+            Self::Quantile { classes } | Self::Jenks { classes } => {
+                let count = check_classes(classes)?;
+                Ok(finalize(
+                    (0..=count)
+                        .map(|i| min + (max - min) * i as f64 / count as f64)
+                        .collect(),
+                    min,
+                ))
+            }
+        }
+    }
+}
+
+/// Equal intervals on a 1, 2, 2.5 or 5 × 10ⁿ step covering the range
+fn nice_interval(min: f64, max: f64, count: usize) -> Result<Vec<f64>> {
+    let count = check_classes(count)?;
+    let span = max - min;
+    let step = if span > 0.0 {
+        nice_step(span / count as f64)
+    } else {
+        nice_step(min.abs().max(1.0) / 10.0)
+    };
+    fixed_step(min, max, step, 0.0)
+}
+
+/// Smallest round step (1, 2, 2.5 or 5 × 10ⁿ) of at least `raw`
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let fraction = raw / magnitude;
+    let nice = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .find(|&n| fraction <= n * (1.0 + 1e-9))
+        .unwrap_or(10.0);
+    nice * magnitude
+}
+
+/// Multiples of `step` away from `base` covering the range
+fn fixed_step(min: f64, max: f64, step: f64, base: f64) -> Result<Vec<f64>> {
+    if !(step.is_finite() && step > 0.0 && base.is_finite()) {
+        return Err(Error::invalid_thresholds(format!(
+            "Step must be positive and finite, got {step} anchored at {base}"
+        )));
+    }
+    let first = ((min - base) / step).floor() as i64;
+    let mut last = ((max - base) / step).ceil() as i64;
+    if last <= first {
+        last = first + 1;
+    }
+    if last - first > 100_000 {
+        return Err(Error::invalid_thresholds(format!(
+            "Step {step} gives more than 100000 thresholds over {min} to {max}"
+        )));
+    }
+    Ok(finalize(
+        (first..=last).map(|k| base + k as f64 * step).collect(),
+        step,
+    ))
+}
+
+/// Round levels per decade covering a positive range
+fn logarithmic(min: f64, max: f64, per_decade: usize) -> Result<Vec<f64>> {
+    if min <= 0.0 {
+        return Err(Error::invalid_thresholds(format!(
+            "Logarithmic thresholds need positive values, got minimum {min}"
+        )));
+    }
+    let mantissas: Vec<f64> = match per_decade {
+        0 => {
+            return Err(Error::invalid_thresholds(
+                "Need at least 1 level per decade",
+            ))
+        }
+        1 => vec![1.0],
+        2 => vec![1.0, 3.0],
+        3 => vec![1.0, 2.0, 5.0],
+        // Two significant digits run out past 30 per decade
+        n => dedup(
+            (0..n)
+                .map(|i| round_significant(10f64.powf(i as f64 / n as f64), 2))
+                .collect(),
+        ),
+    };
+
+    let mut levels = Vec::new();
+    let mut decade = min.log10().floor() as i32;
+    loop {
+        let scale = 10f64.powi(decade);
+        for &m in &mantissas {
+            let level = round_significant(m * scale, 3);
+            if level <= min {
+                // Restart so the list begins at the last level below the data
+                levels.clear();
+            }
+            if levels.last() == Some(&level) {
+                continue;
+            }
+            levels.push(level);
+            if level >= max && levels.len() >= 2 {
+                return Ok(levels);
+            }
+        }
+        decade += 1;
+    }
+}
+
+/// Breaks at evenly spaced quantiles of the sorted values
+fn quantile_breaks(sorted: &[f64], classes: usize) -> Result<Vec<f64>> {
+    let classes = check_classes(classes)?;
+    let last = sorted.len() - 1;
+    let breaks = (0..=classes)
+        .map(|i| sorted[(i as f64 * last as f64 / classes as f64).round() as usize])
+        .collect();
+    Ok(widen(dedup(breaks)))
+}
+
+/// Jenks natural breaks of the sorted values
+fn jenks_breaks(sorted: &[f64], classes: usize) -> Result<Vec<f64>> {
+    let classes = check_classes(classes)?;
+
+    let sample: Vec<f64> = if sorted.len() > JENKS_MAX_VALUES {
+        let last = sorted.len() - 1;
+        (0..JENKS_MAX_VALUES)
+            .map(|i| sorted[i * last / (JENKS_MAX_VALUES - 1)])
+            .collect()
+    } else {
+        sorted.to_vec()
+    };
+    let n = sample.len();
+    let k = classes.min(n);
+
+    // lower[i][j]: 1-based index of the first value in the last class of the
+    // best split of the first i values into j classes
+    let mut lower = vec![vec![1usize; k + 1]; n + 1];
+    let mut variance = vec![vec![f64::INFINITY; k + 1]; n + 1];
+    variance[1][1..].fill(0.0);
+
+    for i in 2..=n {
+        let (mut sum, mut sum_sq, mut count) = (0.0, 0.0, 0.0);
+        for m in 1..=i {
+            // Grow the last class downward to start at value `start`
+            let start = i - m + 1;
+            let v = sample[start - 1];
+            sum += v;
+            sum_sq += v * v;
+            count += 1.0;
+            let class_variance = sum_sq - sum * sum / count;
+            if start > 1 {
+                for j in 2..=k {
+                    let candidate = class_variance + variance[start - 1][j - 1];
+                    if candidate < variance[i][j] {
+                        lower[i][j] = start;
+                        variance[i][j] = candidate;
+                    }
+                }
+            }
+        }
+        lower[i][1] = 1;
+        variance[i][1] = sum_sq - sum * sum / count;
+    }
+
+    let mut breaks = vec![0.0; k + 1];
+    breaks[k] = sample[n - 1];
+    breaks[0] = sample[0];
+    let mut end = n;
+    for j in (2..=k).rev() {
+        let start = lower[end][j];
+        breaks[j - 1] = sample[start - 1];
+        end = start - 1;
+    }
+    Ok(widen(dedup(breaks)))
+}
+
+fn check_classes(classes: usize) -> Result<usize> {
+    if classes == 0 {
+        return Err(Error::invalid_thresholds("Need at least 1 class"));
+    }
+    Ok(classes)
+}
+
+/// Remove floating-point noise from generated levels and drop duplicates
+fn finalize(levels: Vec<f64>, scale: f64) -> Vec<f64> {
+    // Keep a few more decimals than the step needs
+    let decimals = (3 - scale.abs().log10().floor() as i32).clamp(0, 15);
+    let factor = 10f64.powi(decimals);
+    dedup(
+        levels
+            .into_iter()
+            .map(|v| (v * factor).round() / factor)
+            .collect(),
+    )
+}
+
+fn dedup(mut levels: Vec<f64>) -> Vec<f64> {
+    levels.dedup();
+    levels
+}
+
+/// Make a single-value break list usable for isobands
+fn widen(mut breaks: Vec<f64>) -> Vec<f64> {
+    if breaks.len() == 1 {
+        let v = breaks[0];
+        breaks = vec![v - 0.5, v + 0.5];
+    }
+    breaks
+}
+
+fn round_significant(value: f64, digits: i32) -> f64 {
+    let factor = 10f64.powi(digits - 1 - value.abs().log10().floor() as i32);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nice_and_fixed_step() {
+        let nice = ThresholdStrategy::NiceInterval { count: 4 };
+        assert_eq!(
+            nice.from_range(3.0, 88.0).unwrap(),
+            vec![0.0, 25.0, 50.0, 75.0, 100.0]
+        );
+        assert_eq!(
+            nice.from_range(-3.0, 17.0).unwrap(),
+            vec![-5.0, 0.0, 5.0, 10.0, 15.0, 20.0]
+        );
+        // Flat fields still give a usable band
+        assert_eq!(nice.from_range(7.0, 7.0).unwrap().len(), 2);
+
+        let isobars = ThresholdStrategy::FixedStep {
+            step: 4.0,
+            base: 1000.0,
+        };
+        assert_eq!(
+            isobars.from_range(1001.0, 1009.0).unwrap(),
+            vec![1000.0, 1004.0, 1008.0, 1012.0]
+        );
+        let tenths = ThresholdStrategy::FixedStep {
+            step: 0.1,
+            base: 0.0,
+        };
+        assert_eq!(
+            tenths.from_range(0.25, 0.45).unwrap(),
+            vec![0.2, 0.3, 0.4, 0.5]
+        );
+
+        assert!(ThresholdStrategy::FixedStep {
+            step: 0.0,
+            base: 0.0
+        }
+        .from_range(0.0, 1.0)
+        .is_err());
+        assert!(ThresholdStrategy::NiceInterval { count: 0 }
+            .from_range(0.0, 1.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_logarithmic() {
+        let log = ThresholdStrategy::Logarithmic { per_decade: 3 };
+        assert_eq!(
+            log.from_range(0.3, 40.0).unwrap(),
+            vec![0.2, 0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0]
+        );
+        let decades = ThresholdStrategy::Logarithmic { per_decade: 1 };
+        assert_eq!(
+            decades.from_range(1.0, 1000.0).unwrap(),
+            vec![1.0, 10.0, 100.0, 1000.0]
+        );
+        assert!(log.from_range(0.0, 10.0).is_err());
+
+        // Dense levels collapse onto the same rounded values but stay distinct
+        let dense = ThresholdStrategy::Logarithmic { per_decade: 40 };
+        let levels = dense.from_range(1.0, 20.0).unwrap();
+        assert!(levels.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!((levels[0], levels[1]), (1.0, 1.1));
+        assert_eq!(*levels.last().unwrap(), 20.0);
+    }
+
+    #[test]
+    fn test_quantile_breaks() {
+        let values: Vec<f64> = (0..=100).map(f64::from).collect();
+        let quartiles = ThresholdStrategy::Quantile { classes: 4 };
+        assert_eq!(
+            quartiles.from_values(&values).unwrap(),
+            vec![0.0, 25.0, 50.0, 75.0, 100.0]
+        );
+
+        // Skewed data: repeated breaks collapse, and NaN is ignored
+        let skewed = [1.0, 1.0, 1.0, 1.0, 2.0, 3.0, f64::NAN, 100.0];
+        let breaks = ThresholdStrategy::Quantile { classes: 3 }
+            .from_values(&skewed)
+            .unwrap();
+        assert_eq!(breaks, vec![1.0, 2.0, 100.0]);
+        assert!(quartiles.from_values(&[f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_jenks_finds_clusters() {
+        let values = [1.0, 2.0, 1.5, 10.0, 11.0, 10.5, 30.0, 31.0, 29.0];
+        let breaks = ThresholdStrategy::Jenks { classes: 3 }
+            .from_values(&values)
+            .unwrap();
+        assert_eq!(breaks, vec![1.0, 10.0, 29.0, 31.0]);
+
+        // Large inputs are sampled but keep their extremes
+        let many: Vec<f64> = (0..10_000)
+            .map(|i| {
+                if i < 5000 {
+                    i as f64 * 1e-3
+                } else {
+                    100.0 + i as f64 * 1e-3
+                }
+            })
+            .collect();
+        let breaks = ThresholdStrategy::Jenks { classes: 2 }
+            .from_values(&many)
+            .unwrap();
+        assert_eq!(breaks.len(), 3);
+        assert_eq!(breaks[0], 0.0);
+        assert!(breaks[1] >= 105.0 && breaks[1] < 105.1);
+        assert_eq!(breaks[2], many[9999]);
+    }
+}
@@ -1,7 +1,9 @@
 //! Core data types for geographic marching squares
 
+use crate::mask::ClipMask;
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 /// Round coordinate to 5 decimal places (~1.1 meter precision at equator)
 /// This matches the Java implementation (positionAccuracy = 5)
@@ -11,6 +13,9 @@ use std::hash::{Hash, Hasher};
 ///
 /// IMPORTANT: Only applied at final GeoJSON output, NOT during interpolation.
 /// This ensures adjacent cells compute identical edge endpoints during tracing.
+///
+/// Contour output is rounded with [`MarchingSquaresConfig::precision`], whose
+/// default matches this function.
 pub fn round_coordinate(coord: f64) -> f64 {
     // Rust's round() already does HALF_UP (rounds 0.5 away from zero)
     // which matches Java's RoundingMode.HALF_UP behavior
@@ -257,6 +262,113 @@ impl From<SmoothingFactor> for f64 {
     }
 }
 
+/// How output coordinates are rounded to a number of decimal places
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum RoundingMode {
+    /// Round half-way cases away from zero (default, Java `RoundingMode.HALF_UP`)
+    #[default]
+    HalfUp,
+    /// Round half-way cases to the nearest even digit (banker's rounding)
+    HalfEven,
+    /// Drop the extra digits, rounding toward zero
+    TowardZero,
+}
+
+/// Precision of output coordinates
+///
+/// Rounding is applied only when coordinates are written out, after rings have
+/// been traced and closed, so adjacent cells always share identical edge
+/// endpoints regardless of the precision.
+///
+/// # Example
+///
+/// ```rust
+/// use geo_marching_squares_rs::{CoordinatePrecision, RoundingMode};
+///
+/// let tiles = CoordinatePrecision::decimals(4);
+/// assert_eq!(tiles.round(-100.123456), -100.1235);
+///
+/// let truncated = tiles.with_mode(RoundingMode::TowardZero);
+/// assert_eq!(truncated.round(-100.123456), -100.1234);
+///
+/// assert_eq!(CoordinatePrecision::full().round(0.1 + 0.2), 0.1 + 0.2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoordinatePrecision {
+    decimals: Option<u32>,
+    mode: RoundingMode,
+}
+
+impl CoordinatePrecision {
+    /// Round to `places` decimal places, half-way cases away from zero
+    pub const fn decimals(places: u32) -> Self {
+        Self {
+            decimals: Some(places),
+            mode: RoundingMode::HalfUp,
+        }
+    }
+
+    /// Write coordinates at full `f64` precision, without rounding
+    pub const fn full() -> Self {
+        Self {
+            decimals: None,
+            mode: RoundingMode::HalfUp,
+        }
+    }
+
+    /// Use a different rounding mode
+    pub const fn with_mode(self, mode: RoundingMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    /// Number of decimal places, or `None` for full precision
+    pub fn decimal_places(&self) -> Option<u32> {
+        self.decimals
+    }
+
+    /// Rounding mode
+    pub fn mode(&self) -> RoundingMode {
+        self.mode
+    }
+
+    /// Round a coordinate
+    pub fn round(&self, coord: f64) -> f64 {
+        let Some(places) = self.decimals else {
+            return coord;
+        };
+        let factor = 10f64.powi(places.min(i32::MAX as u32) as i32);
+        let scaled = coord * factor;
+        // Past 2^52 every f64 is already an integer at this scale; dividing
+        // back would only add error
+        if !scaled.is_finite() || scaled.abs() >= 4_503_599_627_370_496.0 {
+            return coord;
+        }
+        let rounded = match self.mode {
+            RoundingMode::HalfUp => scaled.round(),
+            RoundingMode::HalfEven => scaled.round_ties_even(),
+            RoundingMode::TowardZero => {
+                // A value already at this precision may scale to just below
+                // an integer (e.g. 99.8765 * 1e4), which must not truncate
+                let nearest = scaled.round();
+                if (scaled - nearest).abs() <= scaled.abs() * 4.0 * f64::EPSILON {
+                    nearest
+                } else {
+                    scaled.trunc()
+                }
+            }
+        };
+        rounded / factor
+    }
+}
+
+impl Default for CoordinatePrecision {
+    /// 5 decimal places, half up, matching the Java `positionAccuracy = 5`
+    fn default() -> Self {
+        Self::decimals(5)
+    }
+}
+
 /// Configuration for marching squares algorithm behavior
 #[derive(Debug, Clone)]
 pub struct MarchingSquaresConfig {
@@ -266,6 +378,30 @@ pub struct MarchingSquaresConfig {
     pub interpolation_method: InterpolationMethod,
     /// Smoothing factor for interpolation (0.0 to 1.0, typically 0.999)
     pub smoothing_factor: SmoothingFactor,
+    /// Rounding of output coordinates (default 5 decimal places)
+    pub precision: CoordinatePrecision,
+    /// Fail isoband generation with [`Error::TraceFailed`](crate::Error::TraceFailed)
+    /// when a ring trace stops before closing, instead of dropping the
+    /// partial ring and recording a warning in the run report (default false)
+    pub strict: bool,
+    /// Optional cap on the cells visited by one ring trace (default none)
+    ///
+    /// Tracing always terminates without it. A trace that reaches the cap is
+    /// reported as [`TraceFailureKind::IterationLimit`](crate::report::TraceFailureKind::IterationLimit),
+    /// its ring is dropped, and it fails strict mode.
+    pub max_ring_steps: Option<usize>,
+    /// Optional lon/lat box (min_lon, min_lat, max_lon, max_lat) to contour
+    /// (default none)
+    ///
+    /// Only the window of cells covering the box is processed, and output
+    /// rings and lines are clipped exactly to it, with rings closed along the
+    /// box edges.
+    pub clip_bbox: Option<(f64, f64, f64, f64)>,
+    /// Optional polygon mask to contour inside (default none)
+    ///
+    /// Cells outside the mask are skipped and output is clipped to it, holes
+    /// included. Combined with `clip_bbox`, output is clipped to both.
+    pub clip_mask: Option<Arc<ClipMask>>,
 }
 
 impl Default for MarchingSquaresConfig {
@@ -274,6 +410,11 @@ impl Default for MarchingSquaresConfig {
             use_parallel: cfg!(feature = "parallel"),
             interpolation_method: InterpolationMethod::Cosine,
             smoothing_factor: SmoothingFactor::default(),
+            precision: CoordinatePrecision::default(),
+            strict: false,
+            max_ring_steps: None,
+            clip_bbox: None,
+            clip_mask: None,
         }
     }
 }
@@ -308,6 +449,11 @@ pub struct MarchingSquaresConfigBuilder {
     use_parallel: Option<bool>,
     interpolation_method: Option<InterpolationMethod>,
     smoothing_factor: Option<SmoothingFactor>,
+    precision: Option<CoordinatePrecision>,
+    strict: Option<bool>,
+    max_ring_steps: Option<usize>,
+    clip_bbox: Option<(f64, f64, f64, f64)>,
+    clip_mask: Option<Arc<ClipMask>>,
 }
 
 impl MarchingSquaresConfigBuilder {
@@ -334,6 +480,36 @@ impl MarchingSquaresConfigBuilder {
         self
     }
 
+    /// Set the output coordinate precision
+    pub fn with_precision(mut self, precision: CoordinatePrecision) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Set whether incomplete ring traces are errors
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = Some(strict);
+        self
+    }
+
+    /// Cap the cells visited by one ring trace
+    pub fn with_max_ring_steps(mut self, steps: usize) -> Self {
+        self.max_ring_steps = Some(steps);
+        self
+    }
+
+    /// Contour only inside `bbox` (min_lon, min_lat, max_lon, max_lat)
+    pub fn with_clip_bbox(mut self, bbox: (f64, f64, f64, f64)) -> Self {
+        self.clip_bbox = Some(bbox);
+        self
+    }
+
+    /// Contour only inside a polygon mask
+    pub fn with_clip_mask(mut self, mask: ClipMask) -> Self {
+        self.clip_mask = Some(Arc::new(mask));
+        self
+    }
+
     /// Build the configuration
     pub fn build(self) -> MarchingSquaresConfig {
         let defaults = MarchingSquaresConfig::default();
@@ -341,6 +517,64 @@ impl MarchingSquaresConfigBuilder {
             use_parallel: self.use_parallel.unwrap_or(defaults.use_parallel),
             interpolation_method: self.interpolation_method.unwrap_or(defaults.interpolation_method),
             smoothing_factor: self.smoothing_factor.unwrap_or(defaults.smoothing_factor),
+            precision: self.precision.unwrap_or(defaults.precision),
+            strict: self.strict.unwrap_or(defaults.strict),
+            max_ring_steps: self.max_ring_steps.or(defaults.max_ring_steps),
+            clip_bbox: self.clip_bbox.or(defaults.clip_bbox),
+            clip_mask: self.clip_mask.or(defaults.clip_mask),
         }
     }
-}
\ No newline at end of file
+}
+
+/// A filled contour band as `geo-types` geometry
+///
+/// Produced by [`GridSource::isobands_geo`](crate::GridSource::isobands_geo) for use
+/// with `geo` algorithms without a GeoJSON round-trip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Isoband {
+    /// Lower threshold of the band
+    pub lower: f64,
+    /// Upper threshold of the band
+    pub upper: f64,
+    /// Band polygons, each an exterior ring with its holes
+    pub polygons: geo_types::MultiPolygon<f64>,
+}
+
+/// A contour line as `geo-types` geometry
+///
+/// Produced by [`GridSource::isolines_geo`](crate::GridSource::isolines_geo).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Isoline {
+    /// Contour level
+    pub level: f64,
+    /// Line segments at this level
+    pub lines: geo_types::MultiLineString<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coordinate_precision_modes() {
+        assert_eq!(
+            CoordinatePrecision::default().round(-97.123455),
+            round_coordinate(-97.123455)
+        );
+        assert_eq!(CoordinatePrecision::decimals(2).round(2.345), 2.35);
+        assert_eq!(CoordinatePrecision::decimals(0).round(-2.5), -3.0);
+
+        let even = CoordinatePrecision::decimals(0).with_mode(RoundingMode::HalfEven);
+        assert_eq!(even.round(2.5), 2.0);
+        assert_eq!(even.round(3.5), 4.0);
+
+        let trunc = CoordinatePrecision::decimals(1).with_mode(RoundingMode::TowardZero);
+        assert_eq!(trunc.round(-1.99), -1.9);
+        let trunc4 = CoordinatePrecision::decimals(4).with_mode(RoundingMode::TowardZero);
+        assert_eq!(trunc4.round(-99.8765), -99.8765);
+
+        // Scales past f64's integer range leave the value untouched
+        assert_eq!(CoordinatePrecision::decimals(400).round(0.1), 0.1);
+        assert_eq!(CoordinatePrecision::full().decimal_places(), None);
+    }
+}
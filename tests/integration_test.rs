@@ -1,6 +1,6 @@
 //! Integration tests for geo-marching-squares-rs
 
-use geo_marching_squares_rs::{GeoGrid, GridPoint};
+use geo_marching_squares_rs::{CoordinatePrecision, GeoGrid, GridPoint, GridSource, RoundingMode};
 
 #[test]
 fn test_simple_isobands() {
@@ -188,3 +188,756 @@ fn test_custom_config() {
     let isobands = grid.isobands(&[12.0, 18.0, 22.0]).expect("Failed with custom config");
     assert_eq!(isobands.len(), 2);
 }
+
+#[test]
+fn test_geo_types_output_matches_geojson() {
+    let points: Vec<Vec<GridPoint>> = (0..5)
+        .map(|row| {
+            (0..5)
+                .map(|col| {
+                    let dr = row as f32 - 2.0;
+                    let dc = col as f32 - 2.0;
+                    GridPoint::new(
+                        -100.0 + col as f64,
+                        42.0 - row as f64,
+                        30.0 - (dr * dr + dc * dc) * 3.0,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+
+    let grid = GeoGrid::from_points(points).expect("Failed to create grid");
+    let thresholds = [0.0, 10.0, 20.0, 25.0];
+
+    let features = grid
+        .isobands(&thresholds)
+        .expect("Failed to generate isobands");
+    let bands = grid
+        .isobands_geo(&thresholds)
+        .expect("Failed to generate isobands");
+    assert_eq!(bands.len(), features.len());
+
+    for (band, feature) in bands.iter().zip(&features) {
+        let props = feature.properties.as_ref().unwrap();
+        assert_eq!(props["lower_level"], band.lower);
+        assert_eq!(props["upper_level"], band.upper);
+
+        // Ring start vertices depend on tracing order, so compare distinct vertices
+        let geojson::Value::MultiPolygon(expected) = &feature.geometry.as_ref().unwrap().value
+        else {
+            panic!("expected a MultiPolygon");
+        };
+        assert_eq!(band.polygons.0.len(), expected.len());
+        let mut expected: Vec<(f64, f64)> = expected
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|c| (c[0], c[1]))
+            .collect();
+        let mut actual: Vec<(f64, f64)> = band
+            .polygons
+            .iter()
+            .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()))
+            .flat_map(|ring| ring.coords().map(|c| (c.x, c.y)))
+            .collect();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        actual.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        expected.dedup();
+        actual.dedup();
+        assert_eq!(actual, expected);
+
+        for polygon in &band.polygons {
+            assert!(polygon.exterior().is_closed());
+        }
+    }
+
+    let features = grid
+        .isolines(&[12.0, 24.0])
+        .expect("Failed to generate isolines");
+    let lines = grid
+        .isolines_geo(&[12.0, 24.0])
+        .expect("Failed to generate isolines");
+    assert_eq!(lines.len(), features.len());
+    for (line, feature) in lines.iter().zip(&features) {
+        assert_eq!(feature.properties.as_ref().unwrap()["isovalue"], line.level);
+        let expected: geo_types::Geometry<f64> =
+            feature.geometry.clone().unwrap().try_into().unwrap();
+        assert_eq!(
+            expected,
+            geo_types::Geometry::MultiLineString(line.lines.clone())
+        );
+    }
+}
+
+#[test]
+fn test_configured_coordinate_precision() {
+    let points: Vec<Vec<GridPoint>> = (0..5)
+        .map(|row| {
+            (0..5)
+                .map(|col| {
+                    let dr = row as f32 - 2.0;
+                    let dc = col as f32 - 2.0;
+                    GridPoint::new(
+                        -100.0 + col as f64 * 0.123_456_789,
+                        42.0 - row as f64 * 0.098_765_432,
+                        30.0 - (dr * dr + dc * dc) * 3.0,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    let mut grid = GeoGrid::from_points(points).expect("Failed to create grid");
+
+    for precision in [
+        CoordinatePrecision::decimals(0),
+        CoordinatePrecision::decimals(4),
+        CoordinatePrecision::decimals(4).with_mode(RoundingMode::TowardZero),
+        CoordinatePrecision::full(),
+    ] {
+        grid.config_mut().precision = precision;
+        let bands = grid
+            .isobands_geo(&[0.0, 10.0, 20.0, 25.0])
+            .expect("Failed to generate isobands");
+        assert!(!bands.is_empty());
+
+        for polygon in bands.iter().flat_map(|b| &b.polygons) {
+            for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+                // Rings are closed before rounding, so they stay closed at any precision
+                assert!(ring.is_closed());
+                for c in ring.coords() {
+                    assert_eq!(precision.round(c.x), c.x);
+                    assert_eq!(precision.round(c.y), c.y);
+                }
+            }
+        }
+    }
+
+    // Full precision keeps digits that the default 5 places would drop
+    let full = grid
+        .isolines_geo(&[12.0])
+        .expect("Failed to generate isolines");
+    grid.config_mut().precision = CoordinatePrecision::default();
+    let rounded = grid
+        .isolines_geo(&[12.0])
+        .expect("Failed to generate isolines");
+    assert_ne!(full, rounded);
+}
+
+#[test]
+fn test_band_descriptors_keep_identity() {
+    use geo_marching_squares_rs::bands::{BandDescriptor, Id, LevelDescriptor};
+    use serde_json::json;
+
+    let points: Vec<Vec<GridPoint>> = (0..6)
+        .map(|row| {
+            (0..6)
+                .map(|col| {
+                    GridPoint::new(
+                        -100.0 + col as f64,
+                        42.0 - row as f64,
+                        (row * 6 + col) as f32,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    let grid = GeoGrid::from_points(points).expect("Failed to create grid");
+
+    // The middle band is empty (values never reach 100) and is dropped
+    let bands = vec![
+        BandDescriptor::new(0.0, 10.0)
+            .with_id(Id::String("low".into()))
+            .with_property("fill", "#0000ff"),
+        BandDescriptor::new(100.0, 200.0).with_id(Id::String("empty".into())),
+        BandDescriptor::new(20.0, 30.0)
+            .with_property("fill", "#ff0000")
+            .with_property("z_order", 2),
+    ];
+    let features = grid
+        .isobands_described(&bands)
+        .expect("Failed to generate isobands");
+    assert_eq!(features.len(), 2);
+
+    assert_eq!(features[0].id, Some(Id::String("low".into())));
+    assert_eq!(features[0].property("fill"), Some(&json!("#0000ff")));
+    assert_eq!(features[0].property("upper_level"), Some(&json!(10.0)));
+
+    assert_eq!(features[1].id, None);
+    assert_eq!(features[1].property("lower_level"), Some(&json!(20.0)));
+    assert_eq!(features[1].property("z_order"), Some(&json!(2)));
+
+    // Same geometry as plain thresholds (ring start vertices may differ)
+    let plain = grid
+        .isobands(&[20.0, 30.0])
+        .expect("Failed to generate isobands");
+    let vertices = |feature: &geojson::Feature| {
+        let geojson::Value::MultiPolygon(polygons) = &feature.geometry.as_ref().unwrap().value
+        else {
+            panic!("expected a MultiPolygon");
+        };
+        let mut vertices: Vec<(f64, f64)> = polygons
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|c| (c[0], c[1]))
+            .collect();
+        vertices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        vertices.dedup();
+        vertices
+    };
+    assert_eq!(vertices(&features[1]), vertices(&plain[0]));
+
+    let levels = LevelDescriptor::from_levels(&[500.0, 12.5], |index, level| {
+        serde_json::Map::from_iter([("label".to_string(), json!(format!("#{index}: {level}")))])
+    });
+    let lines = grid
+        .isolines_described(&levels)
+        .expect("Failed to generate isolines");
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].property("label"), Some(&json!("#1: 12.5")));
+    assert_eq!(lines[0].property("isovalue"), Some(&json!(12.5)));
+
+    assert!(grid
+        .isobands_described(&[BandDescriptor::new(3.0, 1.0)])
+        .is_err());
+}
+
+#[test]
+fn test_palette_thresholds_and_styling() {
+    use geo_marching_squares_rs::palette::Palette;
+    use geo_marching_squares_rs::thresholds::ThresholdStrategy;
+    use serde_json::json;
+
+    let points: Vec<Vec<GridPoint>> = (0..6)
+        .map(|row| {
+            (0..6)
+                .map(|col| {
+                    GridPoint::new(
+                        -100.0 + col as f64,
+                        42.0 - row as f64,
+                        (row * 12 + col * 2) as f32,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    let grid = GeoGrid::from_points(points).expect("Failed to create grid");
+
+    // Reflectivity palette drives both the thresholds and the styling
+    let palette = Palette::nws_reflectivity();
+    let mut features = grid
+        .isobands(&palette.thresholds())
+        .expect("Failed to generate isobands");
+    palette.style_features(&mut features);
+    assert!(!features.is_empty());
+    for feature in &features {
+        let lower = feature
+            .property("lower_level")
+            .and_then(|v| v.as_f64())
+            .unwrap();
+        let expected = match lower as i32 {
+            5 => "#04e9e7",
+            20 => "#02fd02",
+            50 => "#fd0000",
+            _ => continue,
+        };
+        assert_eq!(feature.property("fill"), Some(&json!(expected)));
+    }
+
+    // Sequential colormaps follow any computed threshold list
+    let thresholds = ThresholdStrategy::NiceInterval { count: 5 }
+        .thresholds(&grid)
+        .expect("Failed to compute thresholds");
+    let viridis = Palette::viridis(&thresholds).expect("Failed to build palette");
+    let features = grid
+        .isobands_described(&viridis.band_descriptors())
+        .expect("Failed to generate isobands");
+    assert_eq!(features.len(), thresholds.len() - 1);
+    assert_eq!(features[0].property("fill"), Some(&json!("#440154")));
+}
+
+#[test]
+fn test_isoband_polygons_have_stable_ids() {
+    use geo_marching_squares_rs::bands::Id;
+    use serde_json::json;
+
+    // Two peaks: one in the north-west, one in the south-east
+    let points: Vec<Vec<GridPoint>> = (0..8)
+        .map(|row| {
+            (0..8)
+                .map(|col| {
+                    let value = if (row, col) == (2, 2) || (row, col) == (5, 5) {
+                        20.0
+                    } else {
+                        0.0
+                    };
+                    GridPoint::new(-100.0 + col as f64, 47.0 - row as f64, value)
+                })
+                .collect()
+        })
+        .collect();
+    let grid = GeoGrid::from_points(points).expect("Failed to create grid");
+    let thresholds = [-5.0, 5.0, 10.0, 30.0];
+
+    let features = grid
+        .isoband_polygons(&thresholds)
+        .expect("Failed to generate polygons");
+    assert_eq!(features, grid.isoband_polygons(&thresholds).unwrap());
+
+    // One polygon per ring group, matching the MultiPolygon output
+    for band in grid.isobands(&thresholds).unwrap() {
+        let geojson::Value::MultiPolygon(polygons) = &band.geometry.as_ref().unwrap().value else {
+            panic!("expected a MultiPolygon");
+        };
+        let lower = band.property("lower_level").unwrap();
+        let count = features
+            .iter()
+            .filter(|f| f.property("lower_level") == Some(lower))
+            .count();
+        assert_eq!(count, polygons.len());
+    }
+
+    // Peak cores: band 2, northern peak first
+    let cores: Vec<_> = features
+        .iter()
+        .filter(|f| f.property("band_index") == Some(&json!(2)))
+        .collect();
+    assert_eq!(cores.len(), 2);
+    assert_eq!(cores[0].id, Some(Id::Number((2u64 << 32).into())));
+    assert_eq!(cores[1].id, Some(Id::Number(((2u64 << 32) | 1).into())));
+    assert_eq!(cores[1].property("polygon_index"), Some(&json!(1)));
+    let centroid = |f: &geojson::Feature| {
+        let c = f.property("centroid").and_then(|c| c.as_array()).unwrap();
+        (c[0].as_f64().unwrap(), c[1].as_f64().unwrap())
+    };
+    let (north, south) = (centroid(cores[0]), centroid(cores[1]));
+    assert!((north.0 - -98.0).abs() < 1e-3 && (north.1 - 45.0).abs() < 1e-3);
+    assert!((south.0 - -95.0).abs() < 1e-3 && (south.1 - 42.0).abs() < 1e-3);
+
+    // The bands partition the grid, so polygon areas (holes excluded) add up
+    // to the area of the whole 7° x 7° extent
+    let total: f64 = features
+        .iter()
+        .map(|f| f.property("area_km2").and_then(|a| a.as_f64()).unwrap())
+        .sum();
+    let radius = 6371.0088_f64;
+    let expected =
+        radius * radius * 7f64.to_radians() * (47f64.to_radians().sin() - 40f64.to_radians().sin());
+    assert!(
+        (total - expected).abs() / expected < 1e-6,
+        "total {total} vs {expected}"
+    );
+}
+
+#[test]
+fn test_contour_report() {
+    use geo_marching_squares_rs::report::CellClass;
+
+    let points: Vec<Vec<GridPoint>> = (0..6)
+        .map(|row| {
+            (0..7)
+                .map(|col| {
+                    let value = if (2..4).contains(&row) && (2..5).contains(&col) {
+                        20.0
+                    } else {
+                        0.0
+                    };
+                    GridPoint::new(-100.0 + col as f64, 45.0 - row as f64, value)
+                })
+                .collect()
+        })
+        .collect();
+    let grid = GeoGrid::from_points(points).expect("Failed to create grid");
+    let thresholds = [-5.0, 10.0, 30.0, 50.0];
+
+    let (features, report) = grid
+        .isobands_with_report(&thresholds)
+        .expect("Failed to generate isobands");
+    assert_eq!(features.len(), grid.isobands(&thresholds).unwrap().len());
+    assert!(
+        report.is_clean(),
+        "unexpected failures: {:?}",
+        report.failures().collect::<Vec<_>>()
+    );
+
+    // Every band is reported, including the empty one
+    assert_eq!(report.bands.len(), 3);
+    let empty = &report.bands[2];
+    assert_eq!((empty.lower, empty.upper), (30.0, 50.0));
+    assert_eq!((empty.rings, empty.vertices), (0, 0));
+
+    for band in &report.bands {
+        assert_eq!(band.cells.total(), 5 * 6);
+    }
+    // Outer band: one polygon with the plateau as its hole
+    let outer = &report.bands[0];
+    assert_eq!((outer.rings, outer.polygons, outer.holes), (2, 1, 1));
+    assert!(outer.cells.get(CellClass::Square) > 0);
+    assert_eq!(report.rings(), 3);
+    assert!(report.vertices() > 0);
+    assert!(report.elapsed >= report.bands[0].elapsed);
+
+    let (lines, report) = grid
+        .isolines_with_report(&[10.0, 100.0])
+        .expect("Failed to generate isolines");
+    assert_eq!(lines.len(), 1);
+    assert_eq!(report.levels.len(), 2);
+    assert_eq!(report.levels[0].cells_crossed, 10);
+    assert!(report.levels[0].lines > 0 && report.levels[0].vertices >= 2 * report.levels[0].lines);
+    assert_eq!(report.levels[1].cells_crossed, 0);
+}
+
+#[test]
+fn test_contour_control() {
+    use geo_marching_squares_rs::control::{CancellationToken, ContourControl};
+    use geo_marching_squares_rs::Error;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    let points: Vec<Vec<GridPoint>> = (0..10)
+        .map(|row| {
+            (0..10)
+                .map(|col| {
+                    GridPoint::new(
+                        -100.0 + col as f64,
+                        45.0 - row as f64,
+                        (row * 10 + col) as f32,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    let grid = GeoGrid::from_points(points).expect("Failed to create grid");
+    let thresholds = [0.0, 20.0, 40.0, 60.0, 80.0, 100.0];
+
+    // Progress is reported once per band and the output is unchanged
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let token = CancellationToken::new();
+    let control = ContourControl::new()
+        .with_cancellation(token.clone())
+        .with_progress(move |p| {
+            assert!(p.completed >= 1 && p.completed <= p.total);
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+    let features = grid
+        .isobands_with_control(&thresholds, &control)
+        .expect("Failed to generate isobands");
+    let plain = grid.isobands(&thresholds).unwrap();
+    assert_eq!(features.len(), plain.len());
+    for (feature, expected) in features.iter().zip(&plain) {
+        assert_eq!(feature.properties, expected.properties);
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 5);
+
+    let lines = grid
+        .isolines_with_control(&[15.0, 55.0], &control)
+        .expect("Failed to generate isolines");
+    assert_eq!(lines.len(), 2);
+    assert_eq!(calls.load(Ordering::Relaxed), 7);
+
+    // A cancelled token stops both entry points
+    token.cancel();
+    assert!(matches!(
+        grid.isobands_with_control(&thresholds, &control),
+        Err(Error::Cancelled)
+    ));
+    assert!(matches!(
+        grid.isolines_with_control(&[15.0], &control),
+        Err(Error::Cancelled)
+    ));
+    assert_eq!(calls.load(Ordering::Relaxed), 7);
+
+    // So does an expired deadline
+    let expired = ContourControl::new().with_deadline(Instant::now());
+    assert!(matches!(
+        grid.isobands_with_control(&thresholds, &expired),
+        Err(Error::Cancelled)
+    ));
+}
+
+#[test]
+fn test_strict_mode_trace_failures() {
+    use geo_marching_squares_rs::{Error, MarchingSquaresConfig};
+
+    // Noisy values sitting on the thresholds break ring tracing in the 0-10 band
+    let values = [
+        [4.0, 13.0, 22.0, 1.0],
+        [10.0, 0.0, 3.0, 4.0],
+        [8.0, 13.0, 29.0, 17.0],
+        [29.0, 20.0, 25.0, 28.0],
+    ];
+    let points: Vec<Vec<GridPoint>> = values
+        .iter()
+        .enumerate()
+        .map(|(row, cols)| {
+            cols.iter()
+                .enumerate()
+                .map(|(col, &value)| GridPoint::new(-100.0 + col as f64, 45.0 - row as f64, value))
+                .collect()
+        })
+        .collect();
+    let mut grid = GeoGrid::from_points(points).expect("Failed to create grid");
+
+    // Non-strict: output is produced and the failures are reported as warnings
+    let (_, report) = grid
+        .isobands_with_report(&[0.0, 10.0])
+        .expect("Failed to generate isobands");
+    assert!(report.failures().any(|f| f.kind.is_incomplete()));
+
+    *grid.config_mut() = MarchingSquaresConfig::builder().with_strict(true).build();
+    match grid.isobands(&[0.0, 10.0, 20.0, 30.0]) {
+        Err(Error::TraceFailed {
+            lower,
+            upper,
+            row,
+            col,
+            ..
+        }) => {
+            assert_eq!((lower, upper), (0.0, 10.0));
+            assert!(row < 3 && col < 3);
+        }
+        other => panic!("expected a trace failure, got {other:?}"),
+    }
+    // Bands that trace cleanly are unaffected
+    assert_eq!(grid.isobands(&[20.0, 30.0]).unwrap().len(), 1);
+    assert!(grid.isobands_geo(&[0.0, 10.0]).is_err());
+}
+
+#[test]
+fn test_clip_bbox() {
+    use geo_marching_squares_rs::MarchingSquaresConfig;
+
+    // A radial field on a 1-degree grid, 20 x 20 cells
+    let points: Vec<Vec<GridPoint>> = (0..21)
+        .map(|row| {
+            (0..21)
+                .map(|col| {
+                    let (x, y) = (col as f64 - 10.0, row as f64 - 10.0);
+                    let value = (x * x + y * y).sqrt() as f32;
+                    GridPoint::new(-110.0 + col as f64, 50.0 - row as f64, value)
+                })
+                .collect()
+        })
+        .collect();
+    let mut grid = GeoGrid::from_points(points).expect("Failed to create grid");
+    let thresholds = [0.0, 3.0, 6.0, 9.0];
+    let (_, full) = grid.isobands_with_report(&thresholds).unwrap();
+
+    let bbox = (-100.5, 39.2, -96.3, 43.7);
+    *grid.config_mut() = MarchingSquaresConfig::builder()
+        .with_clip_bbox(bbox)
+        .build();
+    let (features, report) = grid.isobands_with_report(&thresholds).unwrap();
+
+    // Only the covering window of 5 x 5 cells is processed
+    assert!(full.bands.iter().all(|band| band.cells.total() == 400));
+    assert!(report.bands.iter().all(|band| band.cells.total() == 25));
+
+    let inside = |x: f64, y: f64| x >= bbox.0 && x <= bbox.2 && y >= bbox.1 && y <= bbox.3;
+    assert_eq!(features.len(), 2);
+    let mut on_edge = 0;
+    for feature in &features {
+        let Some(geojson::Value::MultiPolygon(polygons)) =
+            feature.geometry.as_ref().map(|g| &g.value)
+        else {
+            panic!("expected a MultiPolygon");
+        };
+        for ring in polygons.iter().flatten() {
+            assert_eq!(ring.first(), ring.last(), "ring is not closed");
+            for p in ring {
+                assert!(inside(p[0], p[1]), "{p:?} outside the box");
+                on_edge += usize::from(
+                    p[0] == bbox.0 || p[0] == bbox.2 || p[1] == bbox.1 || p[1] == bbox.3,
+                );
+            }
+        }
+    }
+    assert!(on_edge > 0, "no ring was closed along the box");
+
+    let lines = grid.isolines_geo(&[4.5]).unwrap();
+    assert!(lines[0]
+        .lines
+        .0
+        .iter()
+        .flat_map(|line| &line.0)
+        .all(|c| inside(c.x, c.y)));
+
+    // A box outside the grid gives no output; an empty box is rejected
+    *grid.config_mut() = MarchingSquaresConfig::builder()
+        .with_clip_bbox((0.0, 0.0, 1.0, 1.0))
+        .build();
+    assert!(grid.isobands(&thresholds).unwrap().is_empty());
+    *grid.config_mut() = MarchingSquaresConfig::builder()
+        .with_clip_bbox((-99.0, 40.0, -99.0, 41.0))
+        .build();
+    assert!(grid.isobands(&thresholds).is_err());
+}
+
+#[test]
+fn test_clip_bbox_concave_band() {
+    use geo_marching_squares_rs::MarchingSquaresConfig;
+    use geo_types::LineString;
+
+    // 7 x 7 points at (col, -row), 1.0 on the marked points and 0.0 elsewhere
+    let grid_of = |marked: &dyn Fn(usize, usize) -> bool| {
+        let points: Vec<Vec<GridPoint>> = (0..7)
+            .map(|row| {
+                (0..7)
+                    .map(|col| {
+                        GridPoint::new(
+                            col as f64,
+                            -(row as f64),
+                            if marked(row, col) { 1.0 } else { 0.0 },
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut grid = GeoGrid::from_points(points).expect("Failed to create grid");
+        *grid.config_mut() = MarchingSquaresConfig::builder()
+            .with_clip_bbox((0.0, -3.2, 6.0, 0.0))
+            .build();
+        grid
+    };
+    // No vertex is visited twice, as a zero-width bridge along the box edge would
+    let distinct_vertices = |ring: &LineString<f64>| {
+        let mut points: Vec<(f64, f64)> = ring.0.iter().map(|c| (c.x, c.y)).collect();
+        points.dedup();
+        points.pop();
+        points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        points.windows(2).all(|pair| pair[0] != pair[1])
+    };
+
+    // A U opening upwards, its bottom just below the box: the box cuts its two
+    // arms apart
+    let u_shape = grid_of(&|row, col| {
+        (1..=4).contains(&row) && (col == 1 || col == 5) || row == 4 && (1..=5).contains(&col)
+    });
+    let bands = u_shape.isobands_geo(&[0.5, 1.5]).unwrap();
+    assert_eq!(bands.len(), 1);
+    let polygons = &bands[0].polygons.0;
+    assert_eq!(
+        polygons.len(),
+        2,
+        "the arms were not split into separate polygons"
+    );
+    for polygon in polygons {
+        let xs: Vec<f64> = polygon.exterior().0.iter().map(|c| c.x).collect();
+        assert!(
+            xs.iter().all(|&x| x < 3.0) || xs.iter().all(|&x| x > 3.0),
+            "a ring bridges the arms"
+        );
+        assert!(distinct_vertices(polygon.exterior()));
+        assert!(polygon
+            .exterior()
+            .0
+            .iter()
+            .all(|c| c.y >= -3.2 && c.y <= 0.0));
+    }
+
+    // A square annulus whose hole straddles the box edge: the hole opens into
+    // a notch of the exterior instead of touching it
+    let annulus = grid_of(&|row, col| {
+        (1..=4).contains(&row)
+            && (1..=5).contains(&col)
+            && (row == 1 || row == 4 || col == 1 || col == 5)
+    });
+    let bands = annulus.isobands_geo(&[0.5, 1.5]).unwrap();
+    let polygons = &bands[0].polygons.0;
+    assert_eq!(polygons.len(), 1);
+    assert!(polygons[0].interiors().is_empty());
+    let exterior = polygons[0].exterior();
+    assert!(distinct_vertices(exterior));
+    assert!(
+        exterior
+            .0
+            .iter()
+            .any(|c| c.x > 2.0 && c.x < 4.0 && c.y > -3.2),
+        "the notch is missing"
+    );
+}
+
+#[test]
+fn test_clip_mask() {
+    use geo_marching_squares_rs::mask::ClipMask;
+    use geo_marching_squares_rs::MarchingSquaresConfig;
+    use geo_types::{polygon, LineString};
+
+    // The radial field of test_clip_bbox, centered on (-100, 40)
+    let points: Vec<Vec<GridPoint>> = (0..21)
+        .map(|row| {
+            (0..21)
+                .map(|col| {
+                    let (x, y) = (col as f64 - 10.0, row as f64 - 10.0);
+                    let value = (x * x + y * y).sqrt() as f32;
+                    GridPoint::new(-110.0 + col as f64, 50.0 - row as f64, value)
+                })
+                .collect()
+        })
+        .collect();
+    let mut grid = GeoGrid::from_points(points).expect("Failed to create grid");
+
+    // An irregular quadrilateral with a square hole around the center
+    let hole = (-100.6, 39.3, -99.1, 40.4);
+    let mask = polygon![
+        exterior: [
+            (x: -105.3, y: 36.1), (x: -94.6, y: 38.2), (x: -97.1, y: 45.4), (x: -104.2, y: 44.1),
+        ],
+        interiors: [[
+            (x: hole.0, y: hole.1), (x: hole.2, y: hole.1), (x: hole.2, y: hole.3), (x: hole.0, y: hole.3),
+        ]],
+    ];
+    let area = |ring: &LineString<f64>| {
+        ring.lines()
+            .map(|l| l.start.x * l.end.y - l.end.x * l.start.y)
+            .sum::<f64>()
+            / 2.0
+    };
+    let mask_area = area(mask.exterior()).abs() - area(&mask.interiors()[0]).abs();
+
+    *grid.config_mut() = MarchingSquaresConfig::builder()
+        .with_clip_mask(ClipMask::new(&mask.into()).unwrap())
+        .build();
+    // The bands cover every grid value
+    let thresholds = [0.0, 5.0, 10.0, 15.0];
+    let (_, report) = grid.isobands_with_report(&thresholds).unwrap();
+    let bands = grid.isobands_geo(&thresholds).unwrap();
+
+    // Cells outside the mask are skipped, out of the 120 covering its bounds
+    assert!(report.bands.iter().all(|band| band.cells.total() < 100));
+    assert!(report.bands.iter().all(|band| band.failures.is_empty()));
+
+    // The bands tile the mask, hole excluded
+    let mut total = 0.0;
+    let mut holes = 0;
+    for band in &bands {
+        for polygon in &band.polygons {
+            total += area(polygon.exterior()).abs();
+            total -= polygon
+                .interiors()
+                .iter()
+                .map(|ring| area(ring).abs())
+                .sum::<f64>();
+            holes += polygon.interiors().len();
+        }
+    }
+    assert!(
+        (total - mask_area).abs() < 1e-3 * mask_area,
+        "{total} != {mask_area}"
+    );
+    assert!(holes > 0, "the mask hole was lost");
+
+    let inside_hole = |x: f64, y: f64| {
+        x > hole.0 + 1e-6 && x < hole.2 - 1e-6 && y > hole.1 + 1e-6 && y < hole.3 - 1e-6
+    };
+    let lines = grid.isolines_geo(&[0.5, 4.5]).unwrap();
+    assert!(lines
+        .iter()
+        .flat_map(|line| &line.lines.0)
+        .flat_map(|line| &line.0)
+        .all(|c| !inside_hole(c.x, c.y)));
+    assert!(!lines[1].lines.0.is_empty());
+}